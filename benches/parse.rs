@@ -0,0 +1,64 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use language_tags::LanguageTag;
+use gtfs_translations::{from_reader, translate_raw_translations, ParseOptions, RawTranslation, StopFields, TranslatableField, TranslationKey};
+
+const ROWS: usize = 100_000;
+
+fn rows() -> Vec<RawTranslation> {
+    (0..ROWS).map(|i| RawTranslation {
+        table_name: "stops".to_string(),
+        field_name: "stop_name".to_string(),
+        language: ["fr", "de", "ja", "zh-Hant"][i % 4].to_string(),
+        translation: format!("Station {}", i),
+        record_id: Some(format!("stop_{}", i / 4)),
+        ..RawTranslation::default()
+    }).collect()
+}
+
+fn csv(rows: &[RawTranslation]) -> String {
+    let mut csv = String::from("table_name,field_name,language,translation,record_id,record_sub_id,field_value\n");
+
+    for row in rows {
+        csv.push_str(&format!("{},{},{},{},{},,\n", row.table_name, row.field_name, row.language, row.translation, row.record_id.as_deref().unwrap_or_default()));
+    }
+
+    csv
+}
+
+fn parse(c: &mut Criterion) {
+    let rows = rows();
+    let csv = csv(&rows);
+
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Elements(ROWS as u64));
+
+    group.bench_function("translate_raw_translations", |b| b.iter(|| translate_raw_translations(black_box(rows.clone()))));
+    group.bench_function("from_reader", |b| b.iter(|| from_reader(black_box(csv.as_bytes()), &ParseOptions::default()).unwrap()));
+
+    let sized = ParseOptions { expected_rows: Some(ROWS), ..ParseOptions::default() };
+    group.bench_function("from_reader_expected_rows", |b| b.iter(|| from_reader(black_box(csv.as_bytes()), &sized).unwrap()));
+
+    #[cfg(feature = "rayon")]
+    group.bench_function("from_reader_parallel", |b| b.iter(|| gtfs_translations::from_reader_parallel(black_box(csv.as_bytes()), &ParseOptions::default()).unwrap()));
+
+    group.finish();
+}
+
+// Run with and without --features fast-hash to compare hashers
+fn lookup(c: &mut Criterion) {
+    let result = translate_raw_translations(rows());
+    let field = TranslatableField::Stops(StopFields::Name);
+    let fr = LanguageTag::parse("fr").unwrap();
+    let keys = (0..1000).map(|i| TranslationKey::Record(format!("stop_{}", i * 7))).collect::<Vec<TranslationKey>>();
+
+    let mut group = c.benchmark_group("lookup");
+    group.throughput(Throughput::Elements(keys.len() as u64));
+
+    group.bench_function("get", |b| b.iter(|| keys.iter().filter(|key| result.get(&field, key, &fr).is_some()).count()));
+    group.bench_function("resolve", |b| b.iter(|| (0..1000).filter(|i| result.resolve(&field, Some(&format!("stop_{}", i)), None, Some("Station"), &fr).is_some()).count()));
+
+    group.finish();
+}
+
+criterion_group!(benches, parse, lookup);
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+#![no_main]
+
+use gtfs_translations::{from_reader, ParseOptions};
+use libfuzzer_sys::fuzz_target;
+
+// Any bytes an agency uploads must come back as translations or an error, never a panic
+fuzz_target!(|data: &[u8]| {
+    let options = ParseOptions {
+        max_total_bytes: Some(1 << 20),
+        ..ParseOptions::default()
+    };
+
+    let _ = from_reader(data, &options);
+});
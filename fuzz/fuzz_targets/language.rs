@@ -0,0 +1,23 @@
+#![no_main]
+
+use gtfs_translations::{best_language, language_matches, normalize_language};
+use language_tags::LanguageTag;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: (&str, &str)| {
+    let (requested, available) = data;
+
+    let requested = normalize_language(requested);
+    let available = available.split(',').filter_map(normalize_language).collect::<Vec<LanguageTag>>();
+
+    if let Some(requested) = requested {
+        for language in &available {
+            let _ = language_matches(&requested, language);
+            let _ = language_matches(language, &requested);
+        }
+
+        if let Some(found) = best_language(&requested, &available) {
+            assert!(requested.as_str().to_ascii_lowercase().starts_with(&found.as_str().to_ascii_lowercase()));
+        }
+    }
+});
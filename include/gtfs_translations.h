@@ -0,0 +1,35 @@
+#ifndef GTFS_TRANSLATIONS_H
+#define GTFS_TRANSLATIONS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Opaque parsed translations.txt. Free with gtfs_translations_free. */
+typedef struct TranslationResult TranslationResult;
+
+/* Return NULL on failure, see gtfs_translations_last_error. */
+TranslationResult *gtfs_translations_parse(const uint8_t *data, size_t len);
+TranslationResult *gtfs_translations_parse_file(const char *path);
+void gtfs_translations_free(TranslationResult *result);
+
+size_t gtfs_translations_len(const TranslationResult *result);
+
+/* record_id, record_sub_id and original_value may be NULL. Returns NULL when there is no translation,
+   otherwise a string to free with gtfs_translations_string_free. */
+char *gtfs_translations_resolve(const TranslationResult *result, const char *table_name, const char *field_name,
+                                const char *record_id, const char *record_sub_id, const char *original_value,
+                                const char *language);
+void gtfs_translations_string_free(char *string);
+
+/* Why the last parse on this thread failed, or NULL. Owned by the library. */
+const char *gtfs_translations_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
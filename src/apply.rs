@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use gtfs_structures::{Agency, FeedInfo, Gtfs, RawGtfs, RawStopTime, RawTrip, Route, Stop};
+use language_tags::LanguageTag;
+use crate::row::RowTranslator;
+use crate::{AgencyFields, FeedInfoFields, RouteFields, StopFields, StopTimeFields, TranslatableField, TripFields, TranslationResult};
+
+// Walks the structs of a feed, translating their values through the RowTranslator that translate_row uses
+struct Applier<'a> {
+    translator: RowTranslator<'a>,
+}
+
+impl Applier<'_> {
+    fn string(&mut self, field: TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, value: &mut String) {
+        self.translator.replace(&field, record_id, record_sub_id, value);
+    }
+
+    fn option(&mut self, field: TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, value: &mut Option<String>) {
+        self.translator.replace_option(&field, record_id, record_sub_id, value);
+    }
+
+    fn stop(&mut self, stop: &mut Stop) {
+        let id = Some(stop.id.as_str());
+        self.option(TranslatableField::Stops(StopFields::Name), id, None, &mut stop.name);
+        self.option(TranslatableField::Stops(StopFields::Code), id, None, &mut stop.code);
+        self.option(TranslatableField::Stops(StopFields::TtsName), id, None, &mut stop.tts_name);
+        self.option(TranslatableField::Stops(StopFields::PlatformCode), id, None, &mut stop.platform_code);
+        self.option(TranslatableField::Stops(StopFields::Desc), id, None, &mut stop.description);
+    }
+
+    fn route(&mut self, route: &mut Route) {
+        let id = Some(route.id.as_str());
+        self.option(TranslatableField::Routes(RouteFields::LongName), id, None, &mut route.long_name);
+        self.option(TranslatableField::Routes(RouteFields::ShortName), id, None, &mut route.short_name);
+        self.option(TranslatableField::Routes(RouteFields::Desc), id, None, &mut route.desc);
+        self.option(TranslatableField::Routes(RouteFields::Url), id, None, &mut route.url);
+    }
+
+    fn trip(&mut self, id: &str, headsign: &mut Option<String>, short_name: &mut Option<String>) {
+        self.option(TranslatableField::Trips(TripFields::Headsign), Some(id), None, headsign);
+        self.option(TranslatableField::Trips(TripFields::ShortName), Some(id), None, short_name);
+    }
+
+    // stop_times rows are referenced by trip_id and stop_sequence
+    fn stop_headsign(&mut self, trip_id: &str, stop_sequence: u16, headsign: &mut Option<String>) {
+        self.option(TranslatableField::StopTimes(StopTimeFields::Headsign), Some(trip_id), Some(&stop_sequence.to_string()), headsign);
+    }
+
+    fn agency(&mut self, agency: &mut Agency) {
+        let id = agency.id.clone();
+        self.string(TranslatableField::Agency(AgencyFields::Name), id.as_deref(), None, &mut agency.name);
+        self.string(TranslatableField::Agency(AgencyFields::Url), id.as_deref(), None, &mut agency.url);
+        self.option(TranslatableField::Agency(AgencyFields::FareUrl), id.as_deref(), None, &mut agency.fare_url);
+    }
+
+    // feed_info has no id, so only value keys can match it
+    fn feed_info(&mut self, feed_info: &mut FeedInfo) {
+        self.string(TranslatableField::FeedInfo(FeedInfoFields::PublisherName), None, None, &mut feed_info.name);
+    }
+
+    fn raw_trip(&mut self, trip: &mut RawTrip) {
+        self.trip(&trip.id.clone(), &mut trip.trip_headsign, &mut trip.trip_short_name);
+    }
+
+    fn raw_stop_time(&mut self, stop_time: &mut RawStopTime) {
+        self.stop_headsign(&stop_time.trip_id.clone(), stop_time.stop_sequence, &mut stop_time.stop_headsign);
+    }
+}
+
+// Rewrites the feed into one language, with the precedence of TranslationResult::resolve and the language fallback
+// of fallback_languages, so fr-CA takes the fr translations.
+// Values without a translation are left as they are. Returns how many values were replaced.
+pub fn apply_gtfs(gtfs: &mut Gtfs, result: &TranslationResult, language: &LanguageTag) -> usize {
+    let mut applier = Applier { translator: RowTranslator::new(result, language) };
+
+    for stop in gtfs.stops.values_mut() {
+        applier.stop(Arc::make_mut(stop));
+    }
+
+    // stop times share the stops through Arc, point them at the translated ones
+    let stops = gtfs.stops.iter().map(|(id, stop)| (id.clone(), stop.clone())).collect::<HashMap<String, Arc<Stop>>>();
+
+    for trip in gtfs.trips.values_mut() {
+        let trip_id = trip.id.clone();
+        applier.trip(&trip_id, &mut trip.trip_headsign, &mut trip.trip_short_name);
+
+        for stop_time in trip.stop_times.iter_mut() {
+            applier.stop_headsign(&trip_id, stop_time.stop_sequence, &mut stop_time.stop_headsign);
+
+            if let Some(stop) = stops.get(&stop_time.stop.id) {
+                stop_time.stop = stop.clone();
+            }
+        }
+    }
+
+    gtfs.routes.values_mut().for_each(|route| applier.route(route));
+    gtfs.agencies.iter_mut().for_each(|agency| applier.agency(agency));
+    gtfs.feed_info.iter_mut().for_each(|feed_info| applier.feed_info(feed_info));
+
+    applier.translator.replaced
+}
+
+// Same as apply_gtfs for a RawGtfs. Files that failed to parse are skipped.
+pub fn apply_raw_gtfs(gtfs: &mut RawGtfs, result: &TranslationResult, language: &LanguageTag) -> usize {
+    let mut applier = Applier { translator: RowTranslator::new(result, language) };
+
+    if let Ok(stops) = &mut gtfs.stops {
+        stops.iter_mut().for_each(|stop| applier.stop(stop));
+    }
+    if let Ok(routes) = &mut gtfs.routes {
+        routes.iter_mut().for_each(|route| applier.route(route));
+    }
+    if let Ok(trips) = &mut gtfs.trips {
+        trips.iter_mut().for_each(|trip| applier.raw_trip(trip));
+    }
+    if let Ok(stop_times) = &mut gtfs.stop_times {
+        stop_times.iter_mut().for_each(|stop_time| applier.raw_stop_time(stop_time));
+    }
+    if let Ok(agencies) = &mut gtfs.agencies {
+        agencies.iter_mut().for_each(|agency| applier.agency(agency));
+    }
+    if let Some(Ok(feed_info)) = &mut gtfs.feed_info {
+        feed_info.iter_mut().for_each(|feed_info| applier.feed_info(feed_info));
+    }
+
+    applier.translator.replaced
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use gtfs_structures::{StopTime, Trip};
+    use crate::test_util::translations;
+
+    #[test]
+    fn rewrites_gtfs_in_place() {
+        let result = translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,fr,Place,,,Square\n\
+            stop_times,stop_headsign,fr,Centre,trip_1,2,\n\
+            trips,trip_headsign,fr,Aéroport,,,Airport\n\
+            agency,agency_name,fr,Transports,agency_1,,\n");
+
+        let mut gtfs = Gtfs::default();
+        let station = Arc::new(Stop { id: "stop_1".to_string(), name: Some("Station".to_string()), ..Stop::default() });
+        gtfs.stops.insert("stop_1".to_string(), station.clone());
+        gtfs.stops.insert("stop_2".to_string(), Arc::new(Stop { id: "stop_2".to_string(), name: Some("Square".to_string()), ..Stop::default() }));
+        gtfs.trips.insert("trip_1".to_string(), Trip {
+            id: "trip_1".to_string(),
+            trip_headsign: Some("Airport".to_string()),
+            stop_times: vec![StopTime { stop: station, stop_sequence: 2, stop_headsign: Some("Downtown".to_string()), ..StopTime::default() }],
+            ..Trip::default()
+        });
+        gtfs.agencies.push(Agency { id: Some("agency_1".to_string()), name: "Transit".to_string(), ..Agency::default() });
+
+        let fr = LanguageTag::parse("fr").unwrap();
+        assert_eq!(apply_gtfs(&mut gtfs, &result, &fr), 5);
+
+        let trip = &gtfs.trips["trip_1"];
+        assert_eq!(gtfs.stops["stop_1"].name.as_deref(), Some("Gare"));
+        assert_eq!(gtfs.stops["stop_2"].name.as_deref(), Some("Place"));
+        assert_eq!(trip.trip_headsign.as_deref(), Some("Aéroport"));
+        assert_eq!(trip.stop_times[0].stop_headsign.as_deref(), Some("Centre"));
+        assert_eq!(trip.stop_times[0].stop.name.as_deref(), Some("Gare"));
+        assert_eq!(gtfs.agencies[0].name, "Transports");
+    }
+
+    #[test]
+    fn falls_back_to_shorter_languages() {
+        let result = translations("stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,fr-CA,Gare du Canada,stop_2,,\n");
+        let mut gtfs = Gtfs::default();
+        for id in ["stop_1", "stop_2", "stop_3"] {
+            gtfs.stops.insert(id.to_string(), Arc::new(Stop { id: id.to_string(), name: Some("Station".to_string()), ..Stop::default() }));
+        }
+
+        assert_eq!(apply_gtfs(&mut gtfs, &result, &LanguageTag::parse("fr-CA").unwrap()), 2);
+
+        let names = ["stop_1", "stop_2", "stop_3"].map(|id| gtfs.stops[id].name.clone().unwrap());
+        assert_eq!(names, ["Gare", "Gare du Canada", "Station"]);
+    }
+}
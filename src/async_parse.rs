@@ -0,0 +1,155 @@
+use std::error::Error;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader, ReadBuf};
+use crate::decompress::compression_of;
+use crate::instrument;
+use crate::limits::{check_record, surface_limit, LimitExceeded};
+use crate::parse::{index_record, normalize_headers, resolve_dialect, sniff_head, RecordConverter};
+use crate::transcode::is_utf8_label;
+use crate::{ParseOptions, ParseStats, RawTranslationIndexer, RowError, TranslationResult};
+
+// LimitedReader for async input. A read may overshoot the cap by one buffer before it fails.
+struct AsyncLimitedReader<R> {
+    inner: R,
+    read: u64,
+    max_total_bytes: Option<u64>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncLimitedReader<R> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        self.read += (buf.filled().len() - filled) as u64;
+
+        match self.max_total_bytes {
+            Some(max_total_bytes) if self.read > max_total_bytes => Poll::Ready(Err(io::Error::other(LimitExceeded::TotalBytes { max_total_bytes }))),
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+// Limit errors raised by the reader come back inside csv_async io errors, as surface_limit does for csv
+fn surface_async_limit(err: csv_async::Error) -> Box<dyn Error> {
+    let limit = match err.kind() {
+        csv_async::ErrorKind::Io(err) => err.get_ref().and_then(|inner| inner.downcast_ref::<LimitExceeded>()).cloned(),
+        _ => None,
+    };
+
+    match limit {
+        Some(limit) => Box::new(limit),
+        None => Box::new(err),
+    }
+}
+
+fn to_csv_record(record: &csv_async::StringRecord) -> csv::StringRecord {
+    let mut converted = csv::StringRecord::from(record.iter().collect::<Vec<&str>>());
+
+    if let Some(position) = record.position() {
+        let mut converted_position = csv::Position::new();
+        converted_position.set_byte(position.byte()).set_line(position.line()).set_record(position.record());
+        converted.set_position(Some(converted_position));
+    }
+
+    converted
+}
+
+// Same as from_reader, reading records as they arrive instead of buffering the whole file. Input has to be
+// uncompressed UTF-8, other encodings and compressed files are refused rather than misread.
+pub async fn from_async_reader<R: AsyncRead + Unpin + Send>(reader: R, options: &ParseOptions) -> Result<(TranslationResult, ParseStats), Box<dyn Error>> {
+    let started = Instant::now();
+    let mut reader = BufReader::new(AsyncLimitedReader { inner: reader, read: 0, max_total_bytes: options.max_total_bytes });
+    let mut stats = ParseStats::default();
+
+    if let Some(label) = options.encoding.as_deref().filter(|label| !is_utf8_label(label)) {
+        return Err(format!("from_async_reader only reads UTF-8, use from_reader for {} input", label).into());
+    }
+
+    let head = reader.fill_buf().await.map_err(|err| surface_limit(err.into()))?;
+    if let Some(compression) = compression_of(head) {
+        return Err(format!("from_async_reader does not decompress input, use from_reader for {:?} input", compression).into());
+    }
+
+    let (delimiter, skip) = sniff_head(head, options, &mut stats);
+    reader.consume(skip);
+
+    let mut rdr = csv_async::AsyncReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(options.flexible)
+        .quoting(options.quoting)
+        .escape(options.escape)
+        .create_reader(reader);
+    let headers = normalize_headers(&to_csv_record(rdr.headers().await.map_err(surface_async_limit)?), options, &mut stats);
+
+    let dialect = resolve_dialect(&headers, options)?;
+    let converter = RecordConverter::new(dialect, &headers, options)?;
+    stats.dialect = Some(dialect);
+    stats.unknown_columns = converter.unknown_columns();
+    let mut indexer = RawTranslationIndexer::with_capacity(options.expected_rows.unwrap_or_default()).with_provenance(options.provenance.clone());
+    let mut record = csv_async::StringRecord::new();
+
+    loop {
+        match rdr.read_record(&mut record).await {
+            Ok(false) => break,
+            Ok(true) => {
+                let record = to_csv_record(&record);
+                check_record(&record, options, stats.rows_read)?;
+                index_record(&converter, Ok(&record), &mut indexer, &mut stats);
+            }
+            Err(err) if err.is_io_error() => return Err(surface_async_limit(err)),
+            Err(err) => index_record(&converter, Err(RowError {
+                line: err.position().map(|position| position.line()),
+                byte: err.position().map(|position| position.byte()),
+                message: err.to_string(),
+            }), &mut indexer, &mut stats),
+        }
+    }
+
+    stats.duplicate_rows = indexer.duplicates;
+    let result = indexer.finish();
+    instrument::parsed(&stats, result.translations.len(), started.elapsed());
+
+    Ok((result, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+    use crate::{from_reader, Dialect, LimitExceeded};
+
+    fn data() -> String {
+        format!("{}stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,de\n\
+            trips,trip_headsign,de,Innenstadt,,,Downtown\n", HEADER)
+    }
+
+    #[tokio::test]
+    async fn matches_sync_parser() {
+        let data = data();
+        let (result, stats) = from_async_reader(data.as_bytes(), &ParseOptions::default()).await.unwrap();
+        let (expected, expected_stats) = from_reader(data.as_bytes(), &ParseOptions::default()).unwrap();
+
+        assert_eq!(result.translations, expected.translations);
+        assert_eq!(stats, expected_stats);
+        assert_eq!(stats, ParseStats { dialect: Some(Dialect::Standard), rows_read: 3, rows_skipped: 1, delimiter: Some(b','), errors: stats.errors.clone(), ..ParseStats::default() });
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_byte_limit() {
+        let limited = ParseOptions { max_total_bytes: Some(100), ..ParseOptions::default() };
+        let err = from_async_reader(data().as_bytes(), &limited).await.unwrap_err();
+
+        assert_eq!(err.downcast_ref::<LimitExceeded>(), Some(&LimitExceeded::TotalBytes { max_total_bytes: 100 }));
+    }
+
+    #[tokio::test]
+    async fn refuses_other_encodings_and_compressed_input() {
+        let latin1 = ParseOptions { encoding: Some("latin1".to_string()), ..ParseOptions::default() };
+
+        assert!(from_async_reader(data().as_bytes(), &latin1).await.is_err());
+        assert!(from_async_reader(&[0x1f, 0x8b, 0x08, 0x00][..], &ParseOptions::default()).await.is_err());
+    }
+}
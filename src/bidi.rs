@@ -0,0 +1,193 @@
+use std::borrow::Cow;
+use language_tags::LanguageTag;
+use crate::validate::sort_notices;
+use crate::{enum_to_table_and_field, Notice, Severity, TranslationResult};
+
+const FSI: char = '\u{2068}';
+const PDI: char = '\u{2069}';
+
+const RTL_SCRIPTS: [&str; 8] = ["Arab", "Hebr", "Thaa", "Syrc", "Nkoo", "Adlm", "Mand", "Samr"];
+// Languages written right to left unless their tag names another script, as in ku-Latn
+const RTL_LANGUAGES: [&str; 14] = ["ar", "arc", "ckb", "dv", "fa", "he", "iw", "ks", "ps", "sd", "syr", "ug", "ur", "yi"];
+
+pub fn is_rtl(language: &LanguageTag) -> bool {
+    match language.script() {
+        Some(script) => RTL_SCRIPTS.iter().any(|rtl| rtl.eq_ignore_ascii_case(script)),
+        None => RTL_LANGUAGES.contains(&language.primary_language().to_lowercase().as_str()),
+    }
+}
+
+fn is_rtl_char(character: char) -> bool {
+    matches!(character, '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFC}' | '\u{10800}'..='\u{10FFF}' | '\u{1E800}'..='\u{1EFFF}')
+}
+
+fn is_ltr_char(character: char) -> bool {
+    character.is_alphabetic() && !is_rtl_char(character)
+}
+
+// What unbalanced or out of place controls a text has. Embeddings and overrides are closed by PDF, isolates by PDI.
+fn bidi_problems(text: &str) -> (bool, bool) {
+    let mut embeddings = 0;
+    let mut isolates = 0;
+    let mut unbalanced = false;
+    let mut overrides = false;
+
+    for character in text.chars() {
+        match character {
+            '\u{202A}' | '\u{202B}' => embeddings += 1,
+            '\u{202D}' | '\u{202E}' => {
+                embeddings += 1;
+                overrides = true;
+            }
+            '\u{202C}' if embeddings == 0 => unbalanced = true,
+            '\u{202C}' => embeddings -= 1,
+            '\u{2066}' | '\u{2067}' | FSI => isolates += 1,
+            PDI if isolates == 0 => unbalanced = true,
+            PDI => isolates -= 1,
+            _ => {}
+        }
+    }
+
+    (unbalanced || embeddings != 0 || isolates != 0, overrides)
+}
+
+// Wraps each run of left to right text inside right to left text, such as the 45A of a headsign, in FSI and PDI
+// so it keeps its own order and the punctuation around it stays put. Runs of digits alone are left as they are,
+// as are texts that already use isolates.
+pub fn isolate_ltr_runs(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(is_rtl_char) || text.contains([FSI, PDI, '\u{2066}', '\u{2067}']) {
+        return Cow::Borrowed(text);
+    }
+
+    let characters = text.char_indices().collect::<Vec<(usize, char)>>();
+    let mut isolated = String::with_capacity(text.len() + 8);
+    let mut copied = 0;
+    let mut index = 0;
+
+    while index < characters.len() {
+        let (start, character) = characters[index];
+        if !is_ltr_char(character) && !character.is_ascii_digit() {
+            index += 1;
+            continue;
+        }
+
+        // a run goes on over neutral characters as long as it ends on a letter or digit
+        let mut end = index;
+        let mut scan = index;
+        while scan < characters.len() && !is_rtl_char(characters[scan].1) {
+            if is_ltr_char(characters[scan].1) || characters[scan].1.is_ascii_digit() {
+                end = scan;
+            }
+            scan += 1;
+        }
+
+        let run_end = characters.get(end + 1).map_or(text.len(), |(byte, _)| *byte);
+        if characters[index..=end].iter().any(|(_, character)| is_ltr_char(*character)) {
+            isolated.push_str(&text[copied..start]);
+            isolated.push(FSI);
+            isolated.push_str(&text[start..run_end]);
+            isolated.push(PDI);
+            copied = run_end;
+        }
+        index = end + 1;
+    }
+
+    match copied {
+        0 => Cow::Borrowed(text),
+        _ => {
+            isolated.push_str(&text[copied..]);
+            Cow::Owned(isolated)
+        }
+    }
+}
+
+// Looks at the translations into right to left languages for embeddings and isolates that are never closed or
+// close nothing, for overrides, which reverse text outright, and for left to right runs that are not isolated
+pub fn check_bidi(result: &TranslationResult) -> Vec<Notice> {
+    let mut notices = vec![];
+
+    for (lookup, translation) in result.translations.iter().filter(|(lookup, _)| is_rtl(&lookup.language)) {
+        let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+        let (unbalanced, overrides) = bidi_problems(translation);
+
+        if unbalanced {
+            notices.push(Notice::about(lookup, "bidi_unbalanced", Severity::Warning,
+                format!("{}.{} {} translation {:?} has unbalanced bidi controls", table_name, field_name, lookup.language, translation)));
+        }
+
+        if overrides {
+            notices.push(Notice::about(lookup, "bidi_override", Severity::Warning,
+                format!("{}.{} {} translation {:?} uses a bidi override", table_name, field_name, lookup.language, translation)));
+        }
+
+        if let Cow::Owned(isolated) = isolate_ltr_runs(translation) {
+            notices.push(Notice {
+                suggestion: Some(isolated),
+                ..Notice::about(lookup, "bidi_unisolated", Severity::Info,
+                    format!("{}.{} {} translation {:?} mixes in left to right text without isolating it", table_name, field_name, lookup.language, translation))
+            });
+        }
+    }
+
+    sort_notices(&mut notices);
+    notices
+}
+
+impl TranslationResult {
+    // Applies isolate_ltr_runs to every translation into a right to left language, returning how many changed
+    pub fn isolate_bidi(&mut self) -> usize {
+        let mut changed = 0;
+
+        for (_, translation) in self.translations.iter_mut().filter(|(lookup, _)| is_rtl(&lookup.language)) {
+            if let Cow::Owned(isolated) = isolate_ltr_runs(translation) {
+                *translation = isolated.into();
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{TranslatableField, TripFields};
+
+    fn headsigns() -> TranslationResult {
+        translations("trips,trip_headsign,ar,\u{0627}\u{0644}\u{062E}\u{0637} 45A - \u{0627}\u{0644}\u{0645}\u{0631}\u{0643}\u{0632},trip_1,,\n\
+            trips,trip_headsign,he,\u{202B}\u{05DE}\u{05E8}\u{05DB}\u{05D6} 12,trip_2,,\n\
+            trips,trip_headsign,fr,\u{202E}Centre,trip_3,,\n")
+    }
+
+    #[test]
+    fn flags_unisolated_and_unbalanced_text() {
+        let codes = check_bidi(&headsigns()).iter().map(|notice| (notice.code.clone(), notice.language.clone().unwrap())).collect::<Vec<_>>();
+
+        // the override in the fr headsign is left alone, as fr is not written right to left
+        assert_eq!(codes, vec![("bidi_unisolated".to_string(), "ar".to_string()), ("bidi_unbalanced".to_string(), "he".to_string())]);
+    }
+
+    #[test]
+    fn isolates_left_to_right_runs() {
+        assert_eq!(isolate_ltr_runs("\u{0645}\u{062D}\u{0637}\u{0629} Bus M1, \u{0634}\u{0627}\u{0631}\u{0639}"),
+            "\u{0645}\u{062D}\u{0637}\u{0629} \u{2068}Bus M1\u{2069}, \u{0634}\u{0627}\u{0631}\u{0639}");
+        assert!(matches!(isolate_ltr_runs("Bus M1"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn isolates_translations_in_place() {
+        let mut isolated = headsigns();
+
+        assert_eq!(isolated.isolate_bidi(), 1);
+        assert_eq!(isolated.resolve(&TranslatableField::Trips(TripFields::Headsign), Some("trip_1"), None, None, &LanguageTag::parse("ar").unwrap()),
+            Some("\u{0627}\u{0644}\u{062E}\u{0637} \u{2068}45A\u{2069} - \u{0627}\u{0644}\u{0645}\u{0631}\u{0643}\u{0632}"));
+    }
+
+    #[test]
+    fn knows_right_to_left_languages() {
+        assert!(is_rtl(&LanguageTag::parse("fa-IR").unwrap()));
+        assert!(!is_rtl(&LanguageTag::parse("ku-Latn").unwrap()));
+    }
+}
@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use clap::{Args, ValueEnum};
+use language_tags::LanguageTag;
+use gtfs_translations::export::{json_bundle, po, wide_csv, xliff, JsonBundleOptions};
+use gtfs_translations::writer::to_translations_csv;
+use gtfs_translations::{normalize_language, Dialect, ParseOptions, TranslationResult};
+use crate::load;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InputFormat {
+    // Whichever csv dialect the header row looks like
+    Auto,
+    Standard,
+    Legacy,
+    Wide,
+    Xliff,
+    Po,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Standard,
+    Wide,
+    Xliff,
+    Po,
+    Json,
+    #[cfg(feature = "fluent")]
+    Fluent,
+}
+
+#[derive(Args)]
+pub struct ConvertArgs {
+    #[arg(help = "File to convert, or a GTFS zip for the csv formats")]
+    path: PathBuf,
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    from: InputFormat,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Standard)]
+    to: OutputFormat,
+    #[arg(short, long, help = "Write here instead of to stdout")]
+    output: Option<PathBuf>,
+    #[arg(long, help = "Target language of the xliff, po, json and fluent formats")]
+    lang: Option<String>,
+    #[arg(long, help = "Source language of the xliff and po formats")]
+    source_lang: Option<String>,
+    #[arg(long, help = "Write json bundles flat, keyed by unit id")]
+    flat: bool,
+}
+
+fn language(tag: Option<&str>, flag: &str) -> Result<LanguageTag, Box<dyn Error>> {
+    let tag = tag.ok_or_else(|| format!("this format needs --{}", flag))?;
+    normalize_language(tag).ok_or_else(|| format!("{} is not a language tag", tag).into())
+}
+
+fn read(args: &ConvertArgs) -> Result<TranslationResult, Box<dyn Error>> {
+    let dialect = match args.from {
+        InputFormat::Auto => None,
+        InputFormat::Standard => Some(Dialect::Standard),
+        InputFormat::Legacy => Some(Dialect::Legacy),
+        InputFormat::Wide => Some(Dialect::Wide),
+        InputFormat::Xliff | InputFormat::Po => {
+            let text = fs::read_to_string(&args.path)?;
+            let mut result = TranslationResult::default();
+
+            match args.from {
+                InputFormat::Xliff => {
+                    xliff::merge(&mut result, &text)?;
+                }
+                _ => {
+                    let merged = po::merge(&mut result, &text)?;
+                    if merged.fuzzy > 0 {
+                        eprintln!("skipped {} fuzzy entries", merged.fuzzy);
+                    }
+                }
+            }
+
+            return Ok(result);
+        }
+    };
+
+    let options = ParseOptions {
+        dialect,
+        ..ParseOptions::default()
+    };
+
+    Ok(load(&args.path, &options)?.0)
+}
+
+fn write(args: &ConvertArgs, result: &TranslationResult) -> Result<String, Box<dyn Error>> {
+    let text = match args.to {
+        OutputFormat::Standard => to_translations_csv(result),
+        OutputFormat::Wide => wide_csv(result),
+        OutputFormat::Xliff => xliff::write(result, &language(args.source_lang.as_deref(), "source-lang")?, &language(args.lang.as_deref(), "lang")?),
+        OutputFormat::Po => po::write(result, &language(args.source_lang.as_deref(), "source-lang")?, &language(args.lang.as_deref(), "lang")?),
+        OutputFormat::Json => {
+            let options = JsonBundleOptions { flat: args.flat };
+            serde_json::to_string_pretty(&json_bundle(result, &language(args.lang.as_deref(), "lang")?, &options))? + "\n"
+        }
+        #[cfg(feature = "fluent")]
+        OutputFormat::Fluent => gtfs_translations::export::fluent::write(result, &language(args.lang.as_deref(), "lang")?),
+    };
+
+    Ok(text)
+}
+
+pub fn run(args: ConvertArgs) -> Result<ExitCode, Box<dyn Error>> {
+    let result = read(&args)?;
+    let text = write(&args, &result)?;
+
+    match &args.output {
+        Some(output) => fs::write(output, text)?,
+        None => print!("{}", text),
+    }
+
+    eprintln!("converted {} translations", result.translations.len());
+
+    Ok(ExitCode::SUCCESS)
+}
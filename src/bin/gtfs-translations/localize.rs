@@ -0,0 +1,33 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use clap::Args;
+use gtfs_translations::{localize_feed, normalize_language};
+
+#[derive(Args)]
+pub struct LocalizeArgs {
+    #[arg(help = "GTFS zip to localize")]
+    path: PathBuf,
+    #[arg(long, help = "Language to write the feed in")]
+    lang: String,
+    #[arg(short, long, help = "Where to write the localized zip")]
+    output: PathBuf,
+}
+
+pub fn run(args: LocalizeArgs) -> Result<ExitCode, Box<dyn Error>> {
+    let language = normalize_language(&args.lang).ok_or_else(|| format!("{} is not a language tag", args.lang))?;
+
+    if args.output == args.path {
+        return Err("the output must not overwrite the input feed".into());
+    }
+
+    let input = BufReader::new(File::open(&args.path)?);
+    let output = BufWriter::new(File::create(&args.output)?);
+    let replaced = localize_feed(input, &language, output)?;
+
+    eprintln!("replaced {} values in {}", replaced, args.output.display());
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+use std::process::ExitCode;
+use clap::{Parser, Subcommand, ValueEnum};
+use gtfs_translations::{from_gtfs_zip_path, from_reader, ParseOptions, ParseStats, TranslationResult};
+
+mod convert;
+mod localize;
+mod report;
+mod validate;
+
+#[derive(Parser)]
+#[command(name = "gtfs-translations", version, about = "Check, report on and convert GTFS translations")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Print the notices for a translations.txt or GTFS zip")]
+    Validate(validate::ValidateArgs),
+    #[command(about = "Print per-language, per-table translation counts")]
+    Report(report::ReportArgs),
+    #[command(about = "Convert between translations.txt, wide csv, xliff, po and json bundles")]
+    Convert(convert::ConvertArgs),
+    #[command(about = "Write a copy of a GTFS zip with its text in one language")]
+    Localize(localize::LocalizeArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+pub fn is_zip(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+// Reads a translations.txt, or the translations.txt inside a GTFS zip
+pub fn load(path: &Path, options: &ParseOptions) -> Result<(TranslationResult, ParseStats), Box<dyn Error>> {
+    if is_zip(path) {
+        let parsed = from_gtfs_zip_path(path, options)?
+            .ok_or_else(|| format!("{} has no translations.txt", path.display()))?;
+        return Ok((parsed.translations, parsed.stats));
+    }
+
+    from_reader(File::open(path)?, options)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let status = match cli.command {
+        Command::Validate(args) => validate::run(args),
+        Command::Report(args) => report::run(args),
+        Command::Convert(args) => convert::run(args),
+        Command::Localize(args) => localize::run(args),
+    };
+
+    status.unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        ExitCode::from(2)
+    })
+}
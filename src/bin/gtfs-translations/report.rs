@@ -0,0 +1,75 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use clap::Args;
+use zip::ZipArchive;
+use gtfs_translations::universe::TranslationUniverse;
+use gtfs_translations::{coverage_report, from_gtfs_archive, CoverageRow, ParseOptions, ParseStats, TranslationResult};
+use crate::{is_zip, load, Format};
+
+#[derive(Args)]
+pub struct ReportArgs {
+    #[arg(help = "translations.txt or a GTFS zip")]
+    path: PathBuf,
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+fn print_text(rows: &[CoverageRow]) {
+    let mut language = None;
+
+    for row in rows {
+        if language != Some(&row.language) {
+            println!("{}", row.language);
+            println!("  {:<16}{:>14}{:>18}", "table", "translations", "covered");
+            language = Some(&row.language);
+        }
+
+        let covered = match (row.covered, row.total, row.percent()) {
+            (Some(covered), Some(total), Some(percent)) => format!("{}/{} ({:.1}%)", covered, total, percent),
+            (Some(covered), Some(total), None) => format!("{}/{}", covered, total),
+            _ => "-".to_string(),
+        };
+        println!("  {:<16}{:>14}{:>18}", row.table_name, row.translations, covered);
+    }
+}
+
+// A whole feed also says how much is left to translate. Its archive is opened once for both.
+fn load_with_universe(path: &Path) -> Result<(TranslationResult, ParseStats, Option<TranslationUniverse>), Box<dyn Error>> {
+    if !is_zip(path) {
+        let (result, stats) = load(path, &ParseOptions::default())?;
+        return Ok((result, stats, None));
+    }
+
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let parsed = from_gtfs_archive(&mut archive, &ParseOptions::default())?
+        .ok_or_else(|| format!("{} has no translations.txt", path.display()))?;
+    let universe = TranslationUniverse::from_gtfs_archive(&mut archive)?;
+
+    Ok((parsed.translations, parsed.stats, Some(universe)))
+}
+
+pub fn run(args: ReportArgs) -> Result<ExitCode, Box<dyn Error>> {
+    let (result, stats, universe) = load_with_universe(&args.path)?;
+    let rows = coverage_report(&result, universe.as_ref());
+
+    match args.format {
+        Format::Text => {
+            print_text(&rows);
+            println!("{} translations from {} rows, {} skipped", result.translations.len(), stats.rows_read, stats.rows_skipped);
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+        Format::Csv => {
+            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+
+            for row in rows.iter() {
+                wtr.serialize(row)?;
+            }
+            wtr.flush()?;
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
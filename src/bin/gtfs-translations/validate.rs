@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use clap::Args;
+use gtfs_translations::report::{render_text_with, sarif, RenderOptions};
+use gtfs_translations::{validate_with, NoticeConfig, ParseOptions, Severity};
+use crate::{load, Format};
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    #[arg(help = "translations.txt or a GTFS zip")]
+    path: PathBuf,
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    #[arg(long, conflicts_with = "lenient", help = "Count warnings as errors")]
+    strict: bool,
+    #[arg(long, help = "Accept rows with more or fewer fields than the header")]
+    lenient: bool,
+    #[arg(long, help = "JSON NoticeConfig with severity overrides and suppressed codes")]
+    config: Option<PathBuf>,
+    #[arg(long, help = "Also write the notices to this file as SARIF")]
+    sarif: Option<PathBuf>,
+}
+
+// Exits with 1 when there are errors, so CI jobs fail on a broken feed
+pub fn run(args: ValidateArgs) -> Result<ExitCode, Box<dyn Error>> {
+    let options = ParseOptions {
+        flexible: args.lenient,
+        ..ParseOptions::default()
+    };
+
+    let config = match &args.config {
+        Some(path) => serde_json::from_str::<NoticeConfig>(&std::fs::read_to_string(path)?)?,
+        None => NoticeConfig::default(),
+    };
+
+    let (result, stats) = load(&args.path, &options)?;
+    let mut notices = validate_with(&result, &stats, &config);
+
+    if args.strict {
+        for notice in notices.iter_mut().filter(|notice| notice.severity == Severity::Warning) {
+            notice.severity = Severity::Error;
+        }
+    }
+
+    if let Some(path) = &args.sarif {
+        std::fs::write(path, serde_json::to_string_pretty(&sarif(&notices, &args.path.to_string_lossy()))?)?;
+    }
+
+    match args.format {
+        Format::Text => {
+            let options = RenderOptions { color: std::io::stdout().is_terminal(), ..RenderOptions::default() };
+            print!("{}", render_text_with(&notices, &options));
+            println!("{} notices in {} rows", notices.len(), stats.rows_read);
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&notices)?),
+        Format::Csv => {
+            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+            wtr.write_record(["severity", "code", "line", "message"])?;
+
+            for notice in notices.iter() {
+                wtr.write_record([notice.severity.to_string(), notice.code.clone(), notice.line.map(|line| line.to_string()).unwrap_or_default(), notice.message.clone()])?;
+            }
+            wtr.flush()?;
+        }
+    }
+
+    match notices.iter().any(|notice| notice.severity == Severity::Error) {
+        true => Ok(ExitCode::FAILURE),
+        false => Ok(ExitCode::SUCCESS),
+    }
+}
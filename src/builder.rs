@@ -0,0 +1,137 @@
+use std::error::Error;
+use crate::language::normalize_language;
+#[cfg(feature = "parse")]
+use crate::writer::to_translations_csv;
+use crate::{AgencyFields, AreaFields, CalendarFields, FareProductFields, FeedInfoFields, RouteFields, StopFields, StopTimeFields, TripFields};
+use crate::{TranslatableField, TranslationKey, TranslationResult};
+
+// Builds a TranslationResult in code, for tools that produce translations rather than read them.
+// Calls chain, and the first invalid language is reported by build.
+//
+//     let csv = TranslationBuilder::new()
+//         .stop_name("stop_1", "fr", "Gare")
+//         .route_long_name("route_1", "fr", "Ligne bleue")
+//         .build_csv()?;
+#[derive(Debug, Clone, Default)]
+pub struct TranslationBuilder {
+    result: TranslationResult,
+    error: Option<String>,
+}
+
+// One method per field that is referenced by its table's id
+macro_rules! record_fields {
+    ($($method:ident => $field:expr,)*) => {
+        $(
+            pub fn $method(self, record_id: &str, language: &str, translation: &str) -> Self {
+                self.record($field, record_id, language, translation)
+            }
+        )*
+    };
+}
+
+impl TranslationBuilder {
+    pub fn new() -> TranslationBuilder {
+        TranslationBuilder::default()
+    }
+
+    pub fn translation(mut self, field: TranslatableField, key: TranslationKey, language: &str, translation: &str) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+
+        match normalize_language(language) {
+            Some(language) => {
+                self.result.insert(field, key, language, translation);
+            }
+            None => self.error = Some(format!("invalid language tag {:?}", language)),
+        }
+
+        self
+    }
+
+    pub fn record(self, field: TranslatableField, record_id: &str, language: &str, translation: &str) -> Self {
+        self.translation(field, TranslationKey::Record(record_id.to_string()), language, translation)
+    }
+
+    pub fn record_sub(self, field: TranslatableField, record_id: &str, record_sub_id: &str, language: &str, translation: &str) -> Self {
+        self.translation(field, TranslationKey::RecordSub((record_id.to_string(), record_sub_id.to_string())), language, translation)
+    }
+
+    // Translates every occurrence of a value of the field, whatever record it is on
+    pub fn value(self, field: TranslatableField, original: &str, language: &str, translation: &str) -> Self {
+        self.translation(field, TranslationKey::Value(original.to_string()), language, translation)
+    }
+
+    record_fields! {
+        agency_name => TranslatableField::Agency(AgencyFields::Name),
+        agency_url => TranslatableField::Agency(AgencyFields::Url),
+        agency_fare_url => TranslatableField::Agency(AgencyFields::FareUrl),
+        area_name => TranslatableField::Areas(AreaFields::Name),
+        calendar_service_id => TranslatableField::Calendar(CalendarFields::ServiceId),
+        fare_product_name => TranslatableField::FareProducts(FareProductFields::ProductName),
+        route_desc => TranslatableField::Routes(RouteFields::Desc),
+        route_long_name => TranslatableField::Routes(RouteFields::LongName),
+        route_short_name => TranslatableField::Routes(RouteFields::ShortName),
+        route_url => TranslatableField::Routes(RouteFields::Url),
+        stop_code => TranslatableField::Stops(StopFields::Code),
+        stop_name => TranslatableField::Stops(StopFields::Name),
+        tts_stop_name => TranslatableField::Stops(StopFields::TtsName),
+        platform_code => TranslatableField::Stops(StopFields::PlatformCode),
+        stop_desc => TranslatableField::Stops(StopFields::Desc),
+        trip_headsign => TranslatableField::Trips(TripFields::Headsign),
+        trip_short_name => TranslatableField::Trips(TripFields::ShortName),
+    }
+
+    // stop_times rows are referenced by trip_id and stop_sequence
+    pub fn stop_headsign(self, trip_id: &str, stop_sequence: u32, language: &str, translation: &str) -> Self {
+        self.record_sub(TranslatableField::StopTimes(StopTimeFields::Headsign), trip_id, &stop_sequence.to_string(), language, translation)
+    }
+
+    // feed_info has no id, so its one row is referenced by the original publisher name
+    pub fn feed_publisher_name(self, original: &str, language: &str, translation: &str) -> Self {
+        self.value(TranslatableField::FeedInfo(FeedInfoFields::PublisherName), original, language, translation)
+    }
+
+    pub fn build(self) -> Result<TranslationResult, Box<dyn Error>> {
+        match self.error {
+            Some(error) => Err(error.into()),
+            None => Ok(self.result),
+        }
+    }
+
+    // The built translations as a translations.txt
+    #[cfg(feature = "parse")]
+    pub fn build_csv(self) -> Result<String, Box<dyn Error>> {
+        self.build().map(|result| to_translations_csv(&result))
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use language_tags::LanguageTag;
+    use crate::translation_csv_text_to_translations;
+
+    #[test]
+    fn builds_and_writes() {
+        let csv = TranslationBuilder::new()
+            .stop_name("stop_1", "fr", "Gare")
+            .route_long_name("route_1", "fr_CA", "Ligne bleue")
+            .stop_headsign("trip_1", 4, "de", "Zentrum")
+            .build_csv()
+            .unwrap();
+
+        let parsed = translation_csv_text_to_translations(&csv).unwrap();
+        let ca = LanguageTag::parse("fr-CA").unwrap();
+
+        assert_eq!(parsed.translations.len(), 3);
+        assert_eq!(parsed.resolve(&TranslatableField::Routes(RouteFields::LongName), Some("route_1"), None, None, &ca), Some("Ligne bleue"));
+        assert_eq!(parsed.resolve(&TranslatableField::StopTimes(StopTimeFields::Headsign), Some("trip_1"), Some("4"), None, &LanguageTag::parse("de").unwrap()), Some("Zentrum"));
+    }
+
+    #[test]
+    fn rejects_an_invalid_language() {
+        let error = TranslationBuilder::new().stop_name("stop_1", "not a language", "Gare").stop_name("stop_2", "fr", "Quai").build().unwrap_err();
+        assert!(error.to_string().contains("not a language"));
+    }
+}
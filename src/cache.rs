@@ -0,0 +1,318 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+use std::sync::{Arc, Mutex, MutexGuard};
+use language_tags::LanguageTag;
+use crate::{fallback_languages, TranslatableField, TranslationHasher, TranslationResult};
+
+const DEFAULT_SHARDS: usize = 16;
+
+// The inputs of one resolution: field, record_id, record_sub_id, original value and the requested language.
+// Lookups hash and compare this borrowed form, so a hit allocates nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Lookup<'a> {
+    field: &'a TranslatableField,
+    record_id: Option<&'a str>,
+    record_sub_id: Option<&'a str>,
+    original_value: Option<&'a str>,
+    language: &'a str,
+}
+
+// The same inputs, owned by the cache
+#[derive(Debug)]
+struct CacheKey {
+    field: TranslatableField,
+    record_id: Option<String>,
+    record_sub_id: Option<String>,
+    original_value: Option<String>,
+    language: String,
+}
+
+impl CacheKey {
+    fn new(lookup: Lookup<'_>) -> CacheKey {
+        CacheKey {
+            field: lookup.field.clone(),
+            record_id: lookup.record_id.map(String::from),
+            record_sub_id: lookup.record_sub_id.map(String::from),
+            original_value: lookup.original_value.map(String::from),
+            language: lookup.language.to_string(),
+        }
+    }
+
+    fn lookup(&self) -> Lookup<'_> {
+        Lookup {
+            field: &self.field,
+            record_id: self.record_id.as_deref(),
+            record_sub_id: self.record_sub_id.as_deref(),
+            original_value: self.original_value.as_deref(),
+            language: &self.language,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    key: CacheKey,
+    value: Option<Arc<str>>,
+    tick: u64,
+}
+
+// One shard of the cache. Entries are filed under the hash of their lookup, with the rare collisions side by side.
+// Least recently used entries go first. The tick of every entry is unique, so the order map finds the oldest.
+#[derive(Debug, Default)]
+struct LookupCache {
+    entries: HashMap<u64, Vec<CacheEntry>, TranslationHasher>,
+    order: BTreeMap<u64, u64>,
+    len: usize,
+    capacity: usize,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl LookupCache {
+    fn with_capacity(capacity: usize) -> LookupCache {
+        LookupCache { capacity, ..LookupCache::default() }
+    }
+
+    fn get(&mut self, hash: u64, lookup: Lookup<'_>) -> Option<Option<Arc<str>>> {
+        self.tick += 1;
+        let Some(entry) = self.entries.get_mut(&hash).and_then(|bucket| bucket.iter_mut().find(|entry| entry.key.lookup() == lookup)) else {
+            self.misses += 1;
+            return None;
+        };
+
+        self.order.remove(&entry.tick);
+        entry.tick = self.tick;
+        self.order.insert(self.tick, hash);
+        self.hits += 1;
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, hash: u64, lookup: Lookup<'_>, value: Option<Arc<str>>) {
+        self.tick += 1;
+
+        // two threads that missed on the same lookup both insert it, the second only replaces the first
+        if let Some(entry) = self.entries.get_mut(&hash).and_then(|bucket| bucket.iter_mut().find(|entry| entry.key.lookup() == lookup)) {
+            self.order.remove(&entry.tick);
+            entry.tick = self.tick;
+            entry.value = value;
+            self.order.insert(self.tick, hash);
+            return;
+        }
+
+        while self.len >= self.capacity {
+            let Some((tick, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.remove(oldest, tick);
+        }
+
+        self.order.insert(self.tick, hash);
+        self.entries.entry(hash).or_default().push(CacheEntry { key: CacheKey::new(lookup), value, tick: self.tick });
+        self.len += 1;
+    }
+
+    fn remove(&mut self, hash: u64, tick: u64) {
+        let Some(bucket) = self.entries.get_mut(&hash) else {
+            return;
+        };
+
+        bucket.retain(|entry| entry.tick != tick);
+        if bucket.is_empty() {
+            self.entries.remove(&hash);
+        }
+        self.len -= 1;
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.len = 0;
+    }
+}
+
+// A TranslationResult with a bounded cache of resolved lookups, for departure boards and APIs that ask for
+// the same stops and headsigns over and over. Resolving with language fallback probes the map for every key
+// and fallback language, while a cached answer, found or not, is one probe. Edits go through edit, which
+// empties the cache. The cache is split into shards with a lock each, so threads resolving different lookups
+// rarely wait on each other, and each shard evicts its own least recently used entries.
+#[derive(Debug)]
+pub struct CachedTranslations {
+    result: TranslationResult,
+    shards: Vec<Mutex<LookupCache>>,
+    hasher: TranslationHasher,
+}
+
+impl CachedTranslations {
+    pub fn new(result: TranslationResult, capacity: usize) -> CachedTranslations {
+        CachedTranslations::with_shards(result, capacity, DEFAULT_SHARDS)
+    }
+
+    // The capacity is split between the shards, so there are never more shards than entries
+    pub fn with_shards(result: TranslationResult, capacity: usize, shards: usize) -> CachedTranslations {
+        let capacity = capacity.max(1);
+        let shards = shards.clamp(1, capacity);
+
+        CachedTranslations {
+            result,
+            shards: (0..shards)
+                .map(|shard| Mutex::new(LookupCache::with_capacity(capacity / shards + usize::from(shard < capacity % shards))))
+                .collect(),
+            hasher: TranslationHasher::default(),
+        }
+    }
+
+    pub fn result(&self) -> &TranslationResult {
+        &self.result
+    }
+
+    pub fn into_inner(self) -> TranslationResult {
+        self.result
+    }
+
+    // TranslationResult::resolve over fallback_languages, so fr-CA is answered from fr when it has to be
+    pub fn resolve(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>, language: &LanguageTag) -> Option<Arc<str>> {
+        let lookup = Lookup { field, record_id, record_sub_id, original_value, language: language.as_str() };
+        let hash = self.hash(&lookup);
+        let shard = &self.shards[hash as usize % self.shards.len()];
+
+        if let Some(cached) = Self::lock(shard).get(hash, lookup) {
+            return cached;
+        }
+
+        // resolved without holding the lock, two threads missing on the same key both resolve it
+        let resolved = fallback_languages(&self.result, language).iter()
+            .find_map(|language| self.result.resolve(field, record_id, record_sub_id, original_value, language))
+            .map(Arc::from);
+
+        Self::lock(shard).insert(hash, lookup, resolved.clone());
+        resolved
+    }
+
+    // Changes the translations and drops every cached answer, which may no longer hold
+    pub fn edit<F, T>(&mut self, edit: F) -> T
+    where
+        F: FnOnce(&mut TranslationResult) -> T,
+    {
+        let edited = edit(&mut self.result);
+        for shard in self.shards.iter() {
+            Self::lock(shard).clear();
+        }
+        edited
+    }
+
+    pub fn cached(&self) -> usize {
+        self.shards.iter().map(|shard| Self::lock(shard).len).sum()
+    }
+
+    // Lookups answered from the cache and lookups that had to be resolved
+    pub fn hits_and_misses(&self) -> (u64, u64) {
+        self.shards.iter().fold((0, 0), |(hits, misses), shard| {
+            let cache = Self::lock(shard);
+            (hits + cache.hits, misses + cache.misses)
+        })
+    }
+
+    fn hash(&self, lookup: &impl Hash) -> u64 {
+        BuildHasher::hash_one(&self.hasher, lookup)
+    }
+
+    fn lock(shard: &Mutex<LookupCache>) -> MutexGuard<'_, LookupCache> {
+        // the cache is only ever a copy of what the result says, so one left behind by a panic is still usable
+        shard.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{StopFields, TranslationKey};
+
+    fn stops() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,fr,Place,stop_2,,\n\
+            stops,stop_name,fr,Quai,stop_3,,\n")
+    }
+
+    #[test]
+    fn caches_fallback_lookups() {
+        let cached = CachedTranslations::new(stops(), 64);
+        let name = TranslatableField::Stops(StopFields::Name);
+        let fr_ca = LanguageTag::parse("fr-CA").unwrap();
+
+        assert_eq!(cached.resolve(&name, Some("stop_1"), None, None, &fr_ca).as_deref(), Some("Gare"));
+        assert_eq!(cached.resolve(&name, Some("stop_1"), None, None, &fr_ca).as_deref(), Some("Gare"));
+        assert_eq!(cached.resolve(&name, Some("stop_9"), None, None, &fr_ca), None);
+        assert_eq!(cached.resolve(&name, Some("stop_9"), None, None, &fr_ca), None);
+        assert_eq!(cached.hits_and_misses(), (2, 2));
+        assert_eq!(cached.cached(), 2);
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used() {
+        let cached = CachedTranslations::with_shards(stops(), 2, 1);
+        let name = TranslatableField::Stops(StopFields::Name);
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        cached.resolve(&name, Some("stop_1"), None, None, &fr);
+        cached.resolve(&name, Some("stop_9"), None, None, &fr);
+        cached.resolve(&name, Some("stop_1"), None, None, &fr);
+
+        // stop_9 was used less recently than stop_1, so it is the one evicted
+        cached.resolve(&name, Some("stop_2"), None, None, &fr);
+        assert_eq!(cached.cached(), 2);
+        cached.resolve(&name, Some("stop_1"), None, None, &fr);
+        assert_eq!(cached.hits_and_misses(), (2, 3));
+    }
+
+    #[test]
+    fn replacing_an_entry_evicts_nothing() {
+        let name = TranslatableField::Stops(StopFields::Name);
+        let lookup = |record_id| Lookup { field: &name, record_id: Some(record_id), record_sub_id: None, original_value: None, language: "fr" };
+        let mut cache = LookupCache::with_capacity(2);
+
+        cache.insert(1, lookup("stop_1"), Some(Arc::from("Gare")));
+        cache.insert(2, lookup("stop_2"), None);
+        cache.insert(1, lookup("stop_1"), Some(Arc::from("Gare SNCF")));
+
+        assert_eq!(cache.len, 2);
+        assert_eq!(cache.get(1, lookup("stop_1")), Some(Some(Arc::from("Gare SNCF"))));
+        assert_eq!(cache.get(2, lookup("stop_2")), Some(None));
+    }
+
+    #[test]
+    fn keeps_colliding_lookups_apart() {
+        let name = TranslatableField::Stops(StopFields::Name);
+        let lookup = |record_id| Lookup { field: &name, record_id: Some(record_id), record_sub_id: None, original_value: None, language: "fr" };
+        let mut cache = LookupCache::with_capacity(2);
+
+        cache.insert(7, lookup("stop_1"), Some(Arc::from("Gare")));
+        cache.insert(7, lookup("stop_2"), Some(Arc::from("Place")));
+        cache.insert(8, lookup("stop_3"), None);
+
+        assert_eq!(cache.len, 2);
+        assert_eq!(cache.get(7, lookup("stop_1")), None);
+        assert_eq!(cache.get(7, lookup("stop_2")), Some(Some(Arc::from("Place"))));
+    }
+
+    #[test]
+    fn splits_the_capacity_between_shards() {
+        let mut cached = CachedTranslations::with_shards(stops(), 10, 4);
+        let name = TranslatableField::Stops(StopFields::Name);
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        let capacities = cached.shards.iter().map(|shard| CachedTranslations::lock(shard).capacity).collect::<Vec<usize>>();
+        assert_eq!(capacities, [3, 3, 2, 2]);
+        assert_eq!(CachedTranslations::with_shards(stops(), 2, 16).shards.len(), 2);
+
+        for stop in 0..100 {
+            cached.resolve(&name, Some(&format!("stop_{}", stop)), None, None, &fr);
+        }
+        assert!(cached.cached() <= 10);
+
+        cached.edit(|result| result.insert(name.clone(), TranslationKey::Record("stop_1".to_string()), fr.clone(), "Gare du Nord"));
+        assert_eq!(cached.cached(), 0);
+        assert_eq!(cached.resolve(&name, Some("stop_1"), None, None, &fr).as_deref(), Some("Gare du Nord"));
+    }
+}
@@ -0,0 +1,186 @@
+use std::borrow::Cow;
+use std::error::Error;
+use serde::{Deserialize, Serialize};
+
+// Optional clean-up of translation and field_value text while parsing. Copy-pasted names often carry invisible
+// characters or odd spacing, which look wrong in apps and keep value keys from matching the feed's own values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextCleanup {
+    // Unicode NFC, so precomposed and combining accents compare equal. Needs the unicode-normalization feature.
+    pub nfc: bool,
+    // Zero-width spaces, word joiners, stray byte order marks and control characters. ZWJ and ZWNJ are kept,
+    // as some scripts and emoji need them.
+    pub strip_invisible: bool,
+    // Trims the ends and turns tabs, line breaks and runs of spaces into a single space
+    pub collapse_whitespace: bool,
+    // Hyphen and minus look-alikes become -, and curly quotes become ' and "
+    pub normalize_punctuation: bool,
+}
+
+impl TextCleanup {
+    pub fn all() -> TextCleanup {
+        TextCleanup {
+            nfc: true,
+            strip_invisible: true,
+            collapse_whitespace: true,
+            normalize_punctuation: true,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        *self != TextCleanup::default()
+    }
+
+    pub(crate) fn check(&self) -> Result<(), Box<dyn Error>> {
+        match self.nfc && cfg!(not(feature = "unicode-normalization")) {
+            true => Err("nfc cleanup requires the unicode-normalization feature".into()),
+            false => Ok(()),
+        }
+    }
+}
+
+// A value that was cleaned up while parsing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TextChange {
+    pub line: Option<u64>,
+    // translation or field_value
+    pub column: String,
+    pub before: String,
+    pub after: String,
+}
+
+fn is_invisible(character: char) -> bool {
+    matches!(character, '\u{200B}' | '\u{2060}' | '\u{FEFF}' | '\u{180E}')
+        || (character.is_control() && !matches!(character, '\t' | '\n' | '\r'))
+}
+
+fn normalize_punctuation(character: char) -> char {
+    match character {
+        '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2212}' | '\u{FE63}' | '\u{FF0D}' => '-',
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+        _ => character,
+    }
+}
+
+#[cfg(feature = "unicode-normalization")]
+fn nfc(text: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    text.nfc().collect()
+}
+
+#[cfg(not(feature = "unicode-normalization"))]
+fn nfc(text: &str) -> String {
+    text.to_string()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut run = String::new();
+
+    for character in text.trim().chars() {
+        if character.is_whitespace() {
+            run.push(character);
+            continue;
+        }
+
+        // a lone space or no-break space, as French puts before : and ?, is left as it was
+        match run.chars().count() {
+            0 => {}
+            1 if run != "\t" && run != "\n" && run != "\r" => collapsed.push_str(&run),
+            _ => collapsed.push(' '),
+        }
+        run.clear();
+        collapsed.push(character);
+    }
+
+    collapsed
+}
+
+// The text after the enabled steps, borrowed when nothing changed
+pub fn clean_text<'a>(text: &'a str, cleanup: &TextCleanup) -> Cow<'a, str> {
+    let mut cleaned = Cow::Borrowed(text);
+
+    if cleanup.nfc {
+        let normalized = nfc(&cleaned);
+        if normalized != cleaned {
+            cleaned = Cow::Owned(normalized);
+        }
+    }
+
+    if cleanup.strip_invisible && cleaned.chars().any(is_invisible) {
+        cleaned = Cow::Owned(cleaned.chars().filter(|character| !is_invisible(*character)).collect());
+    }
+
+    if cleanup.normalize_punctuation && cleaned.chars().any(|character| normalize_punctuation(character) != character) {
+        cleaned = Cow::Owned(cleaned.chars().map(normalize_punctuation).collect());
+    }
+
+    if cleanup.collapse_whitespace {
+        let collapsed = collapse_whitespace(&cleaned);
+        if collapsed != cleaned {
+            cleaned = Cow::Owned(collapsed);
+        }
+    }
+
+    cleaned
+}
+
+// Cleans one column of a row in place, noting the change
+pub(crate) fn clean_column<F>(value: &mut Cow<'_, str>, column: &str, line: Option<u64>, clean: F, changes: &mut Vec<TextChange>)
+where
+    F: for<'a> Fn(&'a str) -> Cow<'a, str>,
+{
+    if let Cow::Owned(after) = clean(value) {
+        changes.push(TextChange {
+            line,
+            column: column.to_string(),
+            before: value.to_string(),
+            after: after.clone(),
+        });
+        *value = Cow::Owned(after);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+    use crate::{from_reader, ParseOptions, StopFields, TranslatableField};
+    use language_tags::LanguageTag;
+
+    fn cleanup() -> TextCleanup {
+        TextCleanup { nfc: false, ..TextCleanup::all() }
+    }
+
+    fn data() -> String {
+        format!("{}stops,stop_name,fr,Gare  Centrale,,,Central\u{200B} Station\nstops,stop_name,fr,Place,stop_2,,\n", HEADER)
+    }
+
+    #[test]
+    fn cleans_text() {
+        assert_eq!(clean_text("Gare\u{200B}  du\tNord ", &cleanup()), "Gare du Nord");
+        assert_eq!(clean_text("\u{201C}Centre\u{201D} \u{2010} Ville", &cleanup()), "\"Centre\" - Ville");
+        assert_eq!(clean_text("Arr\u{00EA}t\u{00A0}:", &cleanup()), "Arr\u{00EA}t\u{00A0}:");
+        assert!(matches!(clean_text("Gare", &cleanup()), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn cleans_rows_while_parsing_and_reports_changes() {
+        let options = ParseOptions { text_cleanup: cleanup(), ..ParseOptions::default() };
+        let (result, stats) = from_reader(data().as_bytes(), &options).unwrap();
+
+        let field = TranslatableField::Stops(StopFields::Name);
+        assert_eq!(result.resolve(&field, None, None, Some("Central Station"), &LanguageTag::parse("fr").unwrap()), Some("Gare Centrale"));
+        assert_eq!(stats.text_changes.iter().map(|change| (change.line, change.column.as_str())).collect::<Vec<_>>(),
+            vec![(Some(2), "translation"), (Some(2), "field_value")]);
+    }
+
+    #[test]
+    fn needs_the_feature_for_nfc() {
+        let nfc = ParseOptions { text_cleanup: TextCleanup { nfc: true, ..TextCleanup::default() }, ..ParseOptions::default() };
+
+        assert_eq!(from_reader(data().as_bytes(), &nfc).is_ok(), cfg!(feature = "unicode-normalization"));
+    }
+}
@@ -0,0 +1,160 @@
+use std::hash::BuildHasher;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use language_tags::LanguageTag;
+use crate::{resolution_keys, TranslatableField, TranslationHasher, TranslationKey, TranslationLookup, TranslationMap, TranslationResult};
+
+const DEFAULT_SHARDS: usize = 16;
+
+type Shard = TranslationMap<TranslationLookup, Arc<str>>;
+
+// Translations that can be read and patched from many threads at once, for services that apply operator
+// corrections at runtime without rebuilding the whole set. The map is split into shards with a lock each, so a
+// writer only holds up the readers of its own shard. Unlike SharedTranslations, which swaps whole versions,
+// this changes a single translation in place. Provenance is not kept.
+#[derive(Debug)]
+pub struct ConcurrentTranslations {
+    shards: Vec<RwLock<Shard>>,
+    hasher: TranslationHasher,
+}
+
+impl ConcurrentTranslations {
+    pub fn new(result: TranslationResult) -> ConcurrentTranslations {
+        ConcurrentTranslations::with_shards(result, DEFAULT_SHARDS)
+    }
+
+    pub fn with_shards(result: TranslationResult, shards: usize) -> ConcurrentTranslations {
+        let shards = shards.max(1);
+        let capacity = result.translations.len() / shards + 1;
+        let hasher = TranslationHasher::default();
+
+        let mut maps = (0..shards)
+            .map(|_| TranslationMap::with_capacity_and_hasher(capacity, TranslationHasher::default()))
+            .collect::<Vec<Shard>>();
+        for (lookup, translation) in result.translations {
+            maps[BuildHasher::hash_one(&hasher, &lookup) as usize % shards].insert(lookup, translation);
+        }
+
+        ConcurrentTranslations {
+            shards: maps.into_iter().map(RwLock::new).collect(),
+            hasher,
+        }
+    }
+
+    fn shard(&self, lookup: &TranslationLookup) -> &RwLock<Shard> {
+        &self.shards[BuildHasher::hash_one(&self.hasher, lookup) as usize % self.shards.len()]
+    }
+
+    // a writer that panicked left a whole map behind, as every change is a single insert or remove
+    fn read(shard: &RwLock<Shard>) -> RwLockReadGuard<'_, Shard> {
+        shard.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(shard: &RwLock<Shard>) -> RwLockWriteGuard<'_, Shard> {
+        shard.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn get(&self, field: &TranslatableField, key: &TranslationKey, language: &LanguageTag) -> Option<Arc<str>> {
+        let lookup = TranslationLookup { language: language.clone(), field: field.clone(), key: key.clone() };
+        Self::read(self.shard(&lookup)).get(&lookup).cloned()
+    }
+
+    // The key precedence of TranslationResult::resolve
+    pub fn resolve(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>, language: &LanguageTag) -> Option<Arc<str>> {
+        resolution_keys(record_id, record_sub_id, original_value).into_iter().find_map(|key| self.get(field, &key, language))
+    }
+
+    pub fn insert(&self, field: TranslatableField, key: TranslationKey, language: LanguageTag, translation: impl Into<Arc<str>>) -> Option<Arc<str>> {
+        let lookup = TranslationLookup { language, field, key };
+        Self::write(self.shard(&lookup)).insert(lookup, translation.into())
+    }
+
+    pub fn remove(&self, field: &TranslatableField, key: &TranslationKey, language: &LanguageTag) -> Option<Arc<str>> {
+        let lookup = TranslationLookup { language: language.clone(), field: field.clone(), key: key.clone() };
+        Self::write(self.shard(&lookup)).remove(&lookup)
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| Self::read(shard).len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // A plain TranslationResult of the current translations with its summaries rebuilt. Shards are copied one at a
+    // time, so writes made while this runs may or may not be in it.
+    pub fn snapshot(&self) -> TranslationResult {
+        let mut result = TranslationResult::default();
+
+        for shard in self.shards.iter() {
+            for (lookup, translation) in Self::read(shard).iter() {
+                result.insert_lookup(lookup.clone(), translation.clone());
+            }
+        }
+
+        result
+    }
+}
+
+impl From<TranslationResult> for ConcurrentTranslations {
+    fn from(result: TranslationResult) -> Self {
+        ConcurrentTranslations::new(result)
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::test_util::translations;
+    use crate::StopFields;
+
+    fn concurrent() -> ConcurrentTranslations {
+        ConcurrentTranslations::with_shards(translations("stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,fr,Centre,,,Downtown\n"), 4)
+    }
+
+    fn name() -> TranslatableField {
+        TranslatableField::Stops(StopFields::Name)
+    }
+
+    fn fr() -> LanguageTag {
+        LanguageTag::parse("fr").unwrap()
+    }
+
+    #[test]
+    fn patches_while_reading_from_other_threads() {
+        let translations = Arc::new(concurrent());
+
+        let readers = (0..4).map(|_| {
+            let translations = translations.clone();
+            std::thread::spawn(move || (0..100).all(|_| translations.resolve(&name(), Some("stop_2"), None, Some("Downtown"), &fr()).is_some()))
+        }).collect::<Vec<_>>();
+
+        for stop in 0..100 {
+            translations.insert(name(), TranslationKey::Record(format!("stop_{}", stop + 10)), fr(), "Arr\u{00EA}t");
+        }
+
+        assert!(readers.into_iter().all(|reader| reader.join().unwrap()));
+        assert_eq!(translations.len(), 102);
+    }
+
+    #[test]
+    fn insert_and_remove_return_the_previous_text() {
+        let translations = concurrent();
+
+        assert_eq!(translations.insert(name(), TranslationKey::Record("stop_1".to_string()), fr(), "Gare SNCF"), Some("Gare".into()));
+        assert_eq!(translations.resolve(&name(), Some("stop_1"), None, None, &fr()).as_deref(), Some("Gare SNCF"));
+        assert_eq!(translations.remove(&name(), &TranslationKey::Value("Downtown".to_string()), &fr()), Some("Centre".into()));
+        assert_eq!(translations.remove(&name(), &TranslationKey::Value("Downtown".to_string()), &fr()), None);
+    }
+
+    #[test]
+    fn snapshots_with_summaries() {
+        let translations = concurrent();
+        translations.insert(name(), TranslationKey::Record("stop_2".to_string()), fr(), "Quai");
+
+        let snapshot = translations.snapshot();
+        assert_eq!((translations.len(), snapshot.translations.len()), (3, 3));
+        assert_eq!(snapshot.languages_as_written(), [fr()]);
+    }
+}
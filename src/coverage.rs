@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::universe::TranslationUniverse;
+use crate::{enum_to_table_and_field, TranslationResult};
+
+// How much of one table is translated into one language
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoverageRow {
+    pub language: String,
+    pub table_name: String,
+    // Entries in translations.txt for this table and language
+    pub translations: usize,
+    // With a universe: the translatable values in the table, and how many of them resolve in the language
+    pub total: Option<usize>,
+    pub covered: Option<usize>,
+}
+
+impl CoverageRow {
+    pub fn percent(&self) -> Option<f64> {
+        match (self.covered, self.total) {
+            (Some(covered), Some(total)) if total > 0 => Some(covered as f64 * 100.0 / total as f64),
+            _ => None,
+        }
+    }
+}
+
+fn row<'a>(rows: &'a mut BTreeMap<(String, &'static str), CoverageRow>, language: String, table_name: &'static str, with_universe: bool) -> &'a mut CoverageRow {
+    rows.entry((language.clone(), table_name)).or_insert_with(|| CoverageRow {
+        language,
+        table_name: table_name.to_string(),
+        translations: 0,
+        total: with_universe.then_some(0),
+        covered: with_universe.then_some(0),
+    })
+}
+
+// One row per language and table, sorted by both. Without a universe only the translation counts are known.
+pub fn coverage_report(result: &TranslationResult, universe: Option<&TranslationUniverse>) -> Vec<CoverageRow> {
+    let mut rows = BTreeMap::new();
+
+    for lookup in result.translations.keys() {
+        row(&mut rows, lookup.language.to_string(), enum_to_table_and_field(&lookup.field).0, universe.is_some()).translations += 1;
+    }
+
+    if let Some(universe) = universe {
+        for language in result.languages_as_written().iter() {
+            for entry in universe.entries.iter() {
+                let row = row(&mut rows, language.to_string(), enum_to_table_and_field(&entry.field).0, true);
+                row.total = row.total.map(|total| total + 1);
+
+                if result.translate_entry(entry, language).is_some() {
+                    row.covered = row.covered.map(|covered| covered + 1);
+                }
+            }
+        }
+    }
+
+    rows.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{StopFields, TranslatableField};
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_desc,fr,Quai,stop_1,,\n\
+            routes,route_long_name,de,Blau,route_1,,\n")
+    }
+
+    fn universe() -> TranslationUniverse {
+        let mut universe = TranslationUniverse::new();
+        universe.push(TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, "Station");
+        universe.push(TranslatableField::Stops(StopFields::Name), Some("stop_2"), None, "Platform");
+        universe
+    }
+
+    #[test]
+    fn counts_per_language_and_table() {
+        let rows = coverage_report(&result(), Some(&universe()));
+        let summary = rows.iter().map(|row| (row.language.as_str(), row.table_name.as_str(), row.translations, row.covered, row.total)).collect::<Vec<_>>();
+
+        assert_eq!(summary, vec![
+            ("de", "routes", 1, Some(0), Some(0)),
+            ("de", "stops", 0, Some(0), Some(2)),
+            ("fr", "stops", 2, Some(1), Some(2)),
+        ]);
+    }
+
+    #[test]
+    fn gives_a_percentage_of_nonzero_totals() {
+        let rows = coverage_report(&result(), Some(&universe()));
+
+        assert_eq!(rows[2].percent(), Some(50.0));
+        assert_eq!(rows[0].percent(), None);
+    }
+
+    #[test]
+    fn leaves_totals_out_without_a_universe() {
+        let rows = coverage_report(&result(), None);
+
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.total.is_none() && row.covered.is_none()));
+    }
+}
@@ -0,0 +1,208 @@
+use std::error::Error;
+use std::io::Write;
+use serde::{Deserialize, Serialize};
+use crate::{enum_to_table_and_field, key_struct_to_options, RawTranslation, RawTranslationIndexer, TranslationResult};
+
+// One translation as a flat database row. feed_id tells apart the feeds sharing a table, a chateau id in Catenary.
+// Columns are text, and nullable for the three key parts. The sqlx and diesel features derive their row traits.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "diesel", derive(diesel::Queryable))]
+pub struct TranslationRow {
+    pub feed_id: String,
+    pub table_name: String,
+    pub field_name: String,
+    pub record_id: Option<String>,
+    pub record_sub_id: Option<String>,
+    pub field_value: Option<String>,
+    pub language: String,
+    pub translation: String,
+}
+
+// Columns in the order the COPY writers emit them
+pub const COPY_COLUMNS: [&str; 8] = ["feed_id", "table_name", "field_name", "record_id", "record_sub_id", "field_value", "language", "translation"];
+
+impl TranslationRow {
+    fn values(&self) -> [Option<&str>; 8] {
+        [
+            Some(self.feed_id.as_str()),
+            Some(self.table_name.as_str()),
+            Some(self.field_name.as_str()),
+            self.record_id.as_deref(),
+            self.record_sub_id.as_deref(),
+            self.field_value.as_deref(),
+            Some(self.language.as_str()),
+            Some(self.translation.as_str()),
+        ]
+    }
+}
+
+pub fn to_rows<'a>(result: &'a TranslationResult, feed_id: &'a str) -> impl Iterator<Item = TranslationRow> + 'a {
+    result.translations.iter().map(move |(lookup, translation)| {
+        let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+        let (record_id, record_sub_id, field_value) = key_struct_to_options(&lookup.key);
+
+        TranslationRow {
+            feed_id: feed_id.to_string(),
+            table_name: table_name.to_string(),
+            field_name: field_name.to_string(),
+            record_id,
+            record_sub_id,
+            field_value,
+            language: lookup.language.to_string(),
+            translation: translation.to_string(),
+        }
+    })
+}
+
+impl From<TranslationRow> for RawTranslation {
+    fn from(row: TranslationRow) -> Self {
+        RawTranslation {
+            table_name: row.table_name,
+            field_name: row.field_name,
+            language: row.language,
+            translation: row.translation,
+            record_id: row.record_id,
+            record_sub_id: row.record_sub_id,
+            field_value: row.field_value,
+            ..RawTranslation::default()
+        }
+    }
+}
+
+impl TranslationResult {
+    // Rebuilds the index from rows loaded from the database, skipping rows that translations.txt would reject.
+    // The feed id is not part of the index, so load one feed at a time.
+    pub fn from_rows(rows: Vec<TranslationRow>) -> TranslationResult {
+        let mut indexer = RawTranslationIndexer::with_capacity(rows.len());
+
+        for row in rows {
+            let _ = indexer.push(row.into());
+        }
+
+        indexer.finish()
+    }
+}
+
+// The statement the output of write_copy_csv or write_copy_binary is the input of
+pub fn copy_statement(table: &str, binary: bool) -> String {
+    format!("COPY {} ({}) FROM STDIN WITH (FORMAT {})", table, COPY_COLUMNS.join(", "), if binary { "binary" } else { "csv" })
+}
+
+// Postgres reads an unquoted empty field as NULL in CSV mode, so empty strings have to be quoted
+fn write_csv_field<W: Write>(writer: &mut W, value: Option<&str>) -> std::io::Result<()> {
+    let Some(value) = value else {
+        return Ok(());
+    };
+
+    if value.is_empty() || value == "\\." || value.contains([',', '"', '\r', '\n']) {
+        write!(writer, "\"{}\"", value.replace('"', "\"\""))
+    } else {
+        writer.write_all(value.as_bytes())
+    }
+}
+
+// Rows for COPY ... WITH (FORMAT csv), without a header line
+pub fn write_copy_csv<W: Write, I: IntoIterator<Item = TranslationRow>>(rows: I, mut writer: W) -> Result<(), Box<dyn Error>> {
+    for row in rows {
+        for (i, value) in row.values().into_iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            write_csv_field(&mut writer, value)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+const PGCOPY_SIGNATURE: &[u8] = b"PGCOPY\n\xff\r\n\0";
+
+// Rows for COPY ... WITH (FORMAT binary), which every column being text keeps simple: each value is its length and UTF-8 bytes
+pub fn write_copy_binary<W: Write, I: IntoIterator<Item = TranslationRow>>(rows: I, mut writer: W) -> Result<(), Box<dyn Error>> {
+    writer.write_all(PGCOPY_SIGNATURE)?;
+    // flags and header extension length
+    writer.write_all(&0i32.to_be_bytes())?;
+    writer.write_all(&0i32.to_be_bytes())?;
+
+    for row in rows {
+        writer.write_all(&(COPY_COLUMNS.len() as i16).to_be_bytes())?;
+
+        for value in row.values() {
+            match value {
+                Some(value) => {
+                    writer.write_all(&i32::try_from(value.len())?.to_be_bytes())?;
+                    writer.write_all(value.as_bytes())?;
+                }
+                None => writer.write_all(&(-1i32).to_be_bytes())?,
+            }
+        }
+    }
+
+    writer.write_all(&(-1i16).to_be_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn rows() -> Vec<TranslationRow> {
+        to_rows(&translations("stops,stop_name,fr,\"Gare, \"\"centrale\"\"\",stop_1,,\n"), "chateau_1").collect()
+    }
+
+    #[test]
+    fn round_trips_rows() {
+        let result = translations("stops,stop_name,fr,\"Gare, \"\"centrale\"\"\",stop_1,,\ntrips,trip_headsign,fr,Centre,,,Downtown\n");
+        let rows = to_rows(&result, "chateau_1").collect::<Vec<TranslationRow>>();
+
+        assert!(rows.iter().any(|row| row.record_id.as_deref() == Some("stop_1")));
+        assert_eq!(TranslationResult::from_rows(rows).translations, result.translations);
+    }
+
+    #[test]
+    fn skips_rows_translations_txt_would_reject() {
+        let mut rows = rows();
+        rows.push(TranslationRow { table_name: "shapes".to_string(), ..rows[0].clone() });
+        rows.push(TranslationRow { language: "not a tag".to_string(), ..rows[0].clone() });
+
+        assert_eq!(TranslationResult::from_rows(rows).translations.len(), 1);
+    }
+
+    #[test]
+    fn writes_copy_csv() {
+        let mut csv = vec![];
+        write_copy_csv(rows(), &mut csv).unwrap();
+
+        assert_eq!(String::from_utf8(csv).unwrap(), "chateau_1,stops,stop_name,stop_1,,,fr,\"Gare, \"\"centrale\"\"\"\n");
+    }
+
+    #[test]
+    fn quotes_what_copy_would_read_as_null_or_the_end() {
+        let rows = ["", "\\."].map(|translation| TranslationRow { translation: translation.to_string(), ..rows()[0].clone() });
+        let mut csv = vec![];
+        write_copy_csv(rows, &mut csv).unwrap();
+
+        assert_eq!(String::from_utf8(csv).unwrap(), "chateau_1,stops,stop_name,stop_1,,,fr,\"\"\nchateau_1,stops,stop_name,stop_1,,,fr,\"\\.\"\n");
+    }
+
+    #[test]
+    fn writes_copy_binary() {
+        let mut binary = vec![];
+        write_copy_binary(rows(), &mut binary).unwrap();
+
+        assert!(binary.starts_with(PGCOPY_SIGNATURE));
+        assert!(binary.ends_with(&[0xff, 0xff]));
+        // header, field count, 8 length prefixes, the text of the 6 present values and the trailer
+        assert_eq!(binary.len(), 19 + 2 + 8 * 4 + "chateau_1stopsstop_namestop_1frGare, \"centrale\"".len() + 2);
+    }
+
+    #[test]
+    fn builds_copy_statements() {
+        assert_eq!(copy_statement("translations", false), "COPY translations (feed_id, table_name, field_name, record_id, record_sub_id, field_value, language, translation) FROM STDIN WITH (FORMAT csv)");
+    }
+}
@@ -0,0 +1,110 @@
+use std::io::{self, BufRead, BufReader, Read};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+pub(crate) enum Input<R: Read> {
+    Plain(BufReader<R>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::read::MultiGzDecoder<BufReader<R>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::read::Decoder<'static, BufReader<R>>),
+}
+
+impl<R: Read> Read for Input<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::Plain(reader) => reader.read(buf),
+            #[cfg(feature = "gzip")]
+            Input::Gzip(reader) => reader.read(buf),
+            #[cfg(feature = "zstd")]
+            Input::Zstd(reader) => reader.read(buf),
+        }
+    }
+}
+
+#[cfg(any(not(feature = "gzip"), not(feature = "zstd")))]
+fn unsupported(compression: &str, feature: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, format!("input is {} compressed, enable the {} feature to read it", compression, feature))
+}
+
+#[cfg(feature = "tokio")]
+pub(crate) fn compression_of(head: &[u8]) -> Option<Compression> {
+    if head.starts_with(GZIP_MAGIC) {
+        Some(Compression::Gzip)
+    } else if head.starts_with(ZSTD_MAGIC) {
+        Some(Compression::Zstd)
+    } else {
+        None
+    }
+}
+
+// Peeks at the first bytes and wraps the reader in the matching decoder
+pub(crate) fn decompress<R: Read>(reader: R) -> io::Result<(Input<R>, Option<Compression>)> {
+    let mut reader = BufReader::new(reader);
+    let head = reader.fill_buf()?;
+
+    if head.starts_with(GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        return Ok((Input::Gzip(flate2::read::MultiGzDecoder::new(reader)), Some(Compression::Gzip)));
+        #[cfg(not(feature = "gzip"))]
+        return Err(unsupported("gzip", "gzip"));
+    }
+
+    if head.starts_with(ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        return Ok((Input::Zstd(zstd::stream::read::Decoder::with_buffer(reader)?), Some(Compression::Zstd)));
+        #[cfg(not(feature = "zstd"))]
+        return Err(unsupported("zstd", "zstd"));
+    }
+
+    Ok((Input::Plain(reader), None))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{from_reader, ParseOptions};
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    fn data() -> String {
+        format!("{}stops,stop_name,fr,Gare,stop_1,,\n", crate::test_util::HEADER)
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn reads_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data().as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let (result, stats) = from_reader(compressed.as_slice(), &ParseOptions::default()).unwrap();
+        assert_eq!(result.translations.len(), 1);
+        assert_eq!(stats.compression, Some(super::Compression::Gzip));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn reads_zstd() {
+        let compressed = zstd::stream::encode_all(data().as_bytes(), 0).unwrap();
+
+        let (result, stats) = from_reader(compressed.as_slice(), &ParseOptions::default()).unwrap();
+        assert_eq!(result.translations.len(), 1);
+        assert_eq!(stats.compression, Some(super::Compression::Zstd));
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn gzip_needs_feature() {
+        assert!(from_reader([0x1f, 0x8b, 0x08, 0x00].as_slice(), &ParseOptions::default()).is_err());
+    }
+}
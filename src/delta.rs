@@ -0,0 +1,171 @@
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize};
+use crate::{key_options_to_struct, table_and_field_to_enum, RawTranslation, TranslationLookup, TranslationResult};
+
+// A translation to remove, named by its translations.txt columns
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TranslationLookupSpec {
+    pub table_name: String,
+    pub field_name: String,
+    pub language: String,
+    pub record_id: Option<String>,
+    pub record_sub_id: Option<String>,
+    pub field_value: Option<String>,
+}
+
+impl TranslationLookupSpec {
+    // Same checks and key precedence as parsing a row
+    pub fn to_lookup(&self) -> Result<TranslationLookup, String> {
+        let language = LanguageTag::parse(&self.language)
+            .map_err(|_| format!("invalid language tag {:?}", self.language))?;
+        let field = table_and_field_to_enum(&self.table_name, &self.field_name)
+            .ok_or_else(|| format!("untranslatable field {}.{}", self.table_name, self.field_name))?;
+        let key = key_options_to_struct(self.record_id.clone(), self.record_sub_id.clone(), self.field_value.clone())
+            .ok_or("row has neither record_id nor field_value")?;
+
+        Ok(TranslationLookup { language, field, key })
+    }
+}
+
+impl From<&RawTranslation> for TranslationLookupSpec {
+    fn from(row: &RawTranslation) -> Self {
+        TranslationLookupSpec {
+            table_name: row.table_name.clone(),
+            field_name: row.field_name.clone(),
+            language: row.language.clone(),
+            record_id: row.record_id.clone(),
+            record_sub_id: row.record_sub_id.clone(),
+            field_value: row.field_value.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeltaReport {
+    pub inserted: usize,
+    // Added rows that overwrote an existing translation
+    pub replaced: usize,
+    pub removed: usize,
+    // Removals of translations that were not there
+    pub missing: usize,
+    // Rows that could not be read, with the reason
+    pub rejected: Vec<String>,
+}
+
+impl TranslationResult {
+    // Removes and then adds translations in place, for feed refreshes that only change a few strings. Like remove,
+    // each removal updates the summaries from their counts without scanning the other translations.
+    pub fn apply_delta(&mut self, added: Vec<RawTranslation>, removed: Vec<TranslationLookupSpec>) -> DeltaReport {
+        let mut report = DeltaReport::default();
+
+        for spec in removed.iter() {
+            let lookup = match spec.to_lookup() {
+                Ok(lookup) => lookup,
+                Err(err) => {
+                    report.rejected.push(err);
+                    continue;
+                }
+            };
+
+            match self.translations.remove(&lookup) {
+                Some(_) => {
+                    report.removed += 1;
+                    self.forget_lookup(&lookup);
+                }
+                None => report.missing += 1,
+            }
+        }
+
+        for row in added {
+            let lookup = match TranslationLookupSpec::from(&row).to_lookup() {
+                Ok(lookup) => lookup,
+                Err(err) => {
+                    report.rejected.push(err);
+                    continue;
+                }
+            };
+
+            self.provenance.remove(&lookup);
+
+            match self.insert_lookup(lookup, row.translation.into()) {
+                Some(_) => report.replaced += 1,
+                None => report.inserted += 1,
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn spec(language: &str, record_id: &str) -> TranslationLookupSpec {
+        TranslationLookupSpec {
+            table_name: "stops".to_string(),
+            field_name: "stop_name".to_string(),
+            language: language.to_string(),
+            record_id: Some(record_id.to_string()),
+            ..TranslationLookupSpec::default()
+        }
+    }
+
+    fn row(record_id: &str, translation: &str) -> RawTranslation {
+        RawTranslation {
+            table_name: "stops".to_string(),
+            field_name: "stop_name".to_string(),
+            language: "fr".to_string(),
+            translation: translation.to_string(),
+            record_id: Some(record_id.to_string()),
+            record_sub_id: None,
+            field_value: None,
+            extras: Default::default(),
+        }
+    }
+
+    fn local() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,de,Bahnhof,stop_1,,\nstops,stop_name,fr,Gare,,,Station\n")
+    }
+
+    fn delta() -> (Vec<RawTranslation>, Vec<TranslationLookupSpec>) {
+        let by_value = TranslationLookupSpec {
+            record_id: None,
+            field_value: Some("Station".to_string()),
+            ..spec("fr", "")
+        };
+
+        (vec![row("stop_2", "Quai"), row("stop_1", "Gare Centrale")], vec![spec("de", "stop_1"), spec("nl", "stop_1"), spec("not a tag", "stop_1"), by_value])
+    }
+
+    #[test]
+    fn reports_what_it_changed() {
+        let (added, removed) = delta();
+        let report = local().apply_delta(added, removed);
+
+        assert_eq!((report.inserted, report.replaced, report.removed, report.missing), (1, 1, 2, 1));
+    }
+
+    #[test]
+    fn rejects_unreadable_lookups_and_rows() {
+        let mut result = local();
+        let report = result.apply_delta(vec![RawTranslation { table_name: "shapes".to_string(), ..row("stop_3", "Quai") }], vec![spec("not a tag", "stop_1")]);
+
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(result.translations, local().translations);
+    }
+
+    #[test]
+    fn matches_a_full_rebuild() {
+        let mut result = local();
+        let (added, removed) = delta();
+        result.apply_delta(added, removed);
+
+        let expected = translations("stops,stop_name,fr,Gare Centrale,stop_1,,\nstops,stop_name,fr,Quai,stop_2,,\n");
+        assert_eq!(result.translations, expected.translations);
+        assert_eq!(result.languages_as_written(), expected.languages_as_written());
+        assert_eq!(result.possible_translations, expected.possible_translations);
+        assert_eq!(result.key_strategies(), expected.key_strategies());
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use crate::{enum_to_table_and_field, key_struct_to_options, TranslationLookup, TranslationResult};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffEntry {
+    pub field_name: String,
+    pub record_id: Option<String>,
+    pub record_sub_id: Option<String>,
+    pub field_value: Option<String>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffGroup {
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub changed: Vec<DiffEntry>,
+}
+
+// What changed from one translation set to another, by language and then by table.
+// Serializes to JSON as { "fr": { "stops": { "added": [...], ... } } }
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslationDiff {
+    pub languages: BTreeMap<String, BTreeMap<String, DiffGroup>>,
+}
+
+impl TranslationDiff {
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+    }
+
+    // Added, removed and changed entries over all groups
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.languages.values().flat_map(|tables| tables.values())
+            .fold((0, 0, 0), |(added, removed, changed), group| (added + group.added.len(), removed + group.removed.len(), changed + group.changed.len()))
+    }
+
+    fn group(&mut self, lookup: &TranslationLookup) -> &mut DiffGroup {
+        let (table_name, _) = enum_to_table_and_field(&lookup.field);
+
+        self.languages.entry(lookup.language.to_string()).or_default().entry(table_name.to_string()).or_default()
+    }
+}
+
+fn entry(lookup: &TranslationLookup, before: Option<&Arc<str>>, after: Option<&Arc<str>>) -> DiffEntry {
+    let (_, field_name) = enum_to_table_and_field(&lookup.field);
+    let (record_id, record_sub_id, field_value) = key_struct_to_options(&lookup.key);
+
+    DiffEntry {
+        field_name: field_name.to_string(),
+        record_id,
+        record_sub_id,
+        field_value,
+        before: before.map(ToString::to_string),
+        after: after.map(ToString::to_string),
+    }
+}
+
+fn entry_order(entry: &DiffEntry) -> (&String, &Option<String>, &Option<String>, &Option<String>) {
+    (&entry.field_name, &entry.record_id, &entry.record_sub_id, &entry.field_value)
+}
+
+pub fn diff(before: &TranslationResult, after: &TranslationResult) -> TranslationDiff {
+    let mut diff = TranslationDiff::default();
+
+    for (lookup, old) in before.translations.iter() {
+        match after.translations.get(lookup) {
+            None => diff.group(lookup).removed.push(entry(lookup, Some(old), None)),
+            Some(new) if new != old => diff.group(lookup).changed.push(entry(lookup, Some(old), Some(new))),
+            Some(_) => {}
+        }
+    }
+
+    for (lookup, new) in after.translations.iter() {
+        if !before.translations.contains_key(lookup) {
+            diff.group(lookup).added.push(entry(lookup, None, Some(new)));
+        }
+    }
+
+    // sorted like the rows of a written translations.txt, so reports of the same change are identical
+    for group in diff.languages.values_mut().flat_map(|tables| tables.values_mut()) {
+        for entries in [&mut group.added, &mut group.removed, &mut group.changed] {
+            entries.sort_by(|a, b| entry_order(a).cmp(&entry_order(b)));
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn versions() -> (TranslationResult, TranslationResult) {
+        let v1 = translations("stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,fr,Quai,stop_2,,\nroutes,route_long_name,de,Blau,route_1,,\n");
+        let v2 = translations("stops,stop_name,fr,Gare SNCF,stop_1,,\nroutes,route_long_name,de,Blau,route_1,,\ntrips,trip_headsign,de,Zentrum,,,Downtown\n");
+        (v1, v2)
+    }
+
+    #[test]
+    fn groups_changes_by_language_and_table() {
+        let (v1, v2) = versions();
+        let changes = diff(&v1, &v2);
+        let stops = &changes.languages["fr"]["stops"];
+
+        assert_eq!(changes.counts(), (1, 1, 1));
+        assert_eq!(stops.changed[0].before.as_deref(), Some("Gare"));
+        assert_eq!(stops.changed[0].after.as_deref(), Some("Gare SNCF"));
+        assert_eq!(stops.removed[0].record_id.as_deref(), Some("stop_2"));
+        assert_eq!(changes.languages["de"]["trips"].added[0].field_value.as_deref(), Some("Downtown"));
+    }
+
+    #[test]
+    fn leaves_out_unchanged_tables() {
+        let (v1, v2) = versions();
+
+        assert!(!diff(&v1, &v2).languages["de"].contains_key("routes"));
+        assert!(diff(&v2, &v2).is_empty());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let (v1, v2) = versions();
+        let changes = diff(&v1, &v2);
+
+        let json = serde_json::to_string(&changes).unwrap();
+        assert_eq!(serde_json::from_str::<TranslationDiff>(&json).unwrap(), changes);
+    }
+}
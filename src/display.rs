@@ -0,0 +1,86 @@
+use language_tags::LanguageTag;
+use crate::{StopFields, StopTimeFields, TranslatableField, TranslationKey, TranslationResult, TripFields};
+
+// What to show for a stop and what to announce for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopNames<'a> {
+    pub display: &'a str,
+    pub tts: &'a str,
+}
+
+impl TranslationResult {
+    // The headsign to show for a trip, or for one of its stop times when stop_sequence is given. A translation of
+    // the stop time wins over one of the trip, and both win over translations of the headsign text itself.
+    pub fn headsign(&self, trip_id: &str, stop_sequence: Option<u32>, original: &str, language: &LanguageTag) -> Option<&str> {
+        let stop_time_field = TranslatableField::StopTimes(StopTimeFields::Headsign);
+        let trip_field = TranslatableField::Trips(TripFields::Headsign);
+
+        let stop_time = stop_sequence.and_then(|stop_sequence| {
+            self.get(&stop_time_field, &TranslationKey::RecordSub((trip_id.to_string(), stop_sequence.to_string())), language)
+        });
+        let value = TranslationKey::Value(original.to_string());
+
+        stop_time
+            .or_else(|| self.get(&trip_field, &TranslationKey::Record(trip_id.to_string()), language))
+            .or_else(|| stop_sequence.and_then(|_| self.get(&stop_time_field, &value, language)))
+            .or_else(|| self.get(&trip_field, &value, language))
+    }
+
+    // An untranslated tts_name is never read out in place of a translated stop_name, as a name in the rider's
+    // language reads better than a pronunciation hint in another. Falls back to the originals when nothing is translated.
+    pub fn stop_display_and_tts<'a>(&'a self, stop_id: &str, original_name: &'a str, original_tts: Option<&'a str>, language: &LanguageTag) -> StopNames<'a> {
+        let display = self.resolve(&TranslatableField::Stops(StopFields::Name), Some(stop_id), None, Some(original_name), language);
+        let tts = self.resolve(&TranslatableField::Stops(StopFields::TtsName), Some(stop_id), None, original_tts, language);
+
+        StopNames {
+            display: display.unwrap_or(original_name),
+            tts: tts.or(display).or(original_tts).unwrap_or(original_name),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn headsigns() -> TranslationResult {
+        translations("stop_times,stop_headsign,fr,Gare (quai 2),trip_1,5,\n\
+            trips,trip_headsign,fr,Gare,trip_1,,\n\
+            trips,trip_headsign,fr,Centre,,,Downtown\n\
+            stop_times,stop_headsign,fr,Aéroport,,,Airport\n")
+    }
+
+    #[test]
+    fn prefers_the_stop_time_then_the_trip() {
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        assert_eq!(headsigns().headsign("trip_1", Some(5), "Station", &fr), Some("Gare (quai 2)"));
+        assert_eq!(headsigns().headsign("trip_1", Some(6), "Station", &fr), Some("Gare"));
+    }
+
+    #[test]
+    fn falls_back_to_the_value() {
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        assert_eq!(headsigns().headsign("trip_2", Some(1), "Airport", &fr), Some("Aéroport"));
+        assert_eq!(headsigns().headsign("trip_2", None, "Downtown", &fr), Some("Centre"));
+    }
+
+    #[test]
+    fn needs_a_stop_sequence_for_stop_time_values() {
+        assert_eq!(headsigns().headsign("trip_2", None, "Airport", &LanguageTag::parse("fr").unwrap()), None);
+    }
+
+    #[test]
+    fn pairs_display_and_tts_names() {
+        let result = translations("stops,stop_name,fr,St-Laurent,stop_1,,\n\
+            stops,tts_stop_name,fr,Saint-Laurent,stop_1,,\n\
+            stops,stop_name,fr,Gare,stop_2,,\n");
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        assert_eq!(result.stop_display_and_tts("stop_1", "St Lawrence", Some("Saint Lawrence"), &fr), StopNames { display: "St-Laurent", tts: "Saint-Laurent" });
+        assert_eq!(result.stop_display_and_tts("stop_2", "Station", Some("Station"), &fr), StopNames { display: "Gare", tts: "Gare" });
+        assert_eq!(result.stop_display_and_tts("stop_3", "Sq", Some("Square"), &fr), StopNames { display: "Sq", tts: "Square" });
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashSet;
+use std::sync::Arc;
+use language_tags::LanguageTag;
+use crate::key_strategy::uncount_key;
+use crate::{TranslatableField, TranslationKey, TranslationLookup, TranslationResult};
+
+// Editing keeps the languages and possible_translations in step with the translations,
+// which writing to the public fields directly does not
+impl TranslationResult {
+    // Adds or replaces a translation, returning the one it replaced. The provenance of a replaced translation goes with it.
+    pub fn insert(&mut self, field: TranslatableField, key: TranslationKey, language: LanguageTag, translation: impl Into<Arc<str>>) -> Option<Arc<str>> {
+        let lookup = TranslationLookup { language, field, key };
+        self.provenance.remove(&lookup);
+        self.insert_lookup(lookup, translation.into())
+    }
+
+    pub fn remove(&mut self, field: &TranslatableField, key: &TranslationKey, language: &LanguageTag) -> Option<Arc<str>> {
+        let lookup = TranslationLookup {
+            language: language.clone(),
+            field: field.clone(),
+            key: key.clone(),
+        };
+        let removed = self.translations.remove(&lookup)?;
+        self.forget_lookup(&lookup);
+
+        Some(removed)
+    }
+
+    // Keeps the translations the closure returns true for, which may also rewrite the text
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&TranslationLookup, &mut Arc<str>) -> bool,
+    {
+        self.translations.retain(|lookup, translation| keep(lookup, translation));
+        self.prune_summaries();
+    }
+
+    // Rewrites the record id of Record and RecordSub keys, such as prefixing them with the feed id when feeds are
+    // merged. Value keys and record sub ids are left alone. Ids the mapper sends to the same place replace each other.
+    pub fn remap_record_ids<F>(&mut self, mapper: F)
+    where
+        F: Fn(&TranslatableField, &str) -> String,
+    {
+        let remap = |mut lookup: TranslationLookup| {
+            lookup.key = match lookup.key {
+                TranslationKey::Record(record_id) => TranslationKey::Record(mapper(&lookup.field, &record_id)),
+                TranslationKey::RecordSub((record_id, record_sub_id)) => TranslationKey::RecordSub((mapper(&lookup.field, &record_id), record_sub_id)),
+                TranslationKey::Value(field_value) => TranslationKey::Value(field_value),
+            };
+            lookup
+        };
+
+        self.translations = std::mem::take(&mut self.translations).into_iter().map(|(lookup, translation)| (remap(lookup), translation)).collect();
+        self.provenance = std::mem::take(&mut self.provenance).into_iter().map(|(lookup, provenance)| (remap(lookup), provenance)).collect();
+        // ids sent to the same place leave fewer translations to count
+        self.recompute_key_counts();
+    }
+
+    // Replaces Value keys by the record keys the resolver lists for the field and original value, such as
+    // TranslationUniverse::records_with_value. Record keys that are already there are kept, as they take precedence
+    // over the value anyway. Values without any record stay as they are. Returns how many record keys were added.
+    pub fn materialize_value_keys<F, I>(&mut self, mut resolver: F) -> usize
+    where
+        F: FnMut(&TranslatableField, &str) -> I,
+        I: IntoIterator<Item = TranslationKey>,
+    {
+        let value_keys = self.translations.keys().filter(|lookup| matches!(lookup.key, TranslationKey::Value(_))).cloned().collect::<Vec<TranslationLookup>>();
+        let mut added = 0;
+
+        for lookup in value_keys {
+            let TranslationKey::Value(field_value) = &lookup.key else {
+                continue;
+            };
+            let records = resolver(&lookup.field, field_value).into_iter()
+                .filter(|key| !matches!(key, TranslationKey::Value(_)))
+                .collect::<Vec<TranslationKey>>();
+
+            if records.is_empty() {
+                continue;
+            }
+
+            let Some(translation) = self.translations.remove(&lookup) else {
+                continue;
+            };
+            let provenance = self.provenance.remove(&lookup);
+
+            for key in records {
+                let record = TranslationLookup { key, ..lookup.clone() };
+
+                if let Some(provenance) = &provenance {
+                    self.provenance.entry(record.clone()).or_insert_with(|| provenance.clone());
+                }
+
+                if let Entry::Vacant(entry) = self.translations.entry(record) {
+                    entry.insert(translation.clone());
+                    added += 1;
+                }
+            }
+        }
+
+        self.recompute_key_counts();
+
+        added
+    }
+
+    // Updates the summaries after the translation of the lookup was taken out of the map, dropping its provenance and
+    // its field and language pair or language when it was the last of them. Takes the time of a scan of the summaries,
+    // not of the translations, so removing many translations one at a time stays linear.
+    #[allow(deprecated)]
+    pub(crate) fn forget_lookup(&mut self, lookup: &TranslationLookup) {
+        self.provenance.remove(lookup);
+
+        if !uncount_key(&mut self.key_counts, &lookup.field, &lookup.language, &lookup.key) {
+            return;
+        }
+
+        self.possible_translations.retain(|(field, language)| field != &lookup.field || language != &lookup.language);
+
+        if !self.key_counts.values().any(|languages| languages.contains_key(&lookup.language)) {
+            self.avaliable_languages.retain(|language| language != &lookup.language);
+        }
+    }
+
+    // Drops the languages and field and language pairs that no translation uses any more, keeping the order of the rest,
+    // and the provenance of translations that are gone. One pass over the translations.
+    #[allow(deprecated)]
+    pub(crate) fn prune_summaries(&mut self) {
+        let translations = &self.translations;
+        self.provenance.retain(|lookup, _| translations.contains_key(lookup));
+
+        self.recompute_key_counts();
+        let counts = &self.key_counts;
+
+        self.possible_translations.retain(|(field, language)| counts.get(field).is_some_and(|languages| languages.contains_key(language)));
+        let languages = counts.values().flat_map(|languages| languages.keys()).collect::<HashSet<&LanguageTag>>();
+        self.avaliable_languages.retain(|language| languages.contains(language));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RouteFields, StopFields};
+
+    #[test]
+    fn keeps_summaries_in_sync() {
+        let mut result = TranslationResult::default();
+        let name = TranslatableField::Stops(StopFields::Name);
+        let route = TranslatableField::Routes(RouteFields::LongName);
+        let fr = LanguageTag::parse("fr").unwrap();
+        let de = LanguageTag::parse("de").unwrap();
+        let stop_1 = TranslationKey::Record("stop_1".to_string());
+
+        assert_eq!(result.insert(name.clone(), stop_1.clone(), fr.clone(), "Gare"), None);
+        assert_eq!(result.insert(name.clone(), stop_1.clone(), fr.clone(), "Gare SNCF"), Some("Gare".into()));
+        result.insert(route.clone(), TranslationKey::Value("Blue Line".to_string()), fr.clone(), "Ligne bleue");
+        result.insert(name.clone(), stop_1.clone(), de.clone(), "Bahnhof");
+
+        assert_eq!(result.languages_as_written(), [de.clone(), fr.clone()]);
+        assert_eq!(result.possible_translations.len(), 3);
+
+        assert_eq!(result.remove(&name, &stop_1, &de), Some("Bahnhof".into()));
+        assert_eq!(result.remove(&name, &stop_1, &de), None);
+        assert_eq!(result.languages_as_written(), vec![fr.clone()]);
+        assert_eq!(result.possible_translations, vec![(name.clone(), fr.clone()), (route.clone(), fr.clone())]);
+
+        result.retain(|lookup, translation| {
+            *translation = format!("{}!", translation).into();
+            lookup.field == route
+        });
+
+        assert_eq!(result.get(&route, &TranslationKey::Value("Blue Line".to_string()), &fr), Some("Ligne bleue!"));
+        assert_eq!(result.possible_translations, vec![(route, fr)]);
+        assert_eq!(result.translations.len(), 1);
+    }
+
+    #[test]
+    fn remaps_record_ids() {
+        let mut result = TranslationResult::default();
+        let name = TranslatableField::Stops(StopFields::Name);
+        let headsign = TranslatableField::StopTimes(crate::StopTimeFields::Headsign);
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        result.insert(name.clone(), TranslationKey::Record("stop_1".to_string()), fr.clone(), "Gare");
+        result.insert(headsign.clone(), TranslationKey::RecordSub(("trip_1".to_string(), "3".to_string())), fr.clone(), "Centre");
+        result.insert(name.clone(), TranslationKey::Value("Main St".to_string()), fr.clone(), "Rue principale");
+
+        result.remap_record_ids(|_, record_id| format!("agencyA:{}", record_id));
+
+        assert_eq!(result.resolve(&name, Some("agencyA:stop_1"), None, None, &fr), Some("Gare"));
+        assert_eq!(result.resolve(&headsign, Some("agencyA:trip_1"), Some("3"), None, &fr), Some("Centre"));
+        assert_eq!(result.resolve(&name, None, None, Some("Main St"), &fr), Some("Rue principale"));
+        assert_eq!(result.resolve(&name, Some("stop_1"), None, None, &fr), None);
+    }
+
+    #[test]
+    fn materializes_value_keys() {
+        let mut result = TranslationResult::default();
+        let name = TranslatableField::Stops(StopFields::Name);
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        result.insert(name.clone(), TranslationKey::Value("Central".to_string()), fr.clone(), "Centrale");
+        result.insert(name.clone(), TranslationKey::Record("stop_2".to_string()), fr.clone(), "Gare centrale");
+        result.insert(name.clone(), TranslationKey::Value("Nowhere".to_string()), fr.clone(), "Nulle part");
+
+        let mut universe = crate::universe::TranslationUniverse::new();
+        universe.push(name.clone(), Some("stop_1"), None, "Central");
+        universe.push(name.clone(), Some("stop_2"), None, "Central");
+        universe.push(name.clone(), Some("stop_3"), None, "Elsewhere");
+
+        assert_eq!(result.materialize_value_keys(|field, value| universe.records_with_value(field, value)), 1);
+        assert_eq!(result.get(&name, &TranslationKey::Record("stop_1".to_string()), &fr), Some("Centrale"));
+        assert_eq!(result.get(&name, &TranslationKey::Record("stop_2".to_string()), &fr), Some("Gare centrale"));
+        assert_eq!(result.get(&name, &TranslationKey::Value("Central".to_string()), &fr), None);
+        assert_eq!(result.get(&name, &TranslationKey::Value("Nowhere".to_string()), &fr), Some("Nulle part"));
+    }
+
+    #[test]
+    fn counts_replaced_and_merged_translations_once() {
+        let mut result = TranslationResult::default();
+        let name = TranslatableField::Stops(StopFields::Name);
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        result.insert(name.clone(), TranslationKey::Record("stop_1".to_string()), fr.clone(), "Gare");
+        result.insert(name.clone(), TranslationKey::Record("stop_1".to_string()), fr.clone(), "Gare SNCF");
+        result.insert(name.clone(), TranslationKey::Record("stop_2".to_string()), fr.clone(), "Place");
+
+        // both ids land on the same record, leaving one translation
+        result.remap_record_ids(|_, _| "stop".to_string());
+        assert_eq!(result.translations.len(), 1);
+
+        result.remove(&name, &TranslationKey::Record("stop".to_string()), &fr);
+        assert!(result.possible_translations.is_empty());
+        assert!(result.languages_as_written().is_empty());
+        assert_eq!(result.key_strategy(&name), None);
+        assert_eq!(result, TranslationResult::default());
+    }
+}
@@ -0,0 +1,296 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use crate::resolution::{resolution_order, resolve_with_fallback};
+use crate::{table_and_field_to_enum, TranslatableField};
+
+// First bytes of every embedded snapshot, then its version
+const MAGIC: &[u8; 4] = b"GTFE";
+pub const EMBEDDED_FORMAT_VERSION: u8 = 1;
+
+const KEY_RECORD: u8 = 0;
+const KEY_RECORD_SUB: u8 = 1;
+const KEY_VALUE: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddedError {
+    NotASnapshot,
+    UnsupportedVersion(u8),
+    Truncated,
+    InvalidKind(u8),
+    InvalidUtf8,
+    UntranslatableField,
+}
+
+impl fmt::Display for EmbeddedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmbeddedError::NotASnapshot => write!(f, "not an embedded translations snapshot"),
+            EmbeddedError::UnsupportedVersion(version) => write!(f, "unsupported embedded format version {}, expected {}", version, EMBEDDED_FORMAT_VERSION),
+            EmbeddedError::Truncated => write!(f, "embedded snapshot ends in the middle of an entry"),
+            EmbeddedError::InvalidKind(kind) => write!(f, "unknown key kind {} in embedded snapshot", kind),
+            EmbeddedError::InvalidUtf8 => write!(f, "embedded snapshot has text that is not UTF-8"),
+            EmbeddedError::UntranslatableField => write!(f, "embedded snapshot has a field translations.txt cannot translate"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EmbeddedError {}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EmbeddedKey<'a> {
+    Record(&'a str),
+    RecordSub(&'a str, &'a str),
+    Value(&'a str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry<'a> {
+    field: TranslatableField,
+    key: EmbeddedKey<'a>,
+    // lowercase, as the writer stores it
+    language: &'a str,
+    translation: &'a str,
+}
+
+impl Entry<'_> {
+    // By field, key and then language, which is compared as if lowercased so a lookup needs no copy of it
+    fn cmp_to(&self, field: &TranslatableField, key: EmbeddedKey<'_>, language: &str) -> Ordering {
+        self.field.cmp(field)
+            .then_with(|| self.key.cmp(&key))
+            .then_with(|| self.language.bytes().cmp(language.bytes().map(|byte| byte.to_ascii_lowercase())))
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], EmbeddedError> {
+        if self.bytes.len() < len {
+            return Err(EmbeddedError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, EmbeddedError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, EmbeddedError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn str(&mut self) -> Result<&'a str, EmbeddedError> {
+        let len = self.u32()? as usize;
+        core::str::from_utf8(self.take(len)?).map_err(|_| EmbeddedError::InvalidUtf8)
+    }
+}
+
+// A read-only lookup over a snapshot written by TranslationResult::to_embedded, for on-vehicle displays and other
+// controllers without std. Text is borrowed from the snapshot, which can stay in flash, and the index is one sorted
+// Vec searched by bisection. Fields and keys are the types TranslationResult uses; languages are plain strings
+// compared without case, as LanguageTag needs std.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbeddedTranslations<'a> {
+    entries: Vec<Entry<'a>>,
+}
+
+impl<'a> EmbeddedTranslations<'a> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<EmbeddedTranslations<'a>, EmbeddedError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len()).map_err(|_| EmbeddedError::NotASnapshot)? != MAGIC {
+            return Err(EmbeddedError::NotASnapshot);
+        }
+        match reader.u8()? {
+            EMBEDDED_FORMAT_VERSION => {}
+            version => return Err(EmbeddedError::UnsupportedVersion(version)),
+        }
+
+        let count = reader.u32()? as usize;
+        // every entry takes at least 21 bytes, so a corrupt count can't reserve more than the snapshot could hold
+        let mut entries = Vec::with_capacity(count.min(reader.bytes.len() / 21));
+
+        for _ in 0..count {
+            let kind = reader.u8()?;
+            let table_name = reader.str()?;
+            let field_name = reader.str()?;
+            let field = table_and_field_to_enum(table_name, field_name).ok_or(EmbeddedError::UntranslatableField)?;
+            let language = reader.str()?;
+            let key = match kind {
+                KEY_RECORD => EmbeddedKey::Record(reader.str()?),
+                KEY_RECORD_SUB => EmbeddedKey::RecordSub(reader.str()?, reader.str()?),
+                KEY_VALUE => EmbeddedKey::Value(reader.str()?),
+                kind => return Err(EmbeddedError::InvalidKind(kind)),
+            };
+            let translation = reader.str()?;
+
+            entries.push(Entry { field, key, language, translation });
+        }
+
+        // written sorted, this only costs a pass over the entries
+        entries.sort_unstable_by(|a, b| a.cmp_to(&b.field, b.key, b.language));
+
+        Ok(EmbeddedTranslations { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, field: &TranslatableField, key: EmbeddedKey<'_>, language: &str) -> Option<&'a str> {
+        self.entries
+            .binary_search_by(|entry| entry.cmp_to(field, key, language))
+            .ok()
+            .map(|index| self.entries[index].translation)
+    }
+
+    // TranslationResult::resolve: the language as given, with the key precedence the spec gives
+    pub fn resolve(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>, language: &str) -> Option<&'a str> {
+        resolution_order(record_id, record_sub_id, original_value).find_map(|key| self.get(field, key, language))
+    }
+
+    // TranslationResult::resolve_with_fallback, so fr-CA uses fr where there is no Canadian French translation
+    pub fn resolve_with_fallback(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>, language: &str) -> Option<&'a str> {
+        resolve_with_fallback(language, record_id, record_sub_id, original_value, |language, key| self.get(field, key, language))
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::TranslationResult {
+    // A snapshot for EmbeddedTranslations. Languages are lowercased and the entries sorted the way the reader searches them.
+    pub fn to_embedded(&self) -> Vec<u8> {
+        let mut entries = self.translations.iter()
+            .map(|(lookup, translation)| {
+                let (table_name, field_name) = crate::enum_to_table_and_field(&lookup.field);
+                (table_name, field_name, lookup.key.as_embedded(), lookup.language.as_str().to_ascii_lowercase(), translation.as_ref())
+            })
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| (a.0, a.1, a.2, a.3.as_str()).cmp(&(b.0, b.1, b.2, b.3.as_str())));
+
+        let mut bytes = Vec::with_capacity(16 + entries.len() * 48);
+        let push_str = |bytes: &mut Vec<u8>, text: &str| {
+            bytes.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(text.as_bytes());
+        };
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(EMBEDDED_FORMAT_VERSION);
+        bytes.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+
+        for (table_name, field_name, key, language, translation) in entries.iter() {
+            bytes.push(match key {
+                EmbeddedKey::Record(_) => KEY_RECORD,
+                EmbeddedKey::RecordSub(_, _) => KEY_RECORD_SUB,
+                EmbeddedKey::Value(_) => KEY_VALUE,
+            });
+            push_str(&mut bytes, table_name);
+            push_str(&mut bytes, field_name);
+            push_str(&mut bytes, language);
+            match key {
+                EmbeddedKey::Record(record_id) => push_str(&mut bytes, record_id),
+                EmbeddedKey::RecordSub(record_id, record_sub_id) => {
+                    push_str(&mut bytes, record_id);
+                    push_str(&mut bytes, record_sub_id);
+                }
+                EmbeddedKey::Value(field_value) => push_str(&mut bytes, field_value),
+            }
+            push_str(&mut bytes, translation);
+        }
+
+        bytes
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use language_tags::LanguageTag;
+    use crate::test_util::translations;
+    use crate::{StopFields, StopTimeFields, TranslationResult, TripFields};
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stop_times,stop_headsign,fr,Centre,trip_1,3,\n\
+            stop_times,stop_headsign,fr,Ville,trip_1,,\n\
+            trips,trip_headsign,de-CH,Innenstadt,,,Downtown\n\
+            trips,trip_headsign,de,Zentrum,trip_1,,\n")
+    }
+
+    const HEADSIGN: TranslatableField = TranslatableField::StopTimes(StopTimeFields::Headsign);
+    const NAME: TranslatableField = TranslatableField::Stops(StopFields::Name);
+    const TRIP_HEADSIGN: TranslatableField = TranslatableField::Trips(TripFields::Headsign);
+
+    #[test]
+    fn resolves_with_key_precedence() {
+        let snapshot = result().to_embedded();
+        let embedded = EmbeddedTranslations::from_bytes(&snapshot).unwrap();
+
+        assert_eq!(embedded.len(), 5);
+        assert_eq!(embedded.resolve(&HEADSIGN, Some("trip_1"), Some("3"), None, "fr"), Some("Centre"));
+        assert_eq!(embedded.resolve(&HEADSIGN, Some("trip_1"), Some("4"), None, "fr"), Some("Ville"));
+        assert_eq!(embedded.resolve(&TRIP_HEADSIGN, Some("trip_1"), None, Some("Downtown"), "de-CH"), Some("Innenstadt"));
+    }
+
+    #[test]
+    fn matches_languages_without_case() {
+        let snapshot = result().to_embedded();
+        let embedded = EmbeddedTranslations::from_bytes(&snapshot).unwrap();
+
+        assert_eq!(embedded.get(&TRIP_HEADSIGN, EmbeddedKey::Value("Downtown"), "DE-ch"), Some("Innenstadt"));
+        assert_eq!(embedded.get(&NAME, EmbeddedKey::Record("stop_1"), "FR"), Some("Gare"));
+    }
+
+    #[test]
+    fn falls_back_only_when_asked() {
+        let snapshot = result().to_embedded();
+        let embedded = EmbeddedTranslations::from_bytes(&snapshot).unwrap();
+
+        assert_eq!(embedded.resolve(&NAME, Some("stop_1"), None, None, "fr-CA"), None);
+        assert_eq!(embedded.resolve_with_fallback(&NAME, Some("stop_1"), None, None, "fr-CA"), Some("Gare"));
+        assert_eq!(embedded.resolve_with_fallback(&NAME, Some("stop_1"), None, None, "de"), None);
+    }
+
+    #[test]
+    fn answers_like_the_translation_result() {
+        let result = result();
+        let snapshot = result.to_embedded();
+        let embedded = EmbeddedTranslations::from_bytes(&snapshot).unwrap();
+
+        // de-CH by value beats de by record, as every key of a language is tried before the shorter tag
+        let queries = [
+            (&NAME, Some("stop_1"), None, None, "fr-CA"),
+            (&HEADSIGN, Some("trip_1"), Some("3"), None, "fr-BE"),
+            (&TRIP_HEADSIGN, Some("trip_1"), None, Some("Downtown"), "de-CH-1996"),
+            (&TRIP_HEADSIGN, Some("trip_1"), None, Some("Uptown"), "de-CH"),
+            (&NAME, Some("stop_1"), None, None, "de"),
+        ];
+
+        for (field, record_id, record_sub_id, original_value, language) in queries {
+            let tag = LanguageTag::parse(language).unwrap();
+
+            assert_eq!(embedded.resolve(field, record_id, record_sub_id, original_value, language), result.resolve(field, record_id, record_sub_id, original_value, &tag));
+            assert_eq!(embedded.resolve_with_fallback(field, record_id, record_sub_id, original_value, language), result.resolve_with_fallback(field, record_id, record_sub_id, original_value, &tag));
+        }
+        assert_eq!(result.resolve_with_fallback(&TRIP_HEADSIGN, Some("trip_1"), None, Some("Downtown"), &LanguageTag::parse("de-CH-1996").unwrap()), Some("Innenstadt"));
+        assert_eq!(result.resolve_with_fallback(&TRIP_HEADSIGN, Some("trip_1"), None, Some("Uptown"), &LanguageTag::parse("de-CH").unwrap()), Some("Zentrum"));
+    }
+
+    #[test]
+    fn rejects_truncated_and_newer_snapshots() {
+        let snapshot = result().to_embedded();
+
+        assert_eq!(EmbeddedTranslations::from_bytes(&snapshot[..snapshot.len() - 1]), Err(EmbeddedError::Truncated));
+        assert_eq!(EmbeddedTranslations::from_bytes(b"GTFE\x09"), Err(EmbeddedError::UnsupportedVersion(9)));
+    }
+}
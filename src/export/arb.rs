@@ -0,0 +1,110 @@
+use language_tags::LanguageTag;
+use serde_json::{json, Value};
+use crate::export::{collect_units, resource_names};
+use crate::{enum_to_table_and_field, key_struct_to_options, TranslationResult};
+
+// Messages are ICU MessageFormat, where braces start a placeholder. Quoting them with apostrophes, and doubling
+// the apostrophes of the text, keeps a headsign like Gate {A} literal; gen-l10n reads it so with use-escaping.
+fn escape_message(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\'' => escaped.push_str("''"),
+            '{' => escaped.push_str("'{'"),
+            '}' => escaped.push_str("'}'"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+// An ARB file for one language, for Flutter's gen-l10n. Each message has an @ entry saying which table, field and
+// record it translates, in x- attributes. The file for source_lang is the template with every source string, the
+// others only have the messages translated into them so Flutter falls back to the template for the rest.
+pub fn arb(result: &TranslationResult, source_lang: &LanguageTag, language: &LanguageTag) -> String {
+    let names = resource_names(result);
+    let mut entries = vec![("@@locale".to_string(), Value::String(language.to_string()))];
+
+    for unit in collect_units(result, source_lang, language) {
+        let text = match language == source_lang {
+            true => unit.source.as_ref(),
+            false => unit.target.as_ref(),
+        };
+        let (Some(text), Some(name)) = (text, names.get(&(unit.field.clone(), unit.key.clone()))) else {
+            continue;
+        };
+
+        let (table_name, field_name) = enum_to_table_and_field(&unit.field);
+        let (record_id, record_sub_id, field_value) = key_struct_to_options(&unit.key);
+        let description = match (&record_id, &field_value) {
+            (Some(record_id), _) => format!("{}.{} of {}", table_name, field_name, record_id),
+            (None, Some(field_value)) => format!("{}.{} where it is \"{}\"", table_name, field_name, field_value),
+            (None, None) => format!("{}.{}", table_name, field_name),
+        };
+
+        let mut metadata = json!({"description": description, "x-table": table_name, "x-field": field_name});
+        for (attribute, value) in [("x-record-id", record_id), ("x-record-sub-id", record_sub_id), ("x-field-value", field_value)] {
+            if let Some(value) = value {
+                metadata[attribute] = Value::String(value);
+            }
+        }
+
+        entries.push((name.clone(), Value::String(escape_message(text))));
+        entries.push((format!("@{}", name), metadata));
+    }
+
+    // written by hand to keep each message next to its metadata, which a serde_json map would sort apart
+    let body = entries.iter()
+        .map(|(key, value)| format!("  {}: {}", Value::String(key.clone()), value))
+        .collect::<Vec<String>>()
+        .join(",\n");
+
+    format!("{{\n{}\n}}\n", body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,en,Station,stop-1,,\n\
+            stops,stop_name,en,Square,stop_1,,\n\
+            stops,stop_name,fr,Gare,stop-1,,\n\
+            stops,stop_name,en,Gate {A},stop_2,,\n\
+            stops,stop_name,en,King's Cross,stop_3,,\n")
+    }
+
+    fn template() -> serde_json::Map<String, Value> {
+        let en = LanguageTag::parse("en").unwrap();
+        serde_json::from_str(&arb(&result(), &en, &en)).unwrap()
+    }
+
+    #[test]
+    fn numbers_colliding_ids_with_their_record() {
+        let template = template();
+
+        assert_eq!(template["@@locale"], "en");
+        assert_eq!(template["stops_name_stop_1"], "Station");
+        assert_eq!(template["stops_name_stop_1_2"], "Square");
+        assert_eq!(template["@stops_name_stop_1_2"]["x-record-id"], "stop_1");
+    }
+
+    #[test]
+    fn quotes_icu_syntax() {
+        let template = template();
+
+        assert_eq!(template["stops_name_stop_2"], "Gate '{'A'}'");
+        assert_eq!(template["stops_name_stop_3"], "King''s Cross");
+    }
+
+    #[test]
+    fn writes_only_the_translated_messages_of_a_language() {
+        let french = arb(&result(), &LanguageTag::parse("en").unwrap(), &LanguageTag::parse("fr").unwrap());
+
+        assert!(french.starts_with("{\n  \"@@locale\": \"fr\",\n  \"stops_name_stop_1\": \"Gare\",\n  \"@stops_name_stop_1\": {"));
+        assert!(!french.contains("Square"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use language_tags::LanguageTag;
+use crate::export::{short_field_name, KeyCodec};
+use crate::{enum_to_table_and_field, key_struct_to_options, TranslatableField, TranslationKey, TranslationResult};
+
+// Fluent identifiers are [a-zA-Z][a-zA-Z0-9_-]*, anything else becomes an underscore
+fn sanitize_identifier(part: &str) -> String {
+    part.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' }).collect()
+}
+
+fn message_id(table_name: &str, field_name: &str, key: &TranslationKey) -> String {
+    let parts = match key {
+        TranslationKey::Record(record_id) => vec![table_name, record_id, field_name],
+        TranslationKey::RecordSub((record_id, record_sub_id)) => vec![table_name, record_id, record_sub_id, field_name],
+        TranslationKey::Value(field_value) => vec![table_name, "value", field_name, field_value],
+    };
+
+    parts.iter().map(|part| sanitize_identifier(part)).collect::<Vec<String>>().join("-")
+}
+
+// Braces open placeables and a line starting with [, * or . would be read as a variant or attribute,
+// so those are written as string literal placeables. Leading spaces are significant, so they are too.
+fn escape_line(line: &str) -> String {
+    let mut escaped = String::with_capacity(line.len());
+
+    for (i, c) in line.chars().enumerate() {
+        match c {
+            '{' => escaped.push_str("{\"{\"}"),
+            '}' => escaped.push_str("{\"}\"}"),
+            '[' | '*' | '.' if i == 0 => escaped.push_str(&format!("{{\"{}\"}}", c)),
+            ' ' if i == 0 => escaped.push_str("{\" \"}"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn escape_value(value: &str) -> String {
+    // a message needs a value, so an empty translation becomes an empty string literal
+    if value.is_empty() {
+        return "{\"\"}".to_string();
+    }
+
+    value.replace("\r\n", "\n").split('\n').map(escape_line).collect::<Vec<String>>().join("\n    ")
+}
+
+// Ids derived from table, record and field, e.g. `stops-stop_1-name`. Ids that collide after sanitizing get a numeric
+// suffix, numbered in sorted order over the whole result so that a message has the same id in every language.
+fn message_ids(result: &TranslationResult) -> HashMap<(TranslatableField, TranslationKey), String> {
+    let mut keys = result.translations.keys()
+        .map(|lookup| (lookup.field.clone(), lookup.key.clone()))
+        .collect::<HashSet<(TranslatableField, TranslationKey)>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    keys.sort_by_cached_key(|(field, key)| (enum_to_table_and_field(field), key_struct_to_options(key)));
+
+    let mut used_ids: HashSet<String> = HashSet::new();
+    let mut ids = HashMap::new();
+
+    for (field, key) in keys {
+        let (table_name, _) = enum_to_table_and_field(&field);
+
+        let base_id = message_id(table_name, short_field_name(&field), &key);
+        let base_id = if base_id.starts_with(|c: char| c.is_ascii_alphabetic()) { base_id } else { format!("m-{}", base_id) };
+
+        let mut id = base_id.clone();
+        let mut suffix = 2;
+        while !used_ids.insert(id.clone()) {
+            id = format!("{}-{}", base_id, suffix);
+            suffix += 1;
+        }
+
+        ids.insert((field, key), id);
+    }
+
+    ids
+}
+
+fn write_with_ids(result: &TranslationResult, language: &LanguageTag, ids: &HashMap<(TranslatableField, TranslationKey), String>) -> String {
+    let mut entries = result.translations.iter()
+        .filter(|(lookup, _)| &lookup.language == language)
+        .collect::<Vec<_>>();
+    entries.sort_by_cached_key(|(lookup, _)| (enum_to_table_and_field(&lookup.field), key_struct_to_options(&lookup.key)));
+
+    let mut ftl = String::new();
+
+    for (lookup, translation) in entries {
+        let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+        let Some(id) = ids.get(&(lookup.field.clone(), lookup.key.clone())) else {
+            continue;
+        };
+
+        let (record_id, record_sub_id, field_value) = key_struct_to_options(&lookup.key);
+        let location = match (record_id, record_sub_id, field_value) {
+            (Some(record_id), Some(record_sub_id), _) => format!("record_id={} record_sub_id={}", record_id, record_sub_id),
+            (Some(record_id), None, _) => format!("record_id={}", record_id),
+            (_, _, Some(field_value)) => format!("field_value={}", field_value),
+            _ => String::new(),
+        };
+
+        let _ = writeln!(ftl, "# {}.{} {}", table_name, field_name, location.replace('\n', " "));
+        let _ = writeln!(ftl, "{} = {}", id, escape_value(translation));
+    }
+
+    ftl
+}
+
+// One Fluent resource for a language, with the ids of message_ids
+pub fn write(result: &TranslationResult, language: &LanguageTag) -> String {
+    write_with_ids(result, language, &message_ids(result))
+}
+
+// With the ids of a KeyCodec instead, which can be decoded. They must be Fluent identifiers, as those of
+// `UnitIdCodec::new('-', '_')` are.
+pub fn write_with_codec(result: &TranslationResult, language: &LanguageTag, codec: &dyn KeyCodec) -> String {
+    let ids = result.translations.keys()
+        .filter(|lookup| &lookup.language == language)
+        .map(|lookup| ((lookup.field.clone(), lookup.key.clone()), codec.encode(&lookup.field, &lookup.key)))
+        .collect();
+
+    write_with_ids(result, language, &ids)
+}
+
+// Every language, assigning the ids once
+pub fn write_all(result: &TranslationResult) -> HashMap<LanguageTag, String> {
+    let ids = message_ids(result);
+
+    result.languages_as_written().iter()
+        .map(|language| (language.clone(), write_with_ids(result, language, &ids)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::UnitIdCodec;
+    use crate::test_util::translations;
+
+    #[test]
+    fn escapes_fluent_syntax() {
+        let result = translations("stops,stop_name,fr,Gare {Nord},stop_1,,\n\
+            trips,trip_headsign,fr,\"Centre\n* ville\",,,Down town\n\
+            routes,route_long_name,fr, Bleue,route_1,,\n");
+
+        let ftl = write(&result, &LanguageTag::parse("fr").unwrap());
+
+        assert!(ftl.contains("stops-stop_1-name = Gare {\"{\"}Nord{\"}\"}\n"));
+        assert!(ftl.contains("trips-value-headsign-Down_town = Centre\n    {\"*\"} ville\n"));
+        assert!(ftl.contains("routes-route_1-long_name = {\" \"}Bleue\n"));
+    }
+
+    #[test]
+    fn numbers_colliding_ids() {
+        let result = translations("stops,stop_name,fr,Gare Nord,stop:1,,\nstops,stop_name,fr,Gare Sud,stop.1,,\n");
+
+        let ftl = write(&result, &LanguageTag::parse("fr").unwrap());
+
+        assert!(ftl.contains("stops-stop_1-name = Gare Sud\n"));
+        assert!(ftl.contains("stops-stop_1-name-2 = Gare Nord\n"));
+    }
+
+    #[test]
+    fn writes_codec_ids() {
+        let result = translations("stops,stop_name,fr,Gare Nord,stop:1,,\n");
+        let codec = UnitIdCodec::new('-', '_').unwrap();
+
+        let ftl = write_with_codec(&result, &LanguageTag::parse("fr").unwrap(), &codec);
+
+        assert!(ftl.contains("stops-stop_name-record-stop_3A1 = Gare Nord\n"));
+        assert!(codec.decode("stops-stop_name-record-stop_3A1").is_some());
+    }
+
+    #[test]
+    fn shares_ids_across_languages() {
+        // only the de file has the record that sorts first, which must not shift the ids of the fr file
+        let result = translations("stops,stop_name,de,Nordbahnhof,stop.1,,\n\
+            stops,stop_name,fr,Gare Nord,stop:1,,\n\
+            stops,stop_name,de,Bahnhof Nord,stop:1,,\n");
+
+        let files = write_all(&result);
+        let id_of = |language: &str, text: &str| files[&LanguageTag::parse(language).unwrap()].lines()
+            .find(|line| line.ends_with(text))
+            .map(|line| line.split(" = ").next().unwrap().to_string());
+
+        assert_eq!(id_of("fr", "Gare Nord"), id_of("de", "Bahnhof Nord"));
+        assert_ne!(id_of("de", "Nordbahnhof"), id_of("de", "Bahnhof Nord"));
+    }
+}
@@ -0,0 +1,102 @@
+use language_tags::LanguageTag;
+use serde_json::{Map, Value};
+use crate::export::{short_field_name, KeyCodec, UnitIdCodec};
+use crate::{enum_to_table_and_field, TranslationKey, TranslationResult};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsonBundleOptions {
+    // Emit `{"stops:stop_name:record:stop_1": "..."}` instead of nested objects, keyed by the unit ids of the codec so
+    // weblate::merge reads a flat bundle back
+    pub flat: bool,
+}
+
+// Value-keyed translations live under `_values.<table>.<field>.<original value>`.
+const VALUES_KEY: &str = "_values";
+
+fn key_path(table_name: &str, field_name: &str, key: &TranslationKey) -> Vec<String> {
+    match key {
+        TranslationKey::Record(record_id) => vec![table_name.to_string(), record_id.clone(), field_name.to_string()],
+        TranslationKey::RecordSub((record_id, record_sub_id)) => vec![table_name.to_string(), record_id.clone(), record_sub_id.clone(), field_name.to_string()],
+        TranslationKey::Value(field_value) => vec![VALUES_KEY.to_string(), table_name.to_string(), field_name.to_string(), field_value.clone()],
+    }
+}
+
+fn insert_nested(root: &mut Map<String, Value>, path: &[String], translation: &str) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+
+    let mut node = root;
+
+    for part in parents {
+        let child = node.entry(part.clone()).or_insert_with(|| Value::Object(Map::new()));
+
+        // a string already sitting where an object is needed wins over the deeper entry
+        node = match child {
+            Value::Object(map) => map,
+            _ => return,
+        };
+    }
+
+    node.insert(last.clone(), Value::String(translation.to_string()));
+}
+
+// Builds an i18next-style bundle such as `stops.<stop_id>.name` or `routes.<route_id>.long_name` for one language.
+pub fn json_bundle(result: &TranslationResult, language: &LanguageTag, options: &JsonBundleOptions) -> Value {
+    json_bundle_with_codec(result, language, options, &UnitIdCodec::default())
+}
+
+pub fn json_bundle_with_codec(result: &TranslationResult, language: &LanguageTag, options: &JsonBundleOptions, codec: &dyn KeyCodec) -> Value {
+    let mut root = Map::new();
+
+    for (lookup, translation) in result.translations.iter().filter(|(lookup, _)| &lookup.language == language) {
+        if options.flat {
+            root.insert(codec.encode(&lookup.field, &lookup.key), Value::String(translation.to_string()));
+        } else {
+            let (table_name, _) = enum_to_table_and_field(&lookup.field);
+            insert_nested(&mut root, &key_path(table_name, short_field_name(&lookup.field), &lookup.key), translation);
+        }
+    }
+
+    Value::Object(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn fixture() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            routes,route_long_name,fr,Ligne Bleue,route_1,,\n\
+            stop_times,stop_headsign,fr,Centre,trip_1,5,\n\
+            trips,trip_headsign,fr,Centre-ville,,,Downtown\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n")
+    }
+
+    #[test]
+    fn nests_by_table_and_record() {
+        let nested = json_bundle(&fixture(), &LanguageTag::parse("fr").unwrap(), &JsonBundleOptions::default());
+
+        assert_eq!(nested, serde_json::json!({
+            "stops": {"stop_1": {"name": "Gare"}},
+            "routes": {"route_1": {"long_name": "Ligne Bleue"}},
+            "stop_times": {"trip_1": {"5": {"headsign": "Centre"}}},
+            "_values": {"trips": {"headsign": {"Downtown": "Centre-ville"}}},
+        }));
+    }
+
+    #[test]
+    fn keys_flat_bundles_by_unit_id() {
+        let fr = LanguageTag::parse("fr").unwrap();
+        let flat = json_bundle(&fixture(), &fr, &JsonBundleOptions { flat: true });
+
+        assert_eq!(flat["stops:stop_name:record:stop_1"], "Gare");
+        assert_eq!(flat["stop_times:stop_headsign:sub:trip_1:5"], "Centre");
+        assert_eq!(flat.as_object().unwrap().len(), 4);
+
+        let dots = UnitIdCodec::new('.', '%').unwrap();
+        let flat = json_bundle_with_codec(&fixture(), &fr, &JsonBundleOptions { flat: true }, &dots);
+        assert_eq!(flat["trips.trip_headsign.value.Downtown"], "Centre-ville");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{enum_to_table_and_field, table_and_field_to_enum, TranslatableField, TranslationKey};
+
+// Turns a field and key into the flat string id of the xliff, po, weblate and sync formats, and back again for
+// merging. ARB and the mobile formats need identifiers instead and use resource names, which can't be decoded.
+pub trait KeyCodec {
+    fn encode(&self, field: &TranslatableField, key: &TranslationKey) -> String;
+    fn decode(&self, id: &str) -> Option<(TranslatableField, TranslationKey)>;
+}
+
+// Ids like `stops:stop_name:record:stop_1`. Bytes of the key parts other than ascii letters, digits, _ and - are
+// escaped as the escape character and two uppercase hex digits, so by default ids only contain NMTOKEN characters.
+// Table and field names have letters and underscores, so the separator can't be either, and the escape can't be a
+// letter or digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitIdCodec {
+    separator: char,
+    escape: char,
+}
+
+impl Default for UnitIdCodec {
+    fn default() -> UnitIdCodec {
+        UnitIdCodec {
+            separator: ':',
+            escape: '.',
+        }
+    }
+}
+
+impl UnitIdCodec {
+    pub fn new(separator: char, escape: char) -> Result<UnitIdCodec, String> {
+        if separator.is_ascii_alphanumeric() || separator == '_' {
+            return Err(format!("the separator {:?} can appear in table and field names", separator));
+        }
+
+        if escape.is_ascii_alphanumeric() {
+            return Err(format!("the escape {:?} can't be a letter or digit", escape));
+        }
+
+        if separator == escape {
+            return Err(format!("the separator and the escape are both {:?}", separator));
+        }
+
+        Ok(UnitIdCodec { separator, escape })
+    }
+
+    pub fn separator(&self) -> char {
+        self.separator
+    }
+
+    pub fn escape(&self) -> char {
+        self.escape
+    }
+
+    fn is_literal(&self, byte: u8) -> bool {
+        let character = byte as char;
+
+        (byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-') && character != self.separator && character != self.escape
+    }
+
+    fn escape_part(&self, part: &str) -> String {
+        let mut escaped = String::with_capacity(part.len());
+
+        for byte in part.bytes() {
+            if self.is_literal(byte) {
+                escaped.push(byte as char);
+            } else {
+                escaped.push(self.escape);
+                escaped.push_str(&format!("{:02X}", byte));
+            }
+        }
+
+        escaped
+    }
+
+    // Only takes what escape_part writes, so every id decodes to one key and encodes back to the same id
+    fn unescape_part(&self, part: &str) -> Option<String> {
+        let mut unescaped: Vec<u8> = Vec::with_capacity(part.len());
+        let mut rest = part;
+
+        while let Some(character) = rest.chars().next() {
+            rest = &rest[character.len_utf8()..];
+
+            if character == self.escape {
+                let digits = rest.get(..2)?;
+                if !digits.bytes().all(|digit| digit.is_ascii_digit() || (b'A'..=b'F').contains(&digit)) {
+                    return None;
+                }
+
+                let byte = u8::from_str_radix(digits, 16).ok()?;
+                if self.is_literal(byte) {
+                    return None;
+                }
+
+                unescaped.push(byte);
+                rest = &rest[2..];
+            } else if character.is_ascii() && self.is_literal(character as u8) {
+                unescaped.push(character as u8);
+            } else {
+                return None;
+            }
+        }
+
+        String::from_utf8(unescaped).ok()
+    }
+}
+
+impl KeyCodec for UnitIdCodec {
+    fn encode(&self, field: &TranslatableField, key: &TranslationKey) -> String {
+        let (table_name, field_name) = enum_to_table_and_field(field);
+        let parts = match key {
+            TranslationKey::Record(record_id) => vec!["record".to_string(), self.escape_part(record_id)],
+            TranslationKey::RecordSub((record_id, record_sub_id)) => vec!["sub".to_string(), self.escape_part(record_id), self.escape_part(record_sub_id)],
+            TranslationKey::Value(field_value) => vec!["value".to_string(), self.escape_part(field_value)],
+        };
+
+        let mut id = format!("{}{}{}", table_name, self.separator, field_name);
+        for part in parts {
+            id.push(self.separator);
+            id.push_str(&part);
+        }
+        id
+    }
+
+    fn decode(&self, id: &str) -> Option<(TranslatableField, TranslationKey)> {
+        let parts = id.split(self.separator).collect::<Vec<&str>>();
+
+        let field = table_and_field_to_enum(parts.first()?, parts.get(1)?)?;
+
+        let key = match (parts.get(2).copied()?, parts.len()) {
+            ("record", 4) => TranslationKey::Record(self.unescape_part(parts[3])?),
+            ("sub", 5) => TranslationKey::RecordSub((self.unescape_part(parts[3])?, self.unescape_part(parts[4])?)),
+            ("value", 4) => TranslationKey::Value(self.unescape_part(parts[3])?),
+            _ => return None,
+        };
+
+        Some((field, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StopFields, StopTimeFields};
+
+    #[test]
+    fn round_trips_with_the_default_rules() {
+        let name = TranslatableField::Stops(StopFields::Name);
+        let value = TranslationKey::Value("Main St. / 5th: North".to_string());
+
+        let codec = UnitIdCodec::default();
+        assert_eq!(codec.encode(&name, &value), "stops:stop_name:value:Main.20St.2E.20.2F.205th.3A.20North");
+        assert_eq!(codec.decode(&codec.encode(&name, &value)), Some((name, value)));
+    }
+
+    #[test]
+    fn round_trips_with_custom_rules() {
+        let slashes = UnitIdCodec::new('/', '%').unwrap();
+        let headsign = TranslatableField::StopTimes(StopTimeFields::Headsign);
+        let sub = TranslationKey::RecordSub(("trip/1".to_string(), "3".to_string()));
+
+        assert_eq!((slashes.separator(), slashes.escape()), ('/', '%'));
+        assert_eq!(slashes.encode(&headsign, &sub), "stop_times/stop_headsign/sub/trip%2F1/3");
+        assert_eq!(slashes.decode("stop_times/stop_headsign/sub/trip%2F1/3"), Some((headsign, sub)));
+    }
+
+    #[test]
+    fn rejects_ambiguous_rules() {
+        assert!(UnitIdCodec::new('_', '%').is_err());
+        assert!(UnitIdCodec::new('a', '%').is_err());
+        assert!(UnitIdCodec::new(':', '7').is_err());
+        assert!(UnitIdCodec::new(':', ':').is_err());
+        assert!(UnitIdCodec::new('-', '_').is_ok());
+    }
+
+    #[test]
+    fn rejects_ids_it_would_not_write() {
+        let codec = UnitIdCodec::default();
+
+        assert_eq!(codec.decode("stops:stop_name:record:bad.Z"), None);
+        assert_eq!(codec.decode("stops:stop_name:record:bad.+F"), None);
+        assert_eq!(codec.decode("stops:stop_name:record:bad.2f"), None);
+        assert_eq!(codec.decode("stops:stop_name:record:bad.41"), None);
+        assert_eq!(codec.decode("stops:stop_name:record:bad id"), None);
+        assert_eq!(codec.decode("stops:stop_name:record"), None);
+        assert_eq!(codec.decode("stops:stop_nom:record:stop_1"), None);
+    }
+}
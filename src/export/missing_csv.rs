@@ -0,0 +1,63 @@
+use language_tags::LanguageTag;
+use crate::universe::TranslationUniverse;
+use crate::{enum_to_table_and_field, TranslationResult};
+
+// Lists every entry of the universe with no translation in target_lang. The output is in the wide csv layout
+// with an empty column for the target language, so the filled-in sheet can go straight back through import::wide_csv.
+pub fn missing_csv(universe: &TranslationUniverse, result: &TranslationResult, target_lang: &LanguageTag) -> String {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+
+    let _ = wtr.write_record(["table", "field", "record_id", "record_sub_id", "original", target_lang.as_str()]);
+
+    let mut missing = universe.untranslated(result, target_lang).collect::<Vec<_>>();
+    missing.sort_by_cached_key(|entry| (enum_to_table_and_field(&entry.field), entry.record_id.clone(), entry.record_sub_id.clone(), entry.original_value.clone()));
+    missing.dedup();
+
+    for entry in missing {
+        let (table_name, field_name) = enum_to_table_and_field(&entry.field);
+
+        let _ = wtr.write_record([
+            table_name,
+            field_name,
+            entry.record_id.as_deref().unwrap_or(""),
+            entry.record_sub_id.as_deref().unwrap_or(""),
+            &entry.original_value,
+            "",
+        ]);
+    }
+
+    String::from_utf8(wtr.into_inner().expect("writing csv into a Vec cannot fail")).expect("csv writer only emits the utf-8 it was given")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{StopFields, TranslatableField, TripFields};
+
+    fn missing() -> String {
+        let result = translations("stops,stop_name,fr,Gare,stop_1,,\ntrips,trip_headsign,fr,Centre-ville,,,Downtown\n");
+
+        let mut universe = TranslationUniverse::new();
+        universe.push(TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, "Station");
+        universe.push(TranslatableField::Stops(StopFields::Name), Some("stop_2"), None, "Main St, North");
+        universe.push(TranslatableField::Trips(TripFields::Headsign), Some("trip_1"), None, "Downtown");
+
+        missing_csv(&universe, &result, &LanguageTag::parse("fr").unwrap())
+    }
+
+    #[test]
+    fn lists_untranslated_entries() {
+        // trip_1 resolves through the value key, so only stop_2 is left
+        assert_eq!(missing(), "table,field,record_id,record_sub_id,original,fr\n\
+            stops,stop_name,stop_2,,\"Main St, North\",\n");
+    }
+
+    #[test]
+    fn reads_back_as_wide_csv() {
+        let rows = crate::import::wide_csv(&missing().replace("North\",\n", "North\",\"Rue Main, Nord\"\n")).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].translation, "Rue Main, Nord");
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use language_tags::LanguageTag;
+use quick_xml::escape::partial_escape;
+use crate::export::{collect_units, resource_names, ExportUnit};
+use crate::{enum_to_table_and_field, TranslationResult};
+
+// (name, text, table.field) of the strings of one language: every source string for source_lang, and only the
+// translated ones otherwise, as both platforms fall back to the development language for the rest
+fn strings(result: &TranslationResult, source_lang: &LanguageTag, language: &LanguageTag) -> Vec<(String, String, String)> {
+    let names = resource_names(result);
+
+    let mut strings = collect_units(result, source_lang, language).into_iter()
+        .filter_map(|unit: ExportUnit| {
+            let name = names.get(&(unit.field.clone(), unit.key.clone()))?.clone();
+            let text = match language == source_lang {
+                true => unit.source?,
+                false => unit.target?,
+            };
+            let (table_name, field_name) = enum_to_table_and_field(&unit.field);
+
+            Some((name, text, format!("{}.{}", table_name, field_name)))
+        })
+        .collect::<Vec<_>>();
+
+    strings.sort();
+    strings
+}
+
+fn escape_android(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for (index, character) in text.chars().enumerate() {
+        match character {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            // a leading @ or ? would be read as a reference to another resource
+            '@' | '?' if index == 0 => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            _ => escaped.push(character),
+        }
+    }
+
+    partial_escape(&escaped).into_owned()
+}
+
+// The resource folder of a language: values for the source language, values-fr, and the b+ form for tags
+// Android's older qualifiers cannot express, such as values-b+zh+Hant
+pub fn android_folder(source_lang: &LanguageTag, language: &LanguageTag) -> String {
+    if language == source_lang {
+        return "values".to_string();
+    }
+
+    let primary = language.primary_language();
+
+    match language.region() {
+        _ if language.as_str() == primary => format!("values-{}", primary),
+        // the r form only takes two letter regions, so UN M.49 codes such as es-419 need b+
+        Some(region) if region.len() == 2 && region.bytes().all(|byte| byte.is_ascii_alphabetic()) && language.as_str() == format!("{}-{}", primary, region) => {
+            format!("values-{}-r{}", primary, region)
+        }
+        _ => format!("values-b+{}", language.as_str().replace('-', "+")),
+    }
+}
+
+// An Android strings.xml for one language
+pub fn android_strings(result: &TranslationResult, source_lang: &LanguageTag, language: &LanguageTag) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<resources>\n");
+
+    for (name, text, location) in strings(result, source_lang, language) {
+        let _ = writeln!(xml, "    <!-- {} -->", location);
+        let _ = writeln!(xml, "    <string name=\"{}\">{}</string>", name, escape_android(&text));
+    }
+
+    xml.push_str("</resources>\n");
+    xml
+}
+
+// Paths such as values-fr/strings.xml to contents, for every language of the result
+pub fn android_files(result: &TranslationResult, source_lang: &LanguageTag) -> BTreeMap<String, String> {
+    let mut languages = result.languages_as_written().to_vec();
+    if !languages.contains(source_lang) {
+        languages.push(source_lang.clone());
+    }
+
+    languages.iter()
+        .map(|language| (format!("{}/strings.xml", android_folder(source_lang, language)), android_strings(result, source_lang, language)))
+        .collect()
+}
+
+fn escape_ios(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t")
+}
+
+// An iOS Localizable.strings for one language
+pub fn ios_strings(result: &TranslationResult, source_lang: &LanguageTag, language: &LanguageTag) -> String {
+    let mut strings_file = String::new();
+
+    for (name, text, location) in strings(result, source_lang, language) {
+        let _ = writeln!(strings_file, "/* {} */\n\"{}\" = \"{}\";\n", location, name, escape_ios(&text));
+    }
+
+    strings_file
+}
+
+// Paths such as fr.lproj/Localizable.strings to contents, for every language of the result
+pub fn ios_files(result: &TranslationResult, source_lang: &LanguageTag) -> BTreeMap<String, String> {
+    let mut languages = result.languages_as_written().to_vec();
+    if !languages.contains(source_lang) {
+        languages.push(source_lang.clone());
+    }
+
+    languages.iter()
+        .map(|language| (format!("{}.lproj/Localizable.strings", language), ios_strings(result, source_lang, language)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,en,Joe's \"Place\",stop_1,,\n\
+            stops,stop_name,en,@Home & Away,stop_2,,\n\
+            stops,stop_name,fr,Chez Joe,stop_1,,\n\
+            stops,stop_name,zh-Hant,車站,stop_1,,\n")
+    }
+
+    #[test]
+    fn writes_android_resources() {
+        let android = android_files(&result(), &LanguageTag::parse("en").unwrap());
+
+        assert_eq!(android.keys().collect::<Vec<_>>(), vec!["values-b+zh+Hant/strings.xml", "values-fr/strings.xml", "values/strings.xml"]);
+        assert!(android["values/strings.xml"].contains("<string name=\"stops_name_stop_1\">Joe\\'s \\\"Place\\\"</string>"));
+        assert!(android["values/strings.xml"].contains("<string name=\"stops_name_stop_2\">\\@Home &amp; Away</string>"));
+        assert!(!android["values-fr/strings.xml"].contains("stop_2"));
+    }
+
+    #[test]
+    fn names_android_folders() {
+        let en = LanguageTag::parse("en").unwrap();
+
+        assert_eq!(android_folder(&en, &en), "values");
+        assert_eq!(android_folder(&en, &LanguageTag::parse("fr-CA").unwrap()), "values-fr-rCA");
+        assert_eq!(android_folder(&en, &LanguageTag::parse("es-419").unwrap()), "values-b+es+419");
+    }
+
+    #[test]
+    fn writes_ios_strings() {
+        let ios = ios_files(&result(), &LanguageTag::parse("en").unwrap());
+
+        assert_eq!(ios["fr.lproj/Localizable.strings"], "/* stops.stop_name */\n\"stops_name_stop_1\" = \"Chez Joe\";\n\n");
+        assert!(ios["en.lproj/Localizable.strings"].contains("\"stops_name_stop_1\" = \"Joe's \\\"Place\\\"\";"));
+    }
+}
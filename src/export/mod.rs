@@ -0,0 +1,149 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use language_tags::LanguageTag;
+use crate::{enum_to_table_and_field, key_struct_to_options};
+use crate::{AgencyFields, AreaFields, CalendarFields, FareProductFields, FeedInfoFields, RouteFields, StopFields, StopTimeFields, TripFields};
+use crate::{TranslatableField, TranslationKey, TranslationResult};
+
+mod arb;
+#[cfg(feature = "fluent")]
+pub mod fluent;
+mod json_bundle;
+mod key_codec;
+mod missing_csv;
+#[cfg(feature = "export-xml")]
+pub mod mobile;
+#[cfg(feature = "netex")]
+pub mod netex;
+mod otp;
+pub mod po;
+#[cfg(feature = "export-xml")]
+mod tmx;
+pub mod weblate;
+mod wide_csv;
+#[cfg(feature = "export-xml")]
+pub mod xliff;
+
+pub use arb::arb;
+pub use json_bundle::{json_bundle, JsonBundleOptions};
+pub use key_codec::{KeyCodec, UnitIdCodec};
+pub use missing_csv::missing_csv;
+pub use otp::{otp, OtpEntity, OtpExport, OtpUnmapped};
+#[cfg(feature = "export-xml")]
+pub use tmx::tmx;
+pub use wide_csv::wide_csv;
+
+// One translatable string: the value in the source language (or the original value for value keys)
+// and the existing translation in the target language, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExportUnit {
+    pub field: TranslatableField,
+    pub key: TranslationKey,
+    pub source: Option<String>,
+    pub target: Option<String>,
+}
+
+pub(crate) fn collect_units(result: &TranslationResult, source_lang: &LanguageTag, target_lang: &LanguageTag) -> Vec<ExportUnit> {
+    let mut units: HashMap<(TranslatableField, TranslationKey), ExportUnit> = HashMap::new();
+
+    for (lookup, translation) in result.translations.iter() {
+        let is_source = &lookup.language == source_lang;
+        let is_target = &lookup.language == target_lang;
+
+        if !is_source && !is_target && !matches!(lookup.key, TranslationKey::Value(_)) {
+            continue;
+        }
+
+        let unit = units.entry((lookup.field.clone(), lookup.key.clone())).or_insert_with(|| ExportUnit {
+            field: lookup.field.clone(),
+            key: lookup.key.clone(),
+            source: match &lookup.key {
+                TranslationKey::Value(field_value) => Some(field_value.clone()),
+                _ => None,
+            },
+            target: None,
+        });
+
+        if is_source {
+            unit.source = Some(translation.to_string());
+        }
+
+        if is_target {
+            unit.target = Some(translation.to_string());
+        }
+    }
+
+    let mut units = units.into_values().collect::<Vec<ExportUnit>>();
+    units.sort_by_cached_key(|unit| (enum_to_table_and_field(&unit.field), key_struct_to_options(&unit.key)));
+    units
+}
+
+// Column name without the table prefix, e.g. `long_name` for routes.route_long_name
+pub(crate) fn short_field_name(field: &TranslatableField) -> &'static str {
+    match field {
+        TranslatableField::Agency(AgencyFields::Name) => "name",
+        TranslatableField::Agency(AgencyFields::Url) => "url",
+        TranslatableField::Agency(AgencyFields::FareUrl) => "fare_url",
+        TranslatableField::Areas(AreaFields::Name) => "name",
+        TranslatableField::Routes(RouteFields::Desc) => "desc",
+        TranslatableField::Routes(RouteFields::LongName) => "long_name",
+        TranslatableField::Routes(RouteFields::ShortName) => "short_name",
+        TranslatableField::Routes(RouteFields::Url) => "url",
+        TranslatableField::StopTimes(StopTimeFields::Headsign) => "headsign",
+        TranslatableField::Stops(StopFields::Code) => "code",
+        TranslatableField::Stops(StopFields::Name) => "name",
+        TranslatableField::Stops(StopFields::TtsName) => "tts_name",
+        TranslatableField::Stops(StopFields::Desc) => "desc",
+        TranslatableField::Stops(StopFields::PlatformCode) => "platform_code",
+        TranslatableField::Trips(TripFields::Headsign) => "headsign",
+        TranslatableField::Trips(TripFields::ShortName) => "short_name",
+        TranslatableField::Calendar(CalendarFields::ServiceId) => "service_id",
+        TranslatableField::FareProducts(FareProductFields::ProductName) => "name",
+        TranslatableField::FeedInfo(FeedInfoFields::PublisherName) => "publisher_name",
+    }
+}
+
+fn sanitize_name(parts: &[&str]) -> String {
+    let mut name = String::new();
+
+    for character in parts.join("_").chars() {
+        match character.is_ascii_alphanumeric() {
+            true => name.push(character.to_ascii_lowercase()),
+            false if !name.ends_with('_') => name.push('_'),
+            false => {}
+        }
+    }
+
+    name.trim_end_matches('_').to_string()
+}
+
+// Names like stops_name_stop_1 for formats whose keys must be identifiers in code: lowercase ascii letters, digits
+// and underscores, starting with the table name. They can't be decoded, so keys that sanitize alike get _2, _3 and so
+// on, numbered in sorted order over the whole result so that a key has the same name in every language.
+pub(crate) fn resource_names(result: &TranslationResult) -> HashMap<(TranslatableField, TranslationKey), String> {
+    let keys = result.translations.keys().map(|lookup| (lookup.field.clone(), lookup.key.clone())).collect::<BTreeSet<_>>();
+    let mut used = HashSet::new();
+    let mut names = HashMap::new();
+
+    for (field, key) in keys {
+        let (table_name, _) = enum_to_table_and_field(&field);
+        let field_name = short_field_name(&field);
+
+        let base = match &key {
+            TranslationKey::Record(record_id) => sanitize_name(&[table_name, field_name, record_id]),
+            TranslationKey::RecordSub((record_id, record_sub_id)) => sanitize_name(&[table_name, field_name, record_id, record_sub_id]),
+            TranslationKey::Value(field_value) => sanitize_name(&[table_name, field_name, "value", field_value]),
+        };
+
+        let mut name = base.clone();
+        let mut suffix = 1;
+
+        while !used.insert(name.clone()) {
+            suffix += 1;
+            name = format!("{}_{}", base, suffix);
+        }
+
+        names.insert((field, key), name);
+    }
+
+    names
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use quick_xml::escape::escape;
+use crate::{RouteFields, StopFields, TranslatableField, TranslationKey, TranslationResult};
+
+// The NeTEx markup for the translated names of one GTFS stop or route, to paste into the StopPlace or Line with that id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetexFragment {
+    // stops or routes
+    pub table_name: String,
+    pub id: String,
+    pub xml: String,
+}
+
+// NeTEx attribute names of the route fields, which AlternativeText refers to
+fn route_attribute(field: &RouteFields) -> Option<&'static str> {
+    match field {
+        RouteFields::LongName => Some("Name"),
+        RouteFields::ShortName => Some("ShortName"),
+        RouteFields::Desc => Some("Description"),
+        RouteFields::Url => None,
+    }
+}
+
+// Stop names become an <alternativeNames> list of AlternativeName with NameType translation, and route fields an
+// <alternativeTexts> list keyed by the attribute they translate. Only translations by record are exported, as NeTEx
+// attaches names to an element; materialize_value_keys turns value keys into record keys beforehand.
+pub fn fragments(result: &TranslationResult) -> Vec<NetexFragment> {
+    let mut stops: BTreeMap<&str, BTreeMap<String, &str>> = BTreeMap::new();
+    let mut routes: BTreeMap<&str, BTreeMap<(&'static str, String), &str>> = BTreeMap::new();
+
+    for (lookup, translation) in result.translations.iter() {
+        let TranslationKey::Record(record_id) = &lookup.key else {
+            continue;
+        };
+
+        match &lookup.field {
+            TranslatableField::Stops(StopFields::Name) => {
+                stops.entry(record_id).or_default().insert(lookup.language.to_string(), translation);
+            }
+            TranslatableField::Routes(field) => {
+                if let Some(attribute) = route_attribute(field) {
+                    routes.entry(record_id).or_default().insert((attribute, lookup.language.to_string()), translation);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut fragments = vec![];
+
+    for (id, names) in stops {
+        let mut xml = String::from("<alternativeNames>\n");
+
+        for (language, name) in names {
+            xml.push_str("  <AlternativeName>\n    <NameType>translation</NameType>\n");
+            let _ = writeln!(xml, "    <Name lang=\"{}\">{}</Name>", escape(&language), escape(name));
+            xml.push_str("  </AlternativeName>\n");
+        }
+
+        xml.push_str("</alternativeNames>\n");
+        fragments.push(NetexFragment { table_name: "stops".to_string(), id: id.to_string(), xml });
+    }
+
+    for (id, texts) in routes {
+        let mut xml = String::from("<alternativeTexts>\n");
+
+        for ((attribute, language), text) in texts {
+            let _ = writeln!(xml, "  <AlternativeText attributeName=\"{}\">", attribute);
+            let _ = writeln!(xml, "    <Text lang=\"{}\">{}</Text>", escape(&language), escape(text));
+            xml.push_str("  </AlternativeText>\n");
+        }
+
+        xml.push_str("</alternativeTexts>\n");
+        fragments.push(NetexFragment { table_name: "routes".to_string(), id: id.to_string(), xml });
+    }
+
+    fragments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    #[test]
+    fn writes_stop_names_by_language() {
+        let fragments = fragments(&translations("stops,stop_name,fr,Gare & Quai,stop_1,,\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n"));
+
+        assert_eq!(fragments.len(), 1);
+        assert_eq!((fragments[0].table_name.as_str(), fragments[0].id.as_str()), ("stops", "stop_1"));
+        assert_eq!(fragments[0].xml, "<alternativeNames>\n  <AlternativeName>\n    <NameType>translation</NameType>\n    <Name lang=\"de\">Bahnhof</Name>\n  </AlternativeName>\n  \
+            <AlternativeName>\n    <NameType>translation</NameType>\n    <Name lang=\"fr\">Gare &amp; Quai</Name>\n  </AlternativeName>\n</alternativeNames>\n");
+    }
+
+    #[test]
+    fn writes_route_fields_by_attribute() {
+        let fragments = fragments(&translations("routes,route_long_name,fr,Ligne bleue,route_1,,\n\
+            routes,route_url,fr,https://example.fr,route_1,,\n"));
+
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].table_name, "routes");
+        assert_eq!(fragments[0].xml, "<alternativeTexts>\n  <AlternativeText attributeName=\"Name\">\n    <Text lang=\"fr\">Ligne bleue</Text>\n  </AlternativeText>\n</alternativeTexts>\n");
+    }
+
+    #[test]
+    fn skips_translations_by_value() {
+        assert!(fragments(&translations("stops,stop_name,fr,Place,,,Square\n")).is_empty());
+    }
+}
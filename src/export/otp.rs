@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::universe::TranslationUniverse;
+use crate::{enum_to_table_and_field, key_struct_to_options, TranslatableField, TranslationKey, TranslationResult};
+
+// One GTFS entity with its translated strings in the shape of OpenTripPlanner's TranslatedString,
+// a map from language to text for each column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OtpEntity {
+    pub table_name: String,
+    // agency_id, route_id, stop_id and so on, or the trip_id for stop_times
+    pub id: String,
+    // Only for stop_times, which are identified by trip and stop_sequence
+    pub stop_sequence: Option<String>,
+    pub fields: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+// A translation the entity layout has no place for, and why
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OtpUnmapped {
+    pub table_name: String,
+    pub field_name: String,
+    pub language: String,
+    pub record_id: Option<String>,
+    pub record_sub_id: Option<String>,
+    pub field_value: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OtpExport {
+    // Sorted by table, id and stop_sequence
+    pub entities: Vec<OtpEntity>,
+    pub unmapped: Vec<OtpUnmapped>,
+}
+
+type EntityKey = (&'static str, String, Option<String>);
+
+// Where a key puts a translation of the field, or why it has no entity
+fn entity_key(field: &TranslatableField, key: &TranslationKey) -> Result<EntityKey, &'static str> {
+    let (table_name, _) = enum_to_table_and_field(field);
+
+    match (field, key) {
+        (TranslatableField::Calendar(_), _) => Err("service ids are identifiers, not text OTP displays"),
+        (TranslatableField::StopTimes(_), TranslationKey::RecordSub((trip_id, stop_sequence))) => Ok((table_name, trip_id.clone(), Some(stop_sequence.clone()))),
+        (TranslatableField::StopTimes(_), TranslationKey::Record(_)) => Err("stop_times translations need a stop_sequence"),
+        (_, TranslationKey::Record(record_id)) => Ok((table_name, record_id.clone(), None)),
+        (_, TranslationKey::RecordSub(_)) => Err("only stop_times have a record_sub_id"),
+        (_, TranslationKey::Value(_)) => Err("value keys have no entity, pass a universe to expand them"),
+    }
+}
+
+fn unmapped(field: &TranslatableField, key: &TranslationKey, language: String, reason: &str) -> OtpUnmapped {
+    let (table_name, field_name) = enum_to_table_and_field(field);
+    let (record_id, record_sub_id, field_value) = key_struct_to_options(key);
+
+    OtpUnmapped {
+        table_name: table_name.to_string(),
+        field_name: field_name.to_string(),
+        language,
+        record_id,
+        record_sub_id,
+        field_value,
+        reason: reason.to_string(),
+    }
+}
+
+// Groups the translations by the entity they belong to, for deployments that hand OpenTripPlanner the same
+// strings Catenary shows. With a universe, value keys are expanded to the records holding the value, and a
+// translation by record wins over one by value as in TranslationResult::resolve.
+pub fn otp(result: &TranslationResult, universe: Option<&TranslationUniverse>) -> OtpExport {
+    let mut entities: BTreeMap<EntityKey, BTreeMap<String, BTreeMap<String, String>>> = BTreeMap::new();
+    let mut unmapped_translations = vec![];
+
+    let mut lookups = result.translations.iter().collect::<Vec<_>>();
+    // record keys first, so value keys only fill what is left
+    lookups.sort_by_key(|(lookup, _)| (matches!(lookup.key, TranslationKey::Value(_)), *lookup));
+
+    for (lookup, translation) in lookups {
+        let (_, field_name) = enum_to_table_and_field(&lookup.field);
+
+        let keys = match (&lookup.key, universe) {
+            (TranslationKey::Value(field_value), Some(universe)) if !matches!(lookup.field, TranslatableField::Calendar(_)) => {
+                let records = universe.records_with_value(&lookup.field, field_value);
+
+                if records.is_empty() {
+                    unmapped_translations.push(unmapped(&lookup.field, &lookup.key, lookup.language.to_string(), "no record in the universe has this value"));
+                    continue;
+                }
+
+                records
+            }
+            _ => vec![lookup.key.clone()],
+        };
+
+        for key in keys {
+            match entity_key(&lookup.field, &key) {
+                Ok(entity) => {
+                    entities.entry(entity).or_default()
+                        .entry(field_name.to_string()).or_default()
+                        .entry(lookup.language.to_string()).or_insert_with(|| translation.to_string());
+                }
+                Err(reason) => unmapped_translations.push(unmapped(&lookup.field, &key, lookup.language.to_string(), reason)),
+            }
+        }
+    }
+
+    OtpExport {
+        entities: entities.into_iter()
+            .map(|((table_name, id, stop_sequence), fields)| OtpEntity {
+                table_name: table_name.to_string(),
+                id,
+                stop_sequence,
+                fields,
+            })
+            .collect(),
+        unmapped: unmapped_translations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::TripFields;
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n\
+            stop_times,stop_headsign,fr,Centre,trip_1,3,\n\
+            trips,trip_headsign,fr,Centre-ville,,,Downtown\n\
+            trips,trip_headsign,fr,Centre,trip_2,,\n\
+            calendar,service_id,fr,Semaine,weekday,,\n")
+    }
+
+    #[test]
+    fn groups_fields_by_entity() {
+        let export = otp(&result(), None);
+        let ids = export.entities.iter().map(|entity| (entity.table_name.as_str(), entity.id.as_str(), entity.stop_sequence.as_deref())).collect::<Vec<_>>();
+
+        assert_eq!(ids, vec![("stop_times", "trip_1", Some("3")), ("stops", "stop_1", None), ("trips", "trip_2", None)]);
+        assert_eq!(export.entities[1].fields["stop_name"]["de"], "Bahnhof");
+        assert_eq!(export.entities[1].fields["stop_name"]["fr"], "Gare");
+    }
+
+    #[test]
+    fn reports_value_keys_without_a_universe() {
+        let export = otp(&result(), None);
+        let mut unmapped = export.unmapped.iter().map(|unmapped| unmapped.table_name.as_str()).collect::<Vec<_>>();
+        unmapped.sort();
+
+        assert_eq!(unmapped, vec!["calendar", "trips"]);
+    }
+
+    #[test]
+    fn expands_value_keys_through_the_universe() {
+        let mut universe = TranslationUniverse::new();
+        universe.push(TranslatableField::Trips(TripFields::Headsign), Some("trip_2"), None, "Downtown");
+        universe.push(TranslatableField::Trips(TripFields::Headsign), Some("trip_3"), None, "Downtown");
+
+        let export = otp(&result(), Some(&universe));
+        let trips = export.entities.iter().filter(|entity| entity.table_name == "trips").map(|entity| (entity.id.as_str(), entity.fields["trip_headsign"]["fr"].as_str())).collect::<Vec<_>>();
+
+        // trip_2 has a translation of its own, which beats the one by value
+        assert_eq!(trips, vec![("trip_2", "Centre"), ("trip_3", "Centre-ville")]);
+        assert_eq!(export.unmapped.iter().map(|unmapped| unmapped.table_name.as_str()).collect::<Vec<_>>(), vec!["calendar"]);
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write;
+use language_tags::LanguageTag;
+use crate::export::{collect_units, KeyCodec, UnitIdCodec};
+use crate::{enum_to_table_and_field, TranslationResult};
+
+fn escape_po(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn unescape_po(value: &str) -> Result<String, Box<dyn Error>> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('"') => unescaped.push('"'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            other => return Err(format!("invalid escape sequence \\{}", other.map(String::from).unwrap_or_default()).into()),
+        }
+    }
+
+    Ok(unescaped)
+}
+
+// msgctxt carries the unit id so msgids may repeat; records without a known original use the id as msgid.
+pub fn write(result: &TranslationResult, source_lang: &LanguageTag, target_lang: &LanguageTag) -> String {
+    write_with_codec(result, source_lang, target_lang, &UnitIdCodec::default())
+}
+
+pub fn write_with_codec(result: &TranslationResult, source_lang: &LanguageTag, target_lang: &LanguageTag, codec: &dyn KeyCodec) -> String {
+    let mut po = String::new();
+
+    po.push_str("msgid \"\"\nmsgstr \"\"\n");
+    po.push_str("\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+    let _ = writeln!(po, "\"Language: {}\\n\"", escape_po(target_lang.as_str()));
+
+    for unit in collect_units(result, source_lang, target_lang) {
+        let (table_name, field_name) = enum_to_table_and_field(&unit.field);
+        let id = codec.encode(&unit.field, &unit.key);
+
+        po.push('\n');
+        let _ = writeln!(po, "#: {}.{}", table_name, field_name);
+        let _ = writeln!(po, "msgctxt \"{}\"", escape_po(&id));
+        let _ = writeln!(po, "msgid \"{}\"", escape_po(unit.source.as_deref().filter(|source| !source.is_empty()).unwrap_or(&id)));
+        let _ = writeln!(po, "msgstr \"{}\"", escape_po(unit.target.as_deref().unwrap_or("")));
+    }
+
+    po
+}
+
+// One po file per language present in the result, other than the source language.
+pub fn write_all(result: &TranslationResult, source_lang: &LanguageTag) -> HashMap<LanguageTag, String> {
+    result.languages_as_written().iter()
+        .filter(|language| *language != source_lang)
+        .map(|language| (language.clone(), write(result, source_lang, language)))
+        .collect()
+}
+
+#[derive(Default)]
+struct PoEntry {
+    // Flagged #, fuzzy: a guess a translator has not signed off on yet
+    fuzzy: bool,
+    msgctxt: Option<String>,
+    msgid: Option<String>,
+    msgstr: Option<String>,
+}
+
+enum PoKeyword {
+    Msgctxt,
+    Msgid,
+    Msgstr,
+}
+
+fn parse_entries(po: &str) -> Result<Vec<PoEntry>, Box<dyn Error>> {
+    let mut entries: Vec<PoEntry> = vec![];
+    let mut current = PoEntry::default();
+    let mut keyword: Option<PoKeyword> = None;
+
+    for (line_number, line) in po.lines().enumerate() {
+        let line = line.trim();
+
+        // flags come before the entry they belong to, so they also end the one before
+        if let Some(flags) = line.strip_prefix("#,") {
+            if current.msgstr.is_some() {
+                entries.push(std::mem::take(&mut current));
+            }
+            current.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            continue;
+        }
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (new_keyword, rest) = if let Some(rest) = line.strip_prefix("msgctxt ") {
+            (Some(PoKeyword::Msgctxt), rest)
+        } else if let Some(rest) = line.strip_prefix("msgid ") {
+            (Some(PoKeyword::Msgid), rest)
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            (Some(PoKeyword::Msgstr), rest)
+        } else {
+            (None, line)
+        };
+
+        let quoted = rest.trim().strip_prefix('"').and_then(|rest| rest.strip_suffix('"'))
+            .ok_or_else(|| format!("line {}: expected a quoted string", line_number + 1))?;
+        let value = unescape_po(quoted)?;
+
+        if let Some(new_keyword) = new_keyword {
+            // a msgctxt or msgid after a msgstr starts the next entry
+            let starts_entry = matches!(new_keyword, PoKeyword::Msgctxt) || (matches!(new_keyword, PoKeyword::Msgid) && current.msgctxt.is_none());
+            if starts_entry && current.msgstr.is_some() {
+                entries.push(std::mem::take(&mut current));
+            }
+            keyword = Some(new_keyword);
+        }
+
+        let slot = match keyword {
+            Some(PoKeyword::Msgctxt) => &mut current.msgctxt,
+            Some(PoKeyword::Msgid) => &mut current.msgid,
+            Some(PoKeyword::Msgstr) => &mut current.msgstr,
+            None => return Err(format!("line {}: string without msgid, msgctxt or msgstr", line_number + 1).into()),
+        };
+        slot.get_or_insert_with(String::new).push_str(&value);
+    }
+
+    if current.msgstr.is_some() {
+        entries.push(current);
+    }
+
+    Ok(entries)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoMerge {
+    pub merged: usize,
+    // Translated entries left out because they are flagged fuzzy, as gettext does
+    pub fuzzy: usize,
+}
+
+// Merges translated entries back into the result, using the Language header as the target language
+pub fn merge(result: &mut TranslationResult, po: &str) -> Result<PoMerge, Box<dyn Error>> {
+    merge_with_codec(result, po, &UnitIdCodec::default())
+}
+
+pub fn merge_with_codec(result: &mut TranslationResult, po: &str, codec: &dyn KeyCodec) -> Result<PoMerge, Box<dyn Error>> {
+    let entries = parse_entries(po)?;
+
+    let header = entries.iter()
+        .find(|entry| entry.msgctxt.is_none() && entry.msgid.as_deref() == Some(""))
+        .and_then(|entry| entry.msgstr.as_deref())
+        .ok_or("po file has no header entry")?;
+
+    let language = header.lines()
+        .find_map(|line| line.strip_prefix("Language:"))
+        .map(str::trim)
+        .ok_or("po header has no Language")?;
+    let language = LanguageTag::parse(language)?;
+
+    let mut report = PoMerge::default();
+
+    for entry in entries {
+        let (Some(msgctxt), Some(msgstr)) = (entry.msgctxt, entry.msgstr) else {
+            continue;
+        };
+
+        if msgstr.is_empty() {
+            continue;
+        }
+
+        if entry.fuzzy {
+            report.fuzzy += 1;
+            continue;
+        }
+
+        let (field, key) = codec.decode(&msgctxt).ok_or_else(|| format!("unrecognised msgctxt {}", msgctxt))?;
+
+        result.insert(field, key, language.clone(), msgstr);
+        report.merged += 1;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,en,\"Main \"\"Central\"\" Station\",stop_1,,\n\
+            stops,stop_name,fr,Gare,stop_2,,\n\
+            trips,trip_headsign,de,Innenstadt,,,Downtown\n")
+    }
+
+    fn french() -> String {
+        write_all(&result(), &LanguageTag::parse("en").unwrap()).remove(&LanguageTag::parse("fr").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn writes_a_file_per_target_language() {
+        let files = write_all(&result(), &LanguageTag::parse("en").unwrap());
+        let mut languages = files.keys().map(|language| language.to_string()).collect::<Vec<_>>();
+        languages.sort();
+
+        assert_eq!(languages, vec!["de", "fr"]);
+    }
+
+    #[test]
+    fn escapes_source_text_and_falls_back_to_the_value() {
+        let fr = french();
+
+        assert!(fr.contains("msgctxt \"stops:stop_name:record:stop_1\"\nmsgid \"Main \\\"Central\\\" Station\"\nmsgstr \"\"\n"));
+        assert!(fr.contains("msgctxt \"trips:trip_headsign:value:Downtown\"\nmsgid \"Downtown\"\nmsgstr \"\"\n"));
+    }
+
+    #[test]
+    fn merges_multiline_and_fuzzy_entries() {
+        let completed = french().replace("msgid \"Downtown\"\nmsgstr \"\"", "msgid \"Downtown\"\nmsgstr \"Centre-\"\n\"ville\"")
+            .replace("#: stops.stop_name\nmsgctxt \"stops:stop_name:record:stop_1\"\nmsgid \"Main \\\"Central\\\" Station\"\nmsgstr \"\"",
+                "#: stops.stop_name\n#, fuzzy\nmsgctxt \"stops:stop_name:record:stop_1\"\nmsgid \"Main \\\"Central\\\" Station\"\nmsgstr \"Gare principale\"");
+
+        let result = result();
+        let mut merged = result.clone();
+        assert_eq!(merge(&mut merged, &completed).unwrap(), PoMerge { merged: 2, fuzzy: 1 });
+        assert_eq!(merged.translations.len(), result.translations.len() + 1);
+        assert!(merged.translations.values().any(|translation| &**translation == "Centre-ville"));
+    }
+
+    #[test]
+    fn skips_untranslated_entries() {
+        // only stop_2 has a French translation, and it merges back as it was
+        let mut merged = result();
+        assert_eq!(merge(&mut merged, &french()).unwrap(), PoMerge { merged: 1, fuzzy: 0 });
+        assert_eq!(merged.translations, result().translations);
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use language_tags::LanguageTag;
+use quick_xml::escape::escape;
+use crate::validate::source_text;
+use crate::{enum_to_table_and_field, TranslationResult};
+
+// A TMX 1.4 translation memory with one unit per source text, language and translation, however many records share
+// it. Each unit lists the fields it was used in as x-field properties. Empty texts are left out.
+pub fn tmx(result: &TranslationResult, source_lang: &LanguageTag) -> String {
+    let mut units: BTreeMap<(&str, String, &str), BTreeSet<String>> = BTreeMap::new();
+
+    for (lookup, translation) in result.translations.iter().filter(|(lookup, _)| &lookup.language != source_lang) {
+        let Some(source) = source_text(result, lookup, Some(source_lang)).filter(|source| !source.is_empty()) else {
+            continue;
+        };
+        if translation.is_empty() {
+            continue;
+        }
+
+        let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+        units.entry((source, lookup.language.to_string(), translation)).or_default().insert(format!("{}.{}", table_name, field_name));
+    }
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tmx version=\"1.4\">\n");
+    let _ = writeln!(xml, "  <header creationtool=\"gtfs-translations\" creationtoolversion=\"{}\" segtype=\"phrase\" o-tmf=\"gtfs\" adminlang=\"en\" srclang=\"{}\" datatype=\"plaintext\"/>",
+        env!("CARGO_PKG_VERSION"), escape(source_lang.as_str()));
+    xml.push_str("  <body>\n");
+
+    for ((source, language, translation), fields) in units {
+        xml.push_str("    <tu>\n");
+        for field in fields {
+            let _ = writeln!(xml, "      <prop type=\"x-field\">{}</prop>", field);
+        }
+        let _ = writeln!(xml, "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>", escape(source_lang.as_str()), escape(source));
+        let _ = writeln!(xml, "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>", escape(&language), escape(translation));
+        xml.push_str("    </tu>\n");
+    }
+
+    xml.push_str("  </body>\n</tmx>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn xml() -> String {
+        tmx(&translations("stops,stop_name,en,Station,stop_1,,\n\
+            stops,stop_name,en,Station,stop_2,,\n\
+            stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,fr,Gare,stop_2,,\n\
+            trips,trip_headsign,fr,Gare,,,Station\n\
+            routes,route_long_name,fr,Ligne A & B,route_1,,\n"), &LanguageTag::parse("en").unwrap())
+    }
+
+    #[test]
+    fn deduplicates_across_records_and_fields() {
+        let xml = xml();
+
+        assert_eq!(xml.matches("<tu>").count(), 1);
+        assert!(xml.contains("<prop type=\"x-field\">stops.stop_name</prop>\n      <prop type=\"x-field\">trips.trip_headsign</prop>"));
+        assert!(xml.contains("<tuv xml:lang=\"en\"><seg>Station</seg></tuv>\n      <tuv xml:lang=\"fr\"><seg>Gare</seg></tuv>"));
+    }
+
+    #[test]
+    fn skips_translations_without_source_text() {
+        assert!(!xml().contains("Ligne"));
+    }
+}
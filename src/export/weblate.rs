@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::error::Error;
+use language_tags::LanguageTag;
+use serde_json::{Map, Value};
+use crate::export::{collect_units, KeyCodec, UnitIdCodec};
+use crate::TranslationResult;
+
+fn json_file(entries: Map<String, Value>) -> String {
+    let mut json = serde_json::to_string_pretty(&Value::Object(entries)).expect("a map of strings always serializes");
+    json.push('\n');
+    json
+}
+
+// The base file of a Weblate monolingual JSON component: every source string under its unit id, the same
+// ids the xliff and po exports use. Keys are sorted so the file only changes where the feed does.
+pub fn write_base(result: &TranslationResult, source_lang: &LanguageTag) -> String {
+    write_base_with_codec(result, source_lang, &UnitIdCodec::default())
+}
+
+pub fn write_base_with_codec(result: &TranslationResult, source_lang: &LanguageTag, codec: &dyn KeyCodec) -> String {
+    let entries = collect_units(result, source_lang, source_lang).into_iter()
+        .filter_map(|unit| Some((codec.encode(&unit.field, &unit.key), Value::String(unit.source?))))
+        .collect::<Map<String, Value>>();
+
+    json_file(entries)
+}
+
+// The file of one language, with the keys of the base file that are translated into it
+pub fn write(result: &TranslationResult, source_lang: &LanguageTag, target_lang: &LanguageTag) -> String {
+    write_with_codec(result, source_lang, target_lang, &UnitIdCodec::default())
+}
+
+pub fn write_with_codec(result: &TranslationResult, source_lang: &LanguageTag, target_lang: &LanguageTag, codec: &dyn KeyCodec) -> String {
+    let entries = collect_units(result, source_lang, target_lang).into_iter()
+        .filter(|unit| unit.source.is_some())
+        .filter_map(|unit| Some((codec.encode(&unit.field, &unit.key), Value::String(unit.target?))))
+        .collect::<Map<String, Value>>();
+
+    json_file(entries)
+}
+
+// Every language other than the source language, as Weblate lays out a component
+pub fn write_all(result: &TranslationResult, source_lang: &LanguageTag) -> HashMap<LanguageTag, String> {
+    result.languages_as_written().iter()
+        .filter(|language| *language != source_lang)
+        .map(|language| (language.clone(), write(result, source_lang, language)))
+        .collect()
+}
+
+// Merges a language file back into the result. The file does not say its language, Weblate puts it in the
+// file name, so the caller passes it. Empty strings are untranslated and skipped.
+pub fn merge(result: &mut TranslationResult, language: &LanguageTag, json: &str) -> Result<usize, Box<dyn Error>> {
+    merge_with_codec(result, language, json, &UnitIdCodec::default())
+}
+
+pub fn merge_with_codec(result: &mut TranslationResult, language: &LanguageTag, json: &str, codec: &dyn KeyCodec) -> Result<usize, Box<dyn Error>> {
+    let Value::Object(entries) = serde_json::from_str(json)? else {
+        return Err("weblate file is not a json object".into());
+    };
+
+    let mut merged = 0;
+
+    for (id, translation) in entries {
+        let translation = translation.as_str().ok_or_else(|| format!("translation of {} is not a string", id))?;
+
+        if translation.is_empty() {
+            continue;
+        }
+
+        let (field, key) = codec.decode(&id).ok_or_else(|| format!("unrecognised key {}", id))?;
+
+        result.insert(field, key, language.clone(), translation);
+        merged += 1;
+    }
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{StopFields, TranslatableField};
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,en,Station,stop_1,,\n\
+            stops,stop_name,en,Square,stop_2,,\n\
+            stops,stop_name,fr,Gare,stop_1,,\n\
+            trips,trip_headsign,fr,Centre,,,Downtown\n")
+    }
+
+    fn en() -> LanguageTag {
+        LanguageTag::parse("en").unwrap()
+    }
+
+    fn fr() -> LanguageTag {
+        LanguageTag::parse("fr").unwrap()
+    }
+
+    #[test]
+    fn writes_every_unit_in_the_base() {
+        let base = write_base(&result(), &en());
+
+        assert_eq!(serde_json::from_str::<Map<String, Value>>(&base).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn writes_a_component_per_target_language() {
+        assert_eq!(write_all(&result(), &en()).keys().collect::<Vec<_>>(), vec![&fr()]);
+    }
+
+    #[test]
+    fn merges_a_component_back() {
+        let result = result();
+        let french = write(&result, &en(), &fr());
+        let mut restored = translations("stops,stop_name,en,Station,stop_1,,\n");
+
+        assert_eq!(merge(&mut restored, &fr(), &french).unwrap(), 2);
+        assert_eq!(restored.resolve(&TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, None, &fr()), Some("Gare"));
+        assert_eq!(restored.translations.len(), result.translations.len() - 1);
+    }
+
+    #[test]
+    fn rejects_a_component_that_is_not_an_object() {
+        assert!(merge(&mut result(), &fr(), "[]").is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use crate::{enum_to_table_and_field, key_struct_to_options, TranslationResult};
+
+// table, field and the (record_id, record_sub_id, field_value) of a key
+type RowKey<'a> = (&'a str, &'a str, (Option<String>, Option<String>, Option<String>));
+
+// One row per field and key with one column per language, the layout import::wide_csv reads back
+pub fn wide_csv(result: &TranslationResult) -> String {
+    let mut languages = result.languages_as_written().iter().map(|language| language.to_string()).collect::<Vec<String>>();
+    languages.sort();
+    languages.dedup();
+
+    let mut rows: BTreeMap<RowKey, Vec<&str>> = BTreeMap::new();
+
+    for (lookup, translation) in result.translations.iter() {
+        let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+        let Ok(column) = languages.binary_search(&lookup.language.to_string()) else {
+            continue;
+        };
+
+        rows.entry((table_name, field_name, key_struct_to_options(&lookup.key))).or_insert_with(|| vec![""; languages.len()])[column] = translation;
+    }
+
+    let mut wtr = csv::Writer::from_writer(vec![]);
+
+    let mut headers = vec!["table", "field", "record_id", "record_sub_id", "original"];
+    headers.extend(languages.iter().map(String::as_str));
+    let _ = wtr.write_record(&headers);
+
+    for ((table_name, field_name, (record_id, record_sub_id, field_value)), translations) in rows.iter() {
+        let mut record = vec![*table_name, *field_name, record_id.as_deref().unwrap_or(""), record_sub_id.as_deref().unwrap_or(""), field_value.as_deref().unwrap_or("")];
+        record.extend(translations.iter().copied());
+        let _ = wtr.write_record(&record);
+    }
+
+    String::from_utf8(wtr.into_inner().expect("writing csv into a Vec cannot fail")).expect("csv writer only emits the utf-8 it was given")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::wide_csv as import_wide_csv;
+    use crate::test_util::translations;
+    use crate::translate_raw_translations;
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n\
+            trips,trip_headsign,fr,Centre,,,Downtown\n")
+    }
+
+    #[test]
+    fn writes_a_column_per_language() {
+        let wide = wide_csv(&result());
+
+        assert_eq!(wide.lines().next(), Some("table,field,record_id,record_sub_id,original,de,fr"));
+        assert!(wide.contains("stops,stop_name,stop_1,,,Bahnhof,Gare\n"));
+    }
+
+    #[test]
+    fn round_trips_through_the_importer() {
+        let result = result();
+
+        assert_eq!(translate_raw_translations(import_wide_csv(&wide_csv(&result)).unwrap()).translations, result.translations);
+    }
+}
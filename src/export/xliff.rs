@@ -0,0 +1,169 @@
+use std::error::Error;
+use std::fmt::Write;
+use language_tags::LanguageTag;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use crate::export::{collect_units, KeyCodec, UnitIdCodec};
+use crate::{enum_to_table_and_field, TranslationResult};
+
+// Emits an XLIFF 2.0 document with one <file> per gtfs table and one <unit> per translatable record or value.
+pub fn write(result: &TranslationResult, source_lang: &LanguageTag, target_lang: &LanguageTag) -> String {
+    write_with_codec(result, source_lang, target_lang, &UnitIdCodec::default())
+}
+
+pub fn write_with_codec(result: &TranslationResult, source_lang: &LanguageTag, target_lang: &LanguageTag, codec: &dyn KeyCodec) -> String {
+    let mut xml = String::new();
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(xml, "<xliff xmlns=\"urn:oasis:names:tc:xliff:document:2.0\" version=\"2.0\" srcLang=\"{}\" trgLang=\"{}\">",
+        escape(source_lang.as_str()), escape(target_lang.as_str()));
+
+    let mut current_table: Option<&str> = None;
+
+    for unit in collect_units(result, source_lang, target_lang) {
+        let (table_name, field_name) = enum_to_table_and_field(&unit.field);
+
+        if current_table != Some(table_name) {
+            if current_table.is_some() {
+                xml.push_str("  </file>\n");
+            }
+            let _ = writeln!(xml, "  <file id=\"{}\">", table_name);
+            current_table = Some(table_name);
+        }
+
+        let _ = writeln!(xml, "    <unit id=\"{}\">", escape(&codec.encode(&unit.field, &unit.key)));
+        let _ = writeln!(xml, "      <notes><note category=\"location\">{}.{}</note></notes>", table_name, field_name);
+        let _ = writeln!(xml, "      <segment state=\"{}\">", if unit.target.is_some() { "translated" } else { "initial" });
+        let _ = writeln!(xml, "        <source>{}</source>", escape(unit.source.as_deref().unwrap_or("")));
+        if let Some(target) = &unit.target {
+            let _ = writeln!(xml, "        <target>{}</target>", escape(target));
+        }
+        xml.push_str("      </segment>\n");
+        xml.push_str("    </unit>\n");
+    }
+
+    if current_table.is_some() {
+        xml.push_str("  </file>\n");
+    }
+
+    xml.push_str("</xliff>\n");
+
+    xml
+}
+
+// Merges the non-empty <target> of every unit back into the result under the document's trgLang.
+// Returns the number of translations merged.
+pub fn merge(result: &mut TranslationResult, xliff: &str) -> Result<usize, Box<dyn Error>> {
+    merge_with_codec(result, xliff, &UnitIdCodec::default())
+}
+
+pub fn merge_with_codec(result: &mut TranslationResult, xliff: &str, codec: &dyn KeyCodec) -> Result<usize, Box<dyn Error>> {
+    let mut reader = Reader::from_str(xliff);
+
+    let mut target_lang: Option<LanguageTag> = None;
+    let mut unit_id: Option<String> = None;
+    let mut in_target = false;
+    let mut target = String::new();
+    let mut merged = 0;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"xliff" => {
+                    let language = element.try_get_attribute("trgLang")?.ok_or("xliff document has no trgLang")?;
+                    target_lang = Some(LanguageTag::parse(&language.unescape_value()?)?);
+                }
+                b"unit" => {
+                    let id = element.try_get_attribute("id")?.ok_or("xliff unit has no id")?;
+                    unit_id = Some(id.unescape_value()?.into_owned());
+                }
+                b"target" => {
+                    in_target = true;
+                    target.clear();
+                }
+                _ => {}
+            },
+            Event::Text(text) if in_target => target.push_str(&text.unescape()?),
+            Event::CData(text) if in_target => target.push_str(std::str::from_utf8(&text)?),
+            Event::End(element) => match element.local_name().as_ref() {
+                b"target" => {
+                    in_target = false;
+
+                    let id = unit_id.as_deref().ok_or("xliff target outside of a unit")?;
+                    let (field, key) = codec.decode(id).ok_or_else(|| format!("unrecognised xliff unit id {}", id))?;
+                    let language = target_lang.clone().ok_or("xliff document has no trgLang")?;
+
+                    if !target.is_empty() {
+                        result.insert(field, key, language, target.clone());
+                        merged += 1;
+                    }
+                }
+                b"unit" => unit_id = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+    use crate::{from_reader, ParseOptions, ProvenanceSource, StopFields, TranslatableField, TranslationKey, TranslationLookup};
+
+    fn result() -> TranslationResult {
+        let csv = format!("{}stops,stop_name,en,Central Station & Bus,stop:1,,\n\
+            stops,stop_name,fr,Gare Centrale,stop:2,,\n\
+            routes,route_long_name,ja,中央線,,,Chuo Line\n", HEADER);
+        let options = ParseOptions { provenance: Some(ProvenanceSource::file("translations.txt")), ..ParseOptions::default() };
+
+        from_reader(csv.as_bytes(), &options).unwrap().0
+    }
+
+    fn french() -> String {
+        write(&result(), &LanguageTag::parse("en").unwrap(), &LanguageTag::parse("fr").unwrap())
+    }
+
+    fn lookup(record_id: &str) -> TranslationLookup {
+        TranslationLookup {
+            language: LanguageTag::parse("fr").unwrap(),
+            field: TranslatableField::Stops(StopFields::Name),
+            key: TranslationKey::Record(record_id.to_string()),
+        }
+    }
+
+    #[test]
+    fn escapes_unit_ids_and_source_text() {
+        let xliff = french();
+
+        assert!(xliff.contains("<unit id=\"stops:stop_name:record:stop.3A1\">"));
+        assert!(xliff.contains("<source>Central Station &amp; Bus</source>"));
+        assert!(xliff.contains("<source>Chuo Line</source>"));
+    }
+
+    #[test]
+    fn merges_translated_segments() {
+        let completed = french().replace("<segment state=\"initial\">\n        <source>Central Station &amp; Bus</source>\n",
+            "<segment state=\"translated\">\n        <source>Central Station &amp; Bus</source>\n        <target>Gare Centrale &amp; Bus</target>\n");
+
+        let mut merged = result();
+        assert_eq!(merge(&mut merged, &completed).unwrap(), 2);
+        assert_eq!(merged.translations.get(&lookup("stop:1")).map(AsRef::as_ref), Some("Gare Centrale & Bus"));
+    }
+
+    #[test]
+    fn merged_text_drops_its_provenance() {
+        let result = result();
+        let mut merged = result.clone();
+        merge(&mut merged, &french()).unwrap();
+
+        // the merged text of stop:2 no longer claims to come from translations.txt
+        assert!(result.provenance().contains_key(&lookup("stop:2")));
+        assert_eq!(merged.provenance().get(&lookup("stop:2")), None);
+    }
+}
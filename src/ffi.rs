@@ -0,0 +1,160 @@
+// C ABI for consumers outside Rust, declared in include/gtfs_translations.h. Every pointer argument must be null
+// or valid for the call: strings NUL terminated UTF-8, results only as returned by the parse functions and not yet
+// freed. Returned strings belong to the caller, who frees them with gtfs_translations_string_free.
+// Build the shared library with cargo rustc --release --lib --crate-type cdylib --features ffi.
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::fs::File;
+use std::ptr;
+use language_tags::LanguageTag;
+use crate::{fallback_languages, from_reader, table_and_field_to_enum, ParseOptions, TranslationResult};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message.replace('\0', " ")).ok());
+}
+
+unsafe fn to_str<'a>(string: *const c_char) -> Option<&'a str> {
+    if string.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(string).to_str().ok()
+}
+
+fn into_raw(parsed: Result<TranslationResult, String>) -> *mut TranslationResult {
+    match parsed {
+        Ok(result) => Box::into_raw(Box::new(result)),
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+// Parses translations.txt from memory. Returns null on failure, see gtfs_translations_last_error.
+#[no_mangle]
+pub unsafe extern "C" fn gtfs_translations_parse(data: *const u8, len: usize) -> *mut TranslationResult {
+    if data.is_null() {
+        return into_raw(Err("data is null".to_string()));
+    }
+
+    let data = std::slice::from_raw_parts(data, len);
+    into_raw(from_reader(data, &ParseOptions::default()).map(|(result, _)| result).map_err(|err| err.to_string()))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn gtfs_translations_parse_file(path: *const c_char) -> *mut TranslationResult {
+    let Some(path) = to_str(path) else {
+        return into_raw(Err("path is null or not UTF-8".to_string()));
+    };
+
+    let parsed = File::open(path).map_err(|err| err.to_string())
+        .and_then(|file| from_reader(file, &ParseOptions::default()).map(|(result, _)| result).map_err(|err| err.to_string()));
+    into_raw(parsed)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn gtfs_translations_free(result: *mut TranslationResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn gtfs_translations_len(result: *const TranslationResult) -> usize {
+    result.as_ref().map(|result| result.translations.len()).unwrap_or_default()
+}
+
+// Resolves like TranslationResult::resolve, falling back from fr-CA to fr. record_id, record_sub_id and
+// original_value may be null. Returns null when there is no translation.
+#[no_mangle]
+pub unsafe extern "C" fn gtfs_translations_resolve(
+    result: *const TranslationResult,
+    table_name: *const c_char,
+    field_name: *const c_char,
+    record_id: *const c_char,
+    record_sub_id: *const c_char,
+    original_value: *const c_char,
+    language: *const c_char,
+) -> *mut c_char {
+    let Some(result) = result.as_ref() else {
+        return ptr::null_mut();
+    };
+    let Some(field) = to_str(table_name).zip(to_str(field_name)).and_then(|(table_name, field_name)| table_and_field_to_enum(table_name, field_name)) else {
+        return ptr::null_mut();
+    };
+    let Some(language) = to_str(language).and_then(|language| LanguageTag::parse(language).ok()) else {
+        return ptr::null_mut();
+    };
+    let (record_id, record_sub_id, original_value) = (to_str(record_id), to_str(record_sub_id), to_str(original_value));
+
+    fallback_languages(result, &language).iter()
+        .find_map(|language| result.resolve(&field, record_id, record_sub_id, original_value, language))
+        .and_then(|translation| CString::new(translation).ok())
+        .map(CString::into_raw)
+        .unwrap_or(ptr::null_mut())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn gtfs_translations_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+// Why the last parse on this thread failed, valid until the next failure. Null if nothing failed.
+#[no_mangle]
+pub extern "C" fn gtfs_translations_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map(|error| error.as_ptr()).unwrap_or(ptr::null()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+
+    fn parse() -> *mut TranslationResult {
+        let data = format!("{}stops,stop_name,fr,Gare,stop_1,,\n", HEADER);
+        unsafe { gtfs_translations_parse(data.as_ptr(), data.len()) }
+    }
+
+    #[test]
+    fn resolves_through_the_c_abi() {
+        unsafe {
+            let result = parse();
+            assert_eq!(gtfs_translations_len(result), 1);
+
+            let translation = gtfs_translations_resolve(result, c"stops".as_ptr(), c"stop_name".as_ptr(), c"stop_1".as_ptr(), ptr::null(), ptr::null(), c"fr-CA".as_ptr());
+            assert_eq!(CStr::from_ptr(translation).to_str(), Ok("Gare"));
+            gtfs_translations_string_free(translation);
+            gtfs_translations_free(result);
+        }
+    }
+
+    #[test]
+    fn returns_null_without_a_translation() {
+        unsafe {
+            let result = parse();
+            assert!(gtfs_translations_resolve(result, c"stops".as_ptr(), c"stop_name".as_ptr(), c"stop_2".as_ptr(), ptr::null(), ptr::null(), c"fr".as_ptr()).is_null());
+            assert!(gtfs_translations_resolve(result, c"stops".as_ptr(), c"stop_colour".as_ptr(), c"stop_1".as_ptr(), ptr::null(), ptr::null(), c"fr".as_ptr()).is_null());
+            gtfs_translations_free(result);
+        }
+    }
+
+    #[test]
+    fn reports_why_parsing_failed() {
+        unsafe {
+            assert!(gtfs_translations_parse(ptr::null(), 0).is_null());
+            assert_eq!(CStr::from_ptr(gtfs_translations_last_error()).to_str(), Ok("data is null"));
+
+            assert!(gtfs_translations_parse_file(c"/nonexistent/translations.txt".as_ptr()).is_null());
+            assert!(!gtfs_translations_last_error().is_null());
+        }
+    }
+}
@@ -0,0 +1,256 @@
+// The fields translations.txt can translate and the keys a translation is filed under. Only core and alloc, so the
+// embedded lookup shares them with TranslationResult.
+use alloc::string::{String, ToString};
+use core::cmp::Ordering;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::embedded::EmbeddedKey;
+
+#[derive(Debug, Hash, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum TranslatableField {
+    Agency(AgencyFields),
+    Areas(AreaFields),
+    Calendar(CalendarFields),
+    FareProducts(FareProductFields),
+    FeedInfo(FeedInfoFields),
+    Routes(RouteFields),
+    StopTimes(StopTimeFields),
+    Stops(StopFields),
+    Trips(TripFields),
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum TranslationKey {
+    Record(String),
+    RecordSub((String, String)),
+    Value(String),
+}
+
+// Fields sort by table and field name as they appear in translations.txt, so the order agrees with the files
+// and reports and does not depend on how the variants are declared
+impl Ord for TranslatableField {
+    fn cmp(&self, other: &Self) -> Ordering {
+        enum_to_table_and_field(self).cmp(&enum_to_table_and_field(other))
+    }
+}
+
+impl PartialOrd for TranslatableField {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum StopTimeFields {
+    Headsign,
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum RouteFields {
+    Desc,
+    LongName,
+    ShortName,
+    Url,
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum CalendarFields {
+    ServiceId,
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum FeedInfoFields {
+    PublisherName,
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum AreaFields {
+    Name,
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum AgencyFields {
+    Name,
+    FareUrl,
+    Url,
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum FareProductFields {
+    ProductName,
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum TripFields {
+    Headsign,
+    ShortName
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum StopFields {
+    Code,
+    Name,
+    TtsName,
+    PlatformCode,
+    Desc,
+}
+
+// Every field translations.txt can reference, in declaration order
+pub const TRANSLATABLE_FIELDS: [TranslatableField; 19] = [
+    TranslatableField::Agency(AgencyFields::Name),
+    TranslatableField::Agency(AgencyFields::FareUrl),
+    TranslatableField::Agency(AgencyFields::Url),
+    TranslatableField::Areas(AreaFields::Name),
+    TranslatableField::Calendar(CalendarFields::ServiceId),
+    TranslatableField::FareProducts(FareProductFields::ProductName),
+    TranslatableField::FeedInfo(FeedInfoFields::PublisherName),
+    TranslatableField::Routes(RouteFields::Desc),
+    TranslatableField::Routes(RouteFields::LongName),
+    TranslatableField::Routes(RouteFields::ShortName),
+    TranslatableField::Routes(RouteFields::Url),
+    TranslatableField::StopTimes(StopTimeFields::Headsign),
+    TranslatableField::Stops(StopFields::Code),
+    TranslatableField::Stops(StopFields::Name),
+    TranslatableField::Stops(StopFields::TtsName),
+    TranslatableField::Stops(StopFields::PlatformCode),
+    TranslatableField::Stops(StopFields::Desc),
+    TranslatableField::Trips(TripFields::Headsign),
+    TranslatableField::Trips(TripFields::ShortName),
+];
+
+pub fn table_and_field_to_enum(table_name: &str, field_name: &str) -> Option<TranslatableField> {
+    match table_name {
+        "agency" => {
+            match field_name {
+                "agency_name" => Some(TranslatableField::Agency(AgencyFields::Name)),
+                "agency_url" => Some(TranslatableField::Agency(AgencyFields::Url)),
+                "agency_fare_url" => Some(TranslatableField::Agency(AgencyFields::FareUrl)),
+                _ => None
+              }
+        },
+        "areas" => {
+            match field_name {
+                "area_name" => Some(TranslatableField::Areas(AreaFields::Name)),
+                _ => None
+              }
+        },
+        "routes" => {
+            match field_name {
+                "route_desc" => Some(TranslatableField::Routes(RouteFields::Desc)),
+                "route_long_name" => Some(TranslatableField::Routes(RouteFields::LongName)),
+                "route_short_name" => Some(TranslatableField::Routes(RouteFields::ShortName)),
+                "route_url" => Some(TranslatableField::Routes(RouteFields::Url)),
+                _ => None
+              }
+        },
+        "stop_times" => {
+            match field_name {
+                "stop_headsign" => Some(TranslatableField::StopTimes(StopTimeFields::Headsign)),
+                _ => None
+              }
+        },
+        "stops" => {
+            match field_name {
+                "stop_code" => Some(TranslatableField::Stops(StopFields::Code)),
+                "stop_name" => Some(TranslatableField::Stops(StopFields::Name)),
+                "tts_stop_name" => Some(TranslatableField::Stops(StopFields::TtsName)),
+                "stop_desc" => Some(TranslatableField::Stops(StopFields::Desc)),
+                "platform_code" => Some(TranslatableField::Stops(StopFields::PlatformCode)),
+                _ => None
+            }
+        },
+        "trips" => {
+            match field_name {
+                "trip_headsign" => Some(TranslatableField::Trips(TripFields::Headsign)),
+                "trip_short_name" => Some(TranslatableField::Trips(TripFields::ShortName)),
+                _ => None
+            }
+        },
+        "calendar" => {
+            match field_name {
+                "service_id" => Some(TranslatableField::Calendar(CalendarFields::ServiceId)),
+                _ => None
+            }
+        },
+        "fare_products" => {
+            match field_name {
+                "fare_product_name" => Some(TranslatableField::FareProducts(FareProductFields::ProductName)),
+                _ => None
+            }
+        },
+        "feed_info" => {
+            match field_name {
+                "feed_publisher_name" => Some(TranslatableField::FeedInfo(FeedInfoFields::PublisherName)),
+            _ => None
+            }
+        }
+        _ => None
+    }
+}
+
+pub fn enum_to_table_and_field(field: &TranslatableField) -> (&'static str, &'static str) {
+    match field {
+        TranslatableField::Agency(AgencyFields::Name) => ("agency", "agency_name"),
+        TranslatableField::Agency(AgencyFields::Url) => ("agency", "agency_url"),
+        TranslatableField::Agency(AgencyFields::FareUrl) => ("agency", "agency_fare_url"),
+        TranslatableField::Areas(AreaFields::Name) => ("areas", "area_name"),
+        TranslatableField::Routes(RouteFields::Desc) => ("routes", "route_desc"),
+        TranslatableField::Routes(RouteFields::LongName) => ("routes", "route_long_name"),
+        TranslatableField::Routes(RouteFields::ShortName) => ("routes", "route_short_name"),
+        TranslatableField::Routes(RouteFields::Url) => ("routes", "route_url"),
+        TranslatableField::StopTimes(StopTimeFields::Headsign) => ("stop_times", "stop_headsign"),
+        TranslatableField::Stops(StopFields::Code) => ("stops", "stop_code"),
+        TranslatableField::Stops(StopFields::Name) => ("stops", "stop_name"),
+        TranslatableField::Stops(StopFields::TtsName) => ("stops", "tts_stop_name"),
+        TranslatableField::Stops(StopFields::Desc) => ("stops", "stop_desc"),
+        TranslatableField::Stops(StopFields::PlatformCode) => ("stops", "platform_code"),
+        TranslatableField::Trips(TripFields::Headsign) => ("trips", "trip_headsign"),
+        TranslatableField::Trips(TripFields::ShortName) => ("trips", "trip_short_name"),
+        TranslatableField::Calendar(CalendarFields::ServiceId) => ("calendar", "service_id"),
+        TranslatableField::FareProducts(FareProductFields::ProductName) => ("fare_products", "fare_product_name"),
+        TranslatableField::FeedInfo(FeedInfoFields::PublisherName) => ("feed_info", "feed_publisher_name"),
+    }
+}
+
+impl TranslationKey {
+    // The key with its text borrowed, as resolution_order and the embedded lookup hand keys around
+    pub fn as_embedded(&self) -> EmbeddedKey<'_> {
+        match self {
+            TranslationKey::Record(record_id) => EmbeddedKey::Record(record_id),
+            TranslationKey::RecordSub((record_id, record_sub_id)) => EmbeddedKey::RecordSub(record_id, record_sub_id),
+            TranslationKey::Value(field_value) => EmbeddedKey::Value(field_value),
+        }
+    }
+}
+
+impl From<EmbeddedKey<'_>> for TranslationKey {
+    fn from(key: EmbeddedKey<'_>) -> Self {
+        match key {
+            EmbeddedKey::Record(record_id) => TranslationKey::Record(record_id.to_string()),
+            EmbeddedKey::RecordSub(record_id, record_sub_id) => TranslationKey::RecordSub((record_id.to_string(), record_sub_id.to_string())),
+            EmbeddedKey::Value(field_value) => TranslationKey::Value(field_value.to_string()),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use language_tags::LanguageTag;
+use crate::language::language_matches;
+use crate::{enum_to_table_and_field, TranslationLookup, TranslationResult};
+
+// Subsets that leave the original untouched, for deployments that only keep what they serve
+impl TranslationResult {
+    // A copy with the translations the predicate accepts, and the summaries cut down to match
+    pub fn retain_where<F>(&self, mut predicate: F) -> TranslationResult
+    where
+        F: FnMut(&TranslationLookup, &str) -> bool,
+    {
+        #[allow(deprecated)]
+        let mut subset = TranslationResult {
+            avaliable_languages: self.languages_as_written().to_vec(),
+            translations: self.translations.iter()
+                .filter(|(lookup, translation)| predicate(lookup, translation))
+                .map(|(lookup, translation)| (lookup.clone(), translation.clone()))
+                .collect(),
+            possible_translations: self.possible_translations.clone(),
+            provenance: self.provenance.clone(),
+            // counted again by prune_summaries
+            key_counts: Default::default(),
+        };
+
+        subset.prune_summaries();
+        subset
+    }
+
+    // Languages are ranges, so keeping fr also keeps fr-CA
+    pub fn filter_languages(&self, languages: &[LanguageTag]) -> TranslationResult {
+        self.retain_where(|lookup, _| languages.iter().any(|language| language_matches(language, &lookup.language)))
+    }
+
+    // Tables by their file name without .txt, such as stops or routes
+    pub fn filter_tables(&self, tables: &[&str]) -> TranslationResult {
+        self.retain_where(|lookup, _| tables.contains(&enum_to_table_and_field(&lookup.field).0))
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,fr-CA,Gare,stop_1,,\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n\
+            routes,route_long_name,de,Blau,route_1,,\n")
+    }
+
+    #[test]
+    fn keeps_languages_that_fall_back_to_a_filter() {
+        let french = result().filter_languages(&[LanguageTag::parse("fr").unwrap()]);
+
+        assert_eq!(french.translations.len(), 2);
+        assert_eq!(french.languages_as_written().len(), 2);
+        assert_eq!(french.possible_translations.len(), 2);
+    }
+
+    #[test]
+    fn keeps_tables_and_their_summaries() {
+        let routes = result().filter_tables(&["routes"]);
+
+        assert_eq!(routes.translations.len(), 1);
+        assert_eq!(routes.languages_as_written(), vec![LanguageTag::parse("de").unwrap()]);
+    }
+
+    #[test]
+    fn retains_by_predicate_without_touching_the_original() {
+        let result = result();
+        let short = result.retain_where(|_, translation| translation.len() < 5);
+
+        assert_eq!(short.translations.len(), 3);
+        assert_eq!(result.translations.len(), 4);
+    }
+}
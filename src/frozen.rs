@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::error::Error;
+use fst::Map;
+use language_tags::LanguageTag;
+use crate::memory::{DuplicateTextStats, TextPool};
+use crate::{resolution_keys, TranslatableField, TranslationKey, TranslationLookup, TranslationResult, TRANSLATABLE_FIELDS};
+
+// An immutable copy of a TranslationResult for feeds that are loaded once and only read afterwards.
+// Keys live in an FST, which shares common prefixes such as the field, language and record id stems,
+// and identical translations are stored once in a single text buffer.
+pub struct FrozenTranslations {
+    pub(crate) index: Map<Vec<u8>>,
+    pub(crate) text: String,
+    // Byte range of every distinct translation in text, the FST maps keys to positions in here
+    pub(crate) spans: Vec<(u32, u32)>,
+}
+
+const KEY_RECORD: u8 = 0;
+const KEY_RECORD_SUB: u8 = 1;
+const KEY_VALUE: u8 = 2;
+
+fn push_part(encoded: &mut Vec<u8>, part: &str) {
+    encoded.extend_from_slice(&(part.len() as u32).to_be_bytes());
+    encoded.extend_from_slice(part.as_bytes());
+}
+
+// field, language and key, each string prefixed with its length so no separator can clash with the data
+pub(crate) fn encode_key(field: &TranslatableField, key: &TranslationKey, language: &str) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(32);
+    encoded.push(TRANSLATABLE_FIELDS.iter().position(|candidate| candidate == field).unwrap_or_default() as u8);
+    push_part(&mut encoded, language);
+
+    match key {
+        TranslationKey::Record(record_id) => {
+            encoded.push(KEY_RECORD);
+            push_part(&mut encoded, record_id);
+        }
+        TranslationKey::RecordSub((record_id, record_sub_id)) => {
+            encoded.push(KEY_RECORD_SUB);
+            push_part(&mut encoded, record_id);
+            push_part(&mut encoded, record_sub_id);
+        }
+        TranslationKey::Value(field_value) => {
+            encoded.push(KEY_VALUE);
+            push_part(&mut encoded, field_value);
+        }
+    }
+
+    encoded
+}
+
+fn read_part(encoded: &[u8]) -> Option<(String, &[u8])> {
+    let len = u32::from_be_bytes(encoded.get(..4)?.try_into().ok()?) as usize;
+    let part = std::str::from_utf8(encoded.get(4..4 + len)?).ok()?.to_string();
+    Some((part, &encoded[4 + len..]))
+}
+
+fn decode_key(encoded: &[u8]) -> Option<TranslationLookup> {
+    let field = TRANSLATABLE_FIELDS.get(*encoded.first()? as usize)?.clone();
+    let (language, rest) = read_part(&encoded[1..])?;
+
+    let key = match *rest.first()? {
+        KEY_RECORD => TranslationKey::Record(read_part(&rest[1..])?.0),
+        KEY_RECORD_SUB => {
+            let (record_id, rest) = read_part(&rest[1..])?;
+            TranslationKey::RecordSub((record_id, read_part(rest)?.0))
+        }
+        KEY_VALUE => TranslationKey::Value(read_part(&rest[1..])?.0),
+        _ => return None,
+    };
+
+    Some(TranslationLookup { language: LanguageTag::parse(&language).ok()?, field, key })
+}
+
+impl FrozenTranslations {
+    fn span(&self, index: u64) -> &str {
+        let (start, end) = self.spans[index as usize];
+        &self.text[start as usize..end as usize]
+    }
+
+    pub fn get(&self, field: &TranslatableField, key: &TranslationKey, language: &LanguageTag) -> Option<&str> {
+        self.index.get(encode_key(field, key, language.as_str())).map(|index| self.span(index))
+    }
+
+    // Same precedence as TranslationResult::resolve
+    pub fn resolve(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>, language: &LanguageTag) -> Option<&str> {
+        resolution_keys(record_id, record_sub_id, original_value).iter().find_map(|key| self.get(field, key, language))
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    // Estimated heap bytes, comparable with TranslationResult::memory_usage
+    pub fn memory_usage(&self) -> usize {
+        self.index.as_fst().size() + self.text.capacity() + self.spans.capacity() * std::mem::size_of::<(u32, u32)>()
+    }
+
+    pub fn duplicate_text_stats(&self) -> DuplicateTextStats {
+        let mut stats = DuplicateTextStats {
+            distinct_values: self.spans.len(),
+            distinct_value_bytes: self.text.len(),
+            stored_bytes: self.text.len(),
+            ..DuplicateTextStats::default()
+        };
+        let mut stream = self.index.stream();
+
+        while let Some((_, index)) = fst::Streamer::next(&mut stream) {
+            stats.values += 1;
+            stats.value_bytes += self.span(index).len();
+        }
+
+        stats
+    }
+
+    // Back to a mutable TranslationResult for editing
+    pub fn thaw(&self) -> TranslationResult {
+        let mut result = TranslationResult::default();
+        let mut texts = TextPool::default();
+        let mut stream = self.index.stream();
+
+        while let Some((encoded, index)) = fst::Streamer::next(&mut stream) {
+            if let Some(lookup) = decode_key(encoded) {
+                result.insert_lookup(lookup, texts.share(self.span(index)));
+            }
+        }
+
+        result
+    }
+}
+
+fn text_offset(offset: usize) -> Result<u32, Box<dyn Error>> {
+    u32::try_from(offset).map_err(|_| "translations are too large to freeze, their text passes 4 GiB".into())
+}
+
+impl TranslationResult {
+    // Rebuilds the index into a FrozenTranslations. The HashMap stays the way to edit translations.
+    // Spans are u32 offsets, so this fails when the distinct translations add up to more than 4 GiB.
+    pub fn freeze(&self) -> Result<FrozenTranslations, Box<dyn Error>> {
+        let mut entries = self.translations.iter()
+            .map(|(lookup, translation)| (encode_key(&lookup.field, &lookup.key, lookup.language.as_str()), translation.as_ref()))
+            .collect::<Vec<(Vec<u8>, &str)>>();
+        // the FST builder needs keys in lexicographic order
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        let mut text = String::new();
+        let mut spans: Vec<(u32, u32)> = vec![];
+        let mut span_of: HashMap<&str, u64> = HashMap::new();
+        let mut builder = fst::MapBuilder::memory();
+
+        for (encoded, translation) in entries.iter() {
+            let index = match span_of.get(translation) {
+                Some(index) => *index,
+                None => {
+                    let start = text.len();
+                    text.push_str(translation);
+                    spans.push((text_offset(start)?, text_offset(text.len())?));
+                    span_of.insert(translation, spans.len() as u64 - 1);
+                    spans.len() as u64 - 1
+                }
+            };
+
+            // keys are unique and sorted, so inserting cannot fail
+            builder.insert(encoded, index).expect("sorted unique keys");
+        }
+
+        let index = Map::new(builder.into_inner().expect("in-memory fst")).expect("freshly built fst");
+
+        Ok(FrozenTranslations { index, text, spans })
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{StopFields, TripFields};
+
+    fn result() -> TranslationResult {
+        let mut rows = String::new();
+        for i in 0..200 {
+            rows.push_str(&format!("stops,stop_name,fr,Gare {},stop_{},,\n", i % 20, i));
+            rows.push_str(&format!("stop_times,stop_headsign,de,Zentrum,trip_{},{},\n", i, i % 5));
+        }
+        rows.push_str("trips,trip_headsign,fr,Centre-ville,,,Downtown\n");
+
+        translations(&rows)
+    }
+
+    #[test]
+    fn looks_up_by_key_and_language() {
+        let frozen = result().freeze().unwrap();
+        let field = TranslatableField::Stops(StopFields::Name);
+        let key = TranslationKey::Record("stop_45".to_string());
+
+        assert_eq!(frozen.get(&field, &key, &LanguageTag::parse("fr").unwrap()), Some("Gare 5"));
+        assert_eq!(frozen.get(&field, &key, &LanguageTag::parse("de").unwrap()), None);
+    }
+
+    #[test]
+    fn resolves_with_key_precedence() {
+        let frozen = result().freeze().unwrap();
+
+        assert_eq!(frozen.resolve(&TranslatableField::Trips(TripFields::Headsign), Some("trip_1"), None, Some("Downtown"), &LanguageTag::parse("fr").unwrap()), Some("Centre-ville"));
+    }
+
+    #[test]
+    fn thaws_to_the_same_translations_in_less_memory() {
+        let result = result();
+        let frozen = result.freeze().unwrap();
+
+        assert_eq!(frozen.len(), result.translations.len());
+        assert_eq!(frozen.thaw().translations, result.translations);
+        assert!(frozen.memory_usage() < result.memory_usage());
+        assert_eq!(frozen.duplicate_text_stats(), result.duplicate_text_stats());
+    }
+
+    #[test]
+    fn rejects_text_past_the_offset_range() {
+        assert!(text_offset(u32::MAX as usize + 1).is_err());
+    }
+}
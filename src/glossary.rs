@@ -0,0 +1,112 @@
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize};
+use crate::language::language_matches;
+use crate::validate::{sort_notices, source_text};
+use crate::{enum_to_table_and_field, Notice, Severity, TranslationResult};
+
+// The approved translation of a source term in one language, such as Station as Gare in fr. Variants are
+// translations the agency has ruled out, like Station in French text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub term: String,
+    // A language range, so fr also covers fr-CA
+    pub language: String,
+    pub approved: String,
+    #[serde(default)]
+    pub variants: Vec<String>,
+}
+
+// Case-insensitive, and only at word boundaries so Station does not match Stationery
+fn contains_word(text: &str, word: &str) -> bool {
+    let (text, word) = (text.to_lowercase(), word.to_lowercase());
+
+    if word.is_empty() {
+        return false;
+    }
+
+    text.match_indices(&word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+// Flags translations that use a ruled out variant of a term, or whose source has a term the translation lacks the
+// approved wording of. Terms are looked for in the original value of value keys and, with source_lang, in the
+// source language translation of the same record. Each notice suggests the approved wording.
+pub fn check_glossary(result: &TranslationResult, glossary: &[GlossaryEntry], source_lang: Option<&LanguageTag>) -> Vec<Notice> {
+    let entries = glossary.iter()
+        .filter_map(|entry| Some((LanguageTag::parse(&entry.language).ok()?, entry)))
+        .collect::<Vec<_>>();
+    let mut notices = vec![];
+
+    for (lookup, translation) in result.translations.iter().filter(|(lookup, _)| Some(&lookup.language) != source_lang) {
+        let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+        let source = source_text(result, lookup, source_lang);
+
+        for (_, entry) in entries.iter().filter(|(language, _)| language_matches(language, &lookup.language)) {
+            if contains_word(translation, &entry.approved) {
+                continue;
+            }
+
+            let notice = match entry.variants.iter().find(|variant| contains_word(translation, variant)) {
+                Some(variant) => Notice::about(lookup, "glossary_variant", Severity::Warning,
+                    format!("{}.{} {} translation \"{}\" uses \"{}\" where the glossary has \"{}\"", table_name, field_name, lookup.language, translation, variant, entry.approved)),
+                None if source.is_some_and(|source| contains_word(source, &entry.term)) => Notice::about(lookup, "glossary_term_missing", Severity::Info,
+                    format!("{}.{} {} translation \"{}\" does not use \"{}\" for \"{}\"", table_name, field_name, lookup.language, translation, entry.approved, entry.term)),
+                None => continue,
+            };
+
+            notices.push(Notice {
+                suggestion: Some(entry.approved.clone()),
+                ..notice
+            });
+        }
+    }
+
+    sort_notices(&mut notices);
+    notices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn glossary() -> Vec<GlossaryEntry> {
+        vec![GlossaryEntry {
+            term: "Station".to_string(),
+            language: "fr".to_string(),
+            approved: "Gare".to_string(),
+            variants: vec!["Station".to_string()],
+        }]
+    }
+
+    fn notices(rows: &str) -> Vec<(String, Option<String>)> {
+        check_glossary(&translations(rows), &glossary(), Some(&LanguageTag::parse("en").unwrap()))
+            .into_iter()
+            .map(|notice| (notice.code, notice.language))
+            .collect()
+    }
+
+    #[test]
+    fn flags_variants_with_the_approved_term() {
+        let result = translations("stops,stop_name,en,Central Station,stop_1,,\nstops,stop_name,fr,Station Centrale,stop_1,,\n");
+        let notices = check_glossary(&result, &glossary(), Some(&LanguageTag::parse("en").unwrap()));
+
+        assert_eq!(notices.len(), 1);
+        assert_eq!((notices[0].code.as_str(), notices[0].language.as_deref()), ("glossary_variant", Some("fr")));
+        assert_eq!(notices[0].suggestion.as_deref(), Some("Gare"));
+    }
+
+    #[test]
+    fn flags_missing_terms_in_fallback_languages() {
+        assert_eq!(notices("stops,stop_name,en,North Station,stop_2,,\nstops,stop_name,fr-CA,Nord,stop_2,,\n"),
+            vec![("glossary_term_missing".to_string(), Some("fr-CA".to_string()))]);
+    }
+
+    #[test]
+    fn matches_whole_words_of_the_source() {
+        assert!(notices("stops,stop_name,fr,Gare du Sud,,,South Station\nstops,stop_name,fr,Papeterie,,,Stationery\n").is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+use crate::parse::rows_for_bytes;
+use crate::{from_reader, ParseOptions, ParseStats, TranslationResult};
+
+// Most rows the declared size of translations.txt may preallocate. The size comes from the archive, so a crafted
+// one could otherwise ask for more memory than any limit allows before a single row is read.
+const MAX_ROWS_HINT: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GtfsZipTranslations {
+    pub translations: TranslationResult,
+    pub stats: ParseStats,
+    // feed_info.feed_lang next to translations.txt, when present
+    pub feed_lang: Option<String>,
+    // Path of translations.txt inside the archive, which is not always at the root
+    pub path: String,
+}
+
+// Some agencies zip the folder rather than its contents, so the shallowest translations.txt wins
+pub(crate) fn find_entry<R: Read + Seek>(archive: &ZipArchive<R>, file_name: &str) -> Option<String> {
+    archive.file_names()
+        .filter(|name| *name == file_name || name.ends_with(&format!("/{}", file_name)))
+        .filter(|name| !name.starts_with("__MACOSX/"))
+        .min_by_key(|name| (name.matches('/').count(), name.len()))
+        .map(String::from)
+}
+
+fn read_feed_lang<R: Read + Seek>(archive: &mut ZipArchive<R>, path: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_reader(archive.by_name(path)?);
+
+    let Some(column) = rdr.headers()?.iter().position(|header| header.trim_start_matches('\u{feff}').trim() == "feed_lang") else {
+        return Ok(None);
+    };
+
+    let feed_lang = rdr.records().next().transpose()?
+        .and_then(|record| record.get(column).map(|feed_lang| feed_lang.trim().to_string()))
+        .filter(|feed_lang| !feed_lang.is_empty());
+
+    Ok(feed_lang)
+}
+
+// Rows to preallocate for an entry of the declared size, never more than the limits would let through
+fn rows_hint(declared_size: u64, options: &ParseOptions) -> usize {
+    rows_for_bytes(declared_size)
+        .min(MAX_ROWS_HINT)
+        .min(options.max_rows.unwrap_or(MAX_ROWS_HINT))
+        .min(options.max_total_bytes.map_or(MAX_ROWS_HINT, rows_for_bytes))
+}
+
+// Returns None when the archive has no translations.txt
+pub fn from_gtfs_zip<R: Read + Seek>(reader: R, options: &ParseOptions) -> Result<Option<GtfsZipTranslations>, Box<dyn Error>> {
+    from_gtfs_archive(&mut ZipArchive::new(reader)?, options)
+}
+
+// For callers that read other tables of the same archive, such as TranslationUniverse::from_gtfs_archive
+pub fn from_gtfs_archive<R: Read + Seek>(archive: &mut ZipArchive<R>, options: &ParseOptions) -> Result<Option<GtfsZipTranslations>, Box<dyn Error>> {
+    let Some(path) = find_entry(archive, "translations.txt") else {
+        return Ok(None);
+    };
+
+    let entry = archive.by_name(&path)?;
+    let options = ParseOptions {
+        expected_rows: options.expected_rows.or(Some(rows_hint(entry.size(), options))),
+        ..options.clone()
+    };
+    let (translations, stats) = from_reader(entry, &options)?;
+
+    let feed_info_path = format!("{}feed_info.txt", path.strip_suffix("translations.txt").unwrap_or_default());
+    let has_feed_info = archive.file_names().any(|name| name == feed_info_path);
+    let feed_lang = match has_feed_info {
+        true => read_feed_lang(archive, &feed_info_path)?,
+        false => None,
+    };
+
+    Ok(Some(GtfsZipTranslations {
+        translations,
+        stats,
+        feed_lang,
+        path,
+    }))
+}
+
+pub fn from_gtfs_zip_path<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Option<GtfsZipTranslations>, Box<dyn Error>> {
+    from_gtfs_zip(BufReader::new(File::open(path)?), options)
+}
+
+pub fn from_gtfs_zip_bytes(bytes: &[u8], options: &ParseOptions) -> Result<Option<GtfsZipTranslations>, Box<dyn Error>> {
+    from_gtfs_zip(std::io::Cursor::new(bytes), options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use crate::test_util::HEADER;
+
+    fn zip_with(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+
+        for (name, contents) in files {
+            writer.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn finds_nested_translations_and_the_feed_language() {
+        let translations = format!("{}stops,stop_name,fr,Gare,stop_1,,\n", HEADER);
+        let bytes = zip_with(&[
+            ("feed/stops.txt", "stop_id,stop_name\nstop_1,Station\n"),
+            ("feed/feed_info.txt", "\u{feff}feed_lang,feed_publisher_name,feed_publisher_url\nen,Agency,https://example.com\n"),
+            ("feed/translations.txt", &translations),
+        ]);
+
+        let parsed = from_gtfs_zip_bytes(&bytes, &ParseOptions::default()).unwrap().unwrap();
+
+        assert_eq!(parsed.path, "feed/translations.txt");
+        assert_eq!(parsed.feed_lang.as_deref(), Some("en"));
+        assert_eq!(parsed.translations.translations.len(), 1);
+    }
+
+    #[test]
+    fn returns_none_without_translations() {
+        let without = zip_with(&[("stops.txt", "stop_id,stop_name\n")]);
+
+        assert!(from_gtfs_zip_bytes(&without, &ParseOptions::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_bytes_that_are_not_a_zip() {
+        assert!(from_gtfs_zip_bytes(b"stop_id,stop_name\n", &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn caps_the_rows_hint() {
+        assert_eq!(rows_hint(u64::MAX, &ParseOptions::default()), MAX_ROWS_HINT);
+        assert_eq!(rows_hint(u64::MAX, &ParseOptions { max_rows: Some(10), ..ParseOptions::default() }), 10);
+        assert_eq!(rows_hint(4800, &ParseOptions::default()), 100);
+    }
+}
@@ -0,0 +1,140 @@
+use std::borrow::Cow;
+use serde::{Deserialize, Serialize};
+
+// What to do with HTML in translations, which some feeds carry over from their websites into desc and url fields.
+// field_value is never touched, as it has to match the feed's own value.
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum HtmlPolicy {
+    #[default]
+    Preserve,
+    // &amp; becomes &, &eacute; becomes é and &#8211; becomes –. Unknown entities are left as they are.
+    DecodeEntities,
+    // Drops tags such as <b> and <br/>, then decodes entities
+    StripTags,
+}
+
+const ENTITIES: [(&str, char); 22] = [
+    ("amp", '&'), ("lt", '<'), ("gt", '>'), ("quot", '"'), ("apos", '\''), ("nbsp", '\u{00A0}'),
+    ("ndash", '\u{2013}'), ("mdash", '\u{2014}'), ("hellip", '\u{2026}'), ("laquo", '\u{00AB}'), ("raquo", '\u{00BB}'),
+    ("lsquo", '\u{2018}'), ("rsquo", '\u{2019}'), ("ldquo", '\u{201C}'), ("rdquo", '\u{201D}'),
+    ("eacute", '\u{00E9}'), ("egrave", '\u{00E8}'), ("agrave", '\u{00E0}'), ("ccedil", '\u{00E7}'),
+    ("auml", '\u{00E4}'), ("ouml", '\u{00F6}'), ("uuml", '\u{00FC}'),
+];
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity.strip_prefix('#') {
+        Some(number) => match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => number.parse::<u32>().ok(),
+        }.and_then(char::from_u32),
+        None => ENTITIES.iter().find(|(name, _)| *name == entity).map(|(_, character)| *character),
+    }
+}
+
+fn decode_entities(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // entities are short, a lone & is much more likely than a 12 character entity
+        let entity = rest[1..].find(';').filter(|end| *end <= 12).and_then(|end| Some((decode_entity(&rest[1..=end])?, end)));
+        match entity {
+            Some((character, end)) => {
+                decoded.push(character);
+                rest = &rest[end + 2..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    Cow::Owned(decoded)
+}
+
+// A < starts a tag when a letter, / or ! follows it, so "< 5 min" is kept
+fn strip_tags(text: &str) -> Cow<'_, str> {
+    let is_tag = |rest: &str| rest[1..].starts_with(|next: char| next.is_ascii_alphabetic() || next == '/' || next == '!') && rest.contains('>');
+
+    if !text.match_indices('<').any(|(start, _)| is_tag(&text[start..])) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        stripped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        match is_tag(rest) {
+            true => rest = &rest[rest.find('>').unwrap_or(rest.len() - 1) + 1..],
+            false => {
+                stripped.push('<');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    stripped.push_str(rest);
+    Cow::Owned(stripped)
+}
+
+pub fn apply_html_policy<'a>(text: &'a str, policy: HtmlPolicy) -> Cow<'a, str> {
+    match policy {
+        HtmlPolicy::Preserve => Cow::Borrowed(text),
+        HtmlPolicy::DecodeEntities => decode_entities(text),
+        HtmlPolicy::StripTags => match strip_tags(text) {
+            Cow::Borrowed(text) => decode_entities(text),
+            Cow::Owned(stripped) => Cow::Owned(decode_entities(&stripped).into_owned()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+    use crate::{from_reader, ParseOptions};
+
+    #[test]
+    fn decodes_known_entities() {
+        assert_eq!(apply_html_policy("Caf&eacute; &amp; Gare &#8211; Quai&nbsp;2 &unknown; R&D", HtmlPolicy::DecodeEntities),
+            "Caf\u{00E9} & Gare \u{2013} Quai\u{00A0}2 &unknown; R&D");
+    }
+
+    #[test]
+    fn strips_tags_and_decodes() {
+        assert_eq!(apply_html_policy("<p>Arr\u{00EA}t <b>fermé</b><br/>&lt; 5 min</p>", HtmlPolicy::StripTags), "Arr\u{00EA}t fermé< 5 min");
+    }
+
+    #[test]
+    fn borrows_text_without_markup() {
+        assert!(matches!(apply_html_policy("< 5 min", HtmlPolicy::StripTags), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn records_changed_translations_while_parsing() {
+        let data = format!("{}stops,stop_desc,fr,Quai <b>A</b> &amp; B,stop_1,,\n\
+            stops,stop_desc,fr,Quai C,stop_2,,\n\
+            stops,stop_name,fr,Gare,,,Caf&eacute;\n", HEADER);
+        let options = ParseOptions { html: HtmlPolicy::StripTags, ..ParseOptions::default() };
+        let (result, stats) = from_reader(data.as_bytes(), &options).unwrap();
+
+        // only translations change, not the field value they are keyed by
+        assert_eq!(stats.text_changes.len(), 1);
+        assert_eq!((stats.text_changes[0].line, stats.text_changes[0].after.as_str()), (Some(2), "Quai A & B"));
+        assert_eq!(result.translations.len(), 3);
+    }
+}
@@ -0,0 +1,192 @@
+use std::error::Error;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use crate::{from_gtfs_zip_bytes, from_reader, LimitExceeded, ParseOptions, ParseStats, TranslationResult};
+
+// Validators remembered from the previous fetch of the same url
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConditionalRequest {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum FetchResult {
+    // The server answered 304, the copy from the previous fetch is still current
+    NotModified,
+    Fetched {
+        translations: TranslationResult,
+        stats: ParseStats,
+        // Pass these back on the next poll
+        conditional: ConditionalRequest,
+    },
+}
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+// The archive cap of downloads that limit max_total_bytes without setting max_archive_bytes
+pub const DEFAULT_MAX_ARCHIVE_BYTES: u64 = 1 << 30;
+
+// A translations.txt is held to max_total_bytes and a zip to max_archive_bytes, by the declared length and by what
+// has come in so far. The translations.txt inside a zip is also limited to max_total_bytes as it is parsed.
+fn check_body(body: &[u8], declared: u64, options: &ParseOptions) -> Result<(), LimitExceeded> {
+    let size = declared.max(body.len() as u64);
+
+    if body.starts_with(ZIP_MAGIC) {
+        let max_archive_bytes = options.max_archive_bytes.or(options.max_total_bytes.map(|_| DEFAULT_MAX_ARCHIVE_BYTES));
+
+        match max_archive_bytes {
+            Some(max_archive_bytes) if size > max_archive_bytes => Err(LimitExceeded::ArchiveBytes { max_archive_bytes }),
+            _ => Ok(()),
+        }
+    } else {
+        match options.max_total_bytes {
+            Some(max_total_bytes) if size > max_total_bytes => Err(LimitExceeded::TotalBytes { max_total_bytes }),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Reads the body as it arrives, checking it once there are enough bytes to tell a zip from a csv
+async fn read_body(mut response: reqwest::Response, options: &ParseOptions) -> Result<Vec<u8>, Box<dyn Error>> {
+    let declared = response.content_length().unwrap_or_default();
+    let mut body: Vec<u8> = vec![];
+
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+
+        if body.len() >= ZIP_MAGIC.len() {
+            check_body(&body, declared, options)?;
+        }
+    }
+    check_body(&body, declared, options)?;
+
+    Ok(body)
+}
+
+// Downloads a translations.txt or a whole GTFS zip, which is recognised by its magic bytes
+pub async fn from_url(url: &str, conditional: &ConditionalRequest, options: &ParseOptions) -> Result<FetchResult, Box<dyn Error>> {
+    from_url_with_client(&reqwest::Client::new(), url, conditional, options).await
+}
+
+pub async fn from_url_with_client(client: &reqwest::Client, url: &str, conditional: &ConditionalRequest, options: &ParseOptions) -> Result<FetchResult, Box<dyn Error>> {
+    let mut request = client.get(url);
+
+    if let Some(etag) = &conditional.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+
+    if let Some(last_modified) = &conditional.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+
+    let response = request.send().await?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(FetchResult::NotModified);
+    }
+
+    let response = response.error_for_status()?;
+
+    let header = |name| response.headers().get(name).and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok()).map(String::from);
+    let conditional = ConditionalRequest {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+
+    let body = read_body(response, options).await?;
+
+    let (translations, stats) = if body.starts_with(ZIP_MAGIC) {
+        let parsed = from_gtfs_zip_bytes(&body, options)?.ok_or("gtfs zip has no translations.txt")?;
+        (parsed.translations, parsed.stats)
+    } else {
+        from_reader(body.as_slice(), options)?
+    };
+
+    Ok(FetchResult::Fetched {
+        translations,
+        stats,
+        conditional,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use crate::test_util::HEADER;
+
+    fn body() -> String {
+        format!("{}stops,stop_name,fr,Gare,stop_1,,\n", HEADER)
+    }
+
+    // Answers 304 when the request carries the etag it handed out, 200 otherwise
+    async fn serve(listener: TcpListener, requests: usize) {
+        for _ in 0..requests {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0; 4096];
+            let read = socket.read(&mut buffer).await.unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_lowercase();
+
+            let response = if request.contains("if-none-match: \"v1\"") {
+                "HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            } else {
+                format!("HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body().len(), body())
+            };
+
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn conditional_fetch() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/translations.txt", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve(listener, 2));
+
+        let first = from_url(&url, &ConditionalRequest::default(), &ParseOptions::default()).await.unwrap();
+        let FetchResult::Fetched { translations, conditional, .. } = first else {
+            panic!("expected a full response");
+        };
+        assert_eq!(translations.translations.len(), 1);
+        assert_eq!(conditional.etag.as_deref(), Some("\"v1\""));
+
+        let second = from_url(&url, &conditional, &ParseOptions::default()).await.unwrap();
+        assert_eq!(second, FetchResult::NotModified);
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn limits_downloaded_translations() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/translations.txt", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve(listener, 1));
+
+        let limited = ParseOptions { max_total_bytes: Some(50), ..ParseOptions::default() };
+        let err = from_url(&url, &ConditionalRequest::default(), &limited).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<LimitExceeded>(), Some(&LimitExceeded::TotalBytes { max_total_bytes: 50 }));
+
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn limits_zips_by_the_archive_cap() {
+        let zip = [ZIP_MAGIC, &[0; 96]].concat();
+        let options = ParseOptions { max_total_bytes: Some(10), max_archive_bytes: Some(200), ..ParseOptions::default() };
+
+        assert_eq!(check_body(&zip, 0, &options), Ok(()));
+        assert_eq!(check_body(&zip, 500, &options), Err(LimitExceeded::ArchiveBytes { max_archive_bytes: 200 }));
+        assert_eq!(check_body(b"PK,table_name,field_name", 0, &options), Err(LimitExceeded::TotalBytes { max_total_bytes: 10 }));
+    }
+
+    #[test]
+    fn defaults_the_archive_cap_when_text_is_limited() {
+        let options = ParseOptions { max_total_bytes: Some(10), ..ParseOptions::default() };
+
+        assert_eq!(check_body(ZIP_MAGIC, DEFAULT_MAX_ARCHIVE_BYTES + 1, &options), Err(LimitExceeded::ArchiveBytes { max_archive_bytes: DEFAULT_MAX_ARCHIVE_BYTES }));
+        assert_eq!(check_body(ZIP_MAGIC, DEFAULT_MAX_ARCHIVE_BYTES + 1, &ParseOptions::default()), Ok(()));
+    }
+}
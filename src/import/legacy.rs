@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::error::Error;
+use crate::{enum_to_table_and_field, RawTranslation, TranslatableField};
+use crate::{AgencyFields, RouteFields, StopFields, StopTimeFields, TripFields};
+
+// Fields that could carry a trans_id in the pre-standard Google format
+pub const LEGACY_TRANSLATABLE_FIELDS: [TranslatableField; 7] = [
+    TranslatableField::Agency(AgencyFields::Name),
+    TranslatableField::Stops(StopFields::Name),
+    TranslatableField::Stops(StopFields::Desc),
+    TranslatableField::Routes(RouteFields::LongName),
+    TranslatableField::Routes(RouteFields::ShortName),
+    TranslatableField::Trips(TripFields::Headsign),
+    TranslatableField::StopTimes(StopTimeFields::Headsign),
+];
+
+pub fn is_legacy_header(headers: &csv::StringRecord) -> bool {
+    let has = |name: &str| headers.iter().any(|header| header.trim() == name);
+
+    has("trans_id") && has("lang") && has("translation")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LegacyColumns {
+    trans_id: usize,
+    lang: usize,
+    translation: usize,
+    fields: Vec<TranslatableField>,
+}
+
+impl LegacyColumns {
+    pub(crate) fn new(headers: &csv::StringRecord, fields: &[TranslatableField]) -> Result<LegacyColumns, Box<dyn Error>> {
+        let column = |name: &str| headers.iter().position(|header| header.trim() == name)
+            .ok_or_else(|| format!("not a legacy trans_id translations.txt, missing {}", name));
+
+        Ok(LegacyColumns {
+            trans_id: column("trans_id")?,
+            lang: column("lang")?,
+            translation: column("translation")?,
+            fields: fields.to_vec(),
+        })
+    }
+
+    // In the legacy format the tables hold a trans_id in place of the text and translations.txt maps
+    // (trans_id, lang) to a string. That is a value-keyed translation, so every row becomes one field_value
+    // row per configured field, as the legacy file does not say which columns reference a trans_id.
+    pub(crate) fn rows(&self, record: &csv::StringRecord) -> Vec<RawTranslation> {
+        let (Some(trans_id), Some(lang), Some(translation)) = (record.get(self.trans_id), record.get(self.lang), record.get(self.translation)) else {
+            return vec![];
+        };
+
+        if trans_id.is_empty() || translation.is_empty() {
+            return vec![];
+        }
+
+        self.fields.iter().map(|field| {
+            let (table_name, field_name) = enum_to_table_and_field(field);
+
+            RawTranslation {
+                table_name: table_name.to_string(),
+                field_name: field_name.to_string(),
+                language: lang.to_string(),
+                translation: translation.to_string(),
+                record_id: None,
+                record_sub_id: None,
+                field_value: Some(trans_id.to_string()),
+                extras: HashMap::new(),
+            }
+        }).collect()
+    }
+}
+
+pub fn legacy_csv(data: &str, fields: &[TranslatableField]) -> Result<Vec<RawTranslation>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_reader(data.as_bytes());
+    let columns = LegacyColumns::new(rdr.headers()?, fields)?;
+
+    let mut rows: Vec<RawTranslation> = vec![];
+
+    for record in rdr.records() {
+        rows.extend(columns.rows(&record?));
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translate_raw_translations;
+    use language_tags::LanguageTag;
+
+    #[test]
+    fn converts_to_value_keys() {
+        let data = "trans_id,lang,translation\n\
+            Central Station,fr,Gare Centrale\n\
+            Central Station,de,Hauptbahnhof\n";
+
+        let rows = legacy_csv(data, &[TranslatableField::Stops(StopFields::Name)]).unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let result = translate_raw_translations(rows);
+        let fr = LanguageTag::parse("fr").unwrap();
+        assert_eq!(result.resolve(&TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, Some("Central Station"), &fr), Some("Gare Centrale"));
+    }
+
+    #[test]
+    fn rejects_another_header() {
+        assert!(legacy_csv("table_name,field_name,language,translation\n", &LEGACY_TRANSLATABLE_FIELDS).is_err());
+    }
+}
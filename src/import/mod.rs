@@ -0,0 +1,5 @@
+pub mod legacy;
+pub(crate) mod wide_csv;
+
+pub use legacy::legacy_csv;
+pub use wide_csv::wide_csv;
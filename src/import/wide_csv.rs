@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::error::Error;
+use crate::{normalize_language, RawTranslation};
+
+// Columns that describe the record, every other column holds the translation for the language named in its header
+const TABLE_COLUMNS: [&str; 2] = ["table_name", "table"];
+const FIELD_COLUMNS: [&str; 2] = ["field_name", "field"];
+const RECORD_ID_COLUMNS: [&str; 1] = ["record_id"];
+const RECORD_SUB_ID_COLUMNS: [&str; 1] = ["record_sub_id"];
+const ORIGINAL_COLUMNS: [&str; 3] = ["original", "field_value", "source"];
+
+fn find_column(headers: &csv::StringRecord, names: &[&str]) -> Option<usize> {
+    headers.iter().position(|header| names.contains(&header.trim().to_lowercase().as_str()))
+}
+
+// Tags of real languages have a two or three letter primary subtag. Longer ones are well formed but unassigned,
+// and are what headers such as notes or status parse as.
+fn language_column(header: &str) -> Option<String> {
+    normalize_language(header)
+        .filter(|language| (2..=3).contains(&language.primary_language().len()))
+        .map(|language| language.to_string())
+}
+
+fn non_empty(record: &csv::StringRecord, column: Option<usize>) -> Option<String> {
+    column.and_then(|column| record.get(column)).filter(|value| !value.is_empty()).map(String::from)
+}
+
+// Column positions of a wide csv, resolved once from the header row
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WideColumns {
+    table: usize,
+    field: usize,
+    record_id: Option<usize>,
+    record_sub_id: Option<usize>,
+    original: Option<usize>,
+    languages: Vec<(usize, String)>,
+    // Columns whose header is not a language, with their position
+    pub(crate) unknown: Vec<(usize, String)>,
+}
+
+impl WideColumns {
+    pub(crate) fn new(headers: &csv::StringRecord) -> Result<WideColumns, Box<dyn Error>> {
+        let table = find_column(headers, &TABLE_COLUMNS).ok_or("wide csv has no table column")?;
+        let field = find_column(headers, &FIELD_COLUMNS).ok_or("wide csv has no field column")?;
+        let record_id = find_column(headers, &RECORD_ID_COLUMNS);
+        let record_sub_id = find_column(headers, &RECORD_SUB_ID_COLUMNS);
+        let original = find_column(headers, &ORIGINAL_COLUMNS);
+
+        let known_columns = [Some(table), Some(field), record_id, record_sub_id, original];
+
+        let mut languages: Vec<(usize, String)> = vec![];
+        let mut unknown: Vec<(usize, String)> = vec![];
+
+        for (column, header) in headers.iter().enumerate().filter(|(column, _)| !known_columns.contains(&Some(*column))) {
+            match language_column(header) {
+                Some(language) => languages.push((column, language)),
+                None => unknown.push((column, header.to_string())),
+            }
+        }
+
+        Ok(WideColumns { table, field, record_id, record_sub_id, original, languages, unknown })
+    }
+
+    // The original value is only used as field_value when the row has no record_id, as the spec forbids both.
+    pub(crate) fn rows(&self, record: &csv::StringRecord) -> Vec<RawTranslation> {
+        let record_id = non_empty(record, self.record_id);
+        let record_sub_id = non_empty(record, self.record_sub_id);
+        let field_value = match record_id {
+            Some(_) => None,
+            None => non_empty(record, self.original),
+        };
+
+        self.languages.iter().filter_map(|(column, language)| {
+            non_empty(record, Some(*column)).map(|translation| RawTranslation {
+                table_name: record.get(self.table).unwrap_or_default().to_string(),
+                field_name: record.get(self.field).unwrap_or_default().to_string(),
+                language: language.clone(),
+                translation,
+                record_id: record_id.clone(),
+                record_sub_id: record_sub_id.clone(),
+                field_value: field_value.clone(),
+                extras: HashMap::new(),
+            })
+        }).collect()
+    }
+}
+
+// Converts a spreadsheet shaped like `table,field,record_id,original,fr,de,...` into translations.txt rows.
+pub fn wide_csv(data: &str) -> Result<Vec<RawTranslation>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_reader(data.as_bytes());
+    let columns = WideColumns::new(rdr.headers()?)?;
+
+    let mut rows: Vec<RawTranslation> = vec![];
+
+    for record in rdr.records() {
+        rows.extend(columns.rows(&record?));
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_row_per_filled_language() {
+        let data = "table,field,record_id,original,fr,de,notes,es\n\
+            stops,stop_name,stop_1,Central Station,Gare Centrale,Hauptbahnhof,checked,\n\
+            trips,trip_headsign,,Downtown,Centre-ville,,,Centro\n";
+
+        let rows = wide_csv(data).unwrap();
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[1], RawTranslation {
+            table_name: "stops".to_string(),
+            field_name: "stop_name".to_string(),
+            language: "de".to_string(),
+            translation: "Hauptbahnhof".to_string(),
+            record_id: Some("stop_1".to_string()),
+            record_sub_id: None,
+            field_value: None,
+            extras: HashMap::new(),
+        });
+        assert_eq!(rows[3].field_value.as_deref(), Some("Downtown"));
+        assert_eq!(rows[3].language, "es");
+    }
+}
@@ -0,0 +1,86 @@
+// Hooks for the tracing and metrics features, which compile to nothing without them. Operators get one event per
+// parse with the row counts and time taken, a debug event per reason rows were skipped for, and a trace event
+// for every lookup that found nothing. The metrics feature counts every lookup.
+#[cfg(feature = "parse")]
+use std::time::Duration;
+use language_tags::LanguageTag;
+use crate::{TranslatableField, TranslationKey};
+#[cfg(feature = "parse")]
+use crate::ParseStats;
+
+#[cfg(all(feature = "parse", feature = "tracing"))]
+pub(crate) type ParseSpan = tracing::span::EnteredSpan;
+// Held for the length of a parse so events inside it carry the parser name
+#[cfg(all(feature = "parse", not(feature = "tracing")))]
+pub(crate) struct ParseSpan;
+
+// Groups the messages of RowError, which name the offending value
+#[cfg(all(feature = "parse", feature = "tracing"))]
+fn skip_reason(message: &str) -> &'static str {
+    match message {
+        message if message.starts_with("invalid language tag") => "invalid_language",
+        message if message.starts_with("untranslatable field") => "untranslatable_field",
+        message if message.starts_with("row has neither") => "missing_key",
+        _ => "unreadable_row",
+    }
+}
+
+#[cfg(all(feature = "parse", feature = "tracing"))]
+pub(crate) fn parse_span(parser: &'static str) -> ParseSpan {
+    tracing::info_span!("parse_translations", parser).entered()
+}
+
+#[cfg(all(feature = "parse", not(feature = "tracing")))]
+pub(crate) fn parse_span(_parser: &'static str) -> ParseSpan {
+    ParseSpan
+}
+
+#[cfg(all(feature = "parse", feature = "tracing"))]
+pub(crate) fn parsed(stats: &ParseStats, translations: usize, elapsed: Duration) {
+    let mut reasons: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+
+    for error in stats.errors.iter() {
+        *reasons.entry(skip_reason(&error.message)).or_default() += 1;
+    }
+
+    tracing::info!(
+        rows_read = stats.rows_read,
+        rows_skipped = stats.rows_skipped,
+        translations,
+        elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+        dialect = ?stats.dialect,
+        "parsed translations"
+    );
+
+    for (reason, rows) in reasons {
+        tracing::debug!(reason, rows, "skipped rows");
+    }
+}
+
+#[cfg(all(feature = "parse", not(feature = "tracing")))]
+pub(crate) fn parsed(_stats: &ParseStats, _translations: usize, _elapsed: Duration) {}
+
+// key is the most specific key that was tried
+#[inline(always)]
+#[allow(unused_variables)]
+pub(crate) fn lookup(field: &TranslatableField, key: Option<&TranslationKey>, language: &LanguageTag, found: bool) {
+    #[cfg(feature = "tracing")]
+    if !found {
+        tracing::trace!(field = ?field, key = ?key, language = language.as_str(), "no translation");
+    }
+
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_lookup(field, key, language, found);
+}
+
+#[cfg(all(test, feature = "parse", feature = "tracing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_skip_reasons() {
+        assert_eq!(skip_reason("invalid language tag \"xx yy\""), "invalid_language");
+        assert_eq!(skip_reason("untranslatable field stops.stop_color"), "untranslatable_field");
+        assert_eq!(skip_reason("CSV error: record 2 (line: 3, byte: 80): found record with 3 fields"), "unreadable_row");
+    }
+}
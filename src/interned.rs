@@ -0,0 +1,263 @@
+use std::sync::Arc;
+use language_tags::LanguageTag;
+use crate::memory::{map_bytes, vec_bytes, TextPool, ARC_HEADER};
+use crate::{TranslatableField, TranslationKey, TranslationLookup, TranslationHasher, TranslationMap, TranslationResult, TRANSLATABLE_FIELDS};
+
+// Index of a string in an Interner
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// Stores each distinct string once. The map and the list share the allocation.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    strings: Vec<Arc<str>>,
+    symbols: TranslationMap<Arc<str>, Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    pub fn intern(&mut self, string: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(string) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(self.strings.len() as u32);
+        let string: Arc<str> = Arc::from(string);
+        self.strings.push(string.clone());
+        self.symbols.insert(string, symbol);
+        symbol
+    }
+
+    // The symbol of a string that was interned before, without adding it
+    pub fn get(&self, string: &str) -> Option<Symbol> {
+        self.symbols.get(string).copied()
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum InternedKey {
+    Record(Symbol),
+    RecordSub(Symbol, Symbol),
+    Value(Symbol),
+}
+
+// A translation key as handed out by InternedTranslations::iter. The map itself stores CompactKey.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct InternedLookup {
+    pub language: Symbol,
+    pub field: TranslatableField,
+    pub key: InternedKey,
+}
+
+// The key the map is hashed on: the position of the field in TRANSLATABLE_FIELDS, an index into the key table
+// and the language symbol. Twelve bytes that hash as three integers. Only InternedTranslations uses it; the
+// TranslationLookup keys of TranslationResult are unchanged, and memory_usage compares the two.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub(crate) struct CompactKey {
+    field: u32,
+    key: u32,
+    language: u32,
+}
+
+fn field_code(field: &TranslatableField) -> u32 {
+    TRANSLATABLE_FIELDS.iter().position(|candidate| candidate == field).unwrap_or_default() as u32
+}
+
+// A read-only copy of a TranslationResult where languages, record ids, original values and translations
+// are interned. Large feeds repeat the same few languages and record ids on every row, so this keeps one
+// copy of each instead of one per map key. TranslationResult and TranslationLookup themselves still own
+// their strings, as their fields are public; convert once building and editing is done.
+#[derive(Debug, Clone, Default)]
+pub struct InternedTranslations {
+    interner: Interner,
+    keys: Vec<InternedKey>,
+    key_codes: TranslationMap<InternedKey, u32>,
+    translations: TranslationMap<CompactKey, Symbol>,
+}
+
+impl InternedTranslations {
+    fn intern_key(&mut self, key: &TranslationKey) -> u32 {
+        let key = match key {
+            TranslationKey::Record(record_id) => InternedKey::Record(self.interner.intern(record_id)),
+            TranslationKey::RecordSub((record_id, record_sub_id)) => InternedKey::RecordSub(self.interner.intern(record_id), self.interner.intern(record_sub_id)),
+            TranslationKey::Value(field_value) => InternedKey::Value(self.interner.intern(field_value)),
+        };
+
+        *self.key_codes.entry(key).or_insert_with(|| {
+            self.keys.push(key);
+            self.keys.len() as u32 - 1
+        })
+    }
+
+    // None when a part of the key was never interned, in which case there is no translation for it either
+    fn find_key(&self, key: &TranslationKey) -> Option<u32> {
+        let key = match key {
+            TranslationKey::Record(record_id) => InternedKey::Record(self.interner.get(record_id)?),
+            TranslationKey::RecordSub((record_id, record_sub_id)) => InternedKey::RecordSub(self.interner.get(record_id)?, self.interner.get(record_sub_id)?),
+            TranslationKey::Value(field_value) => InternedKey::Value(self.interner.get(field_value)?),
+        };
+
+        self.key_codes.get(&key).copied()
+    }
+
+    pub fn get(&self, field: &TranslatableField, key: &TranslationKey, language: &LanguageTag) -> Option<&str> {
+        let compact = CompactKey {
+            field: field_code(field),
+            key: self.find_key(key)?,
+            language: self.interner.get(language.as_str())?.0,
+        };
+
+        self.translations.get(&compact).map(|translation| self.interner.resolve(*translation))
+    }
+
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (InternedLookup, &str)> + '_ {
+        self.translations.iter().map(|(compact, translation)| (InternedLookup {
+            language: Symbol(compact.language),
+            field: TRANSLATABLE_FIELDS[compact.field as usize].clone(),
+            key: self.keys[compact.key as usize],
+        }, self.interner.resolve(*translation)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.translations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.translations.is_empty()
+    }
+
+    pub fn to_translation_result(&self) -> TranslationResult {
+        let mut result = TranslationResult::default();
+        let mut texts = TextPool::default();
+
+        for (lookup, translation) in self.iter() {
+            let Ok(language) = LanguageTag::parse(self.interner.resolve(lookup.language)) else {
+                continue;
+            };
+
+            let key = match lookup.key {
+                InternedKey::Record(record_id) => TranslationKey::Record(self.interner.resolve(record_id).to_string()),
+                InternedKey::RecordSub(record_id, record_sub_id) => TranslationKey::RecordSub((self.interner.resolve(record_id).to_string(), self.interner.resolve(record_sub_id).to_string())),
+                InternedKey::Value(field_value) => TranslationKey::Value(self.interner.resolve(field_value).to_string()),
+            };
+
+            result.insert_lookup(TranslationLookup { language, field: lookup.field, key }, texts.share(translation));
+        }
+
+        result
+    }
+
+    // Estimated heap bytes, comparable with TranslationResult::memory_usage
+    pub fn memory_usage(&self) -> usize {
+        let strings = self.interner.strings.iter().map(|string| ARC_HEADER + string.len()).sum::<usize>();
+
+        strings
+            + vec_bytes(&self.interner.strings)
+            + map_bytes(&self.interner.symbols)
+            + vec_bytes(&self.keys)
+            + map_bytes(&self.key_codes)
+            + map_bytes(&self.translations)
+    }
+}
+
+impl From<&TranslationResult> for InternedTranslations {
+    fn from(result: &TranslationResult) -> Self {
+        let mut interned = InternedTranslations {
+            translations: TranslationMap::with_capacity_and_hasher(result.translations.len(), TranslationHasher::default()),
+            ..InternedTranslations::default()
+        };
+
+        for (lookup, translation) in result.translations.iter() {
+            let compact = CompactKey {
+                field: field_code(&lookup.field),
+                key: interned.intern_key(&lookup.key),
+                language: interned.interner.intern(lookup.language.as_str()).0,
+            };
+            let translation = interned.interner.intern(translation);
+
+            interned.translations.insert(compact, translation);
+        }
+
+        interned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StopFields;
+
+    fn result() -> TranslationResult {
+        let mut result = TranslationResult::default();
+
+        for language in ["fr", "de", "nl"] {
+            for i in 0..100 {
+                result.insert_lookup(TranslationLookup {
+                    language: LanguageTag::parse(language).unwrap(),
+                    field: field(),
+                    key: TranslationKey::Record(format!("stop_{}", i)),
+                }, format!("Station {}", i % 10).into());
+            }
+        }
+
+        result
+    }
+
+    fn field() -> TranslatableField {
+        TranslatableField::Stops(StopFields::Name)
+    }
+
+    #[test]
+    fn interns_repeated_strings() {
+        let interned = InternedTranslations::from(&result());
+
+        // 3 languages, 100 record ids and 10 distinct translations instead of 300 copies of each
+        assert_eq!(interned.interner().len(), 113);
+        assert_eq!(interned.len(), 300);
+    }
+
+    #[test]
+    fn looks_up_by_key_and_language() {
+        let interned = InternedTranslations::from(&result());
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        assert_eq!(interned.get(&field(), &TranslationKey::Record("stop_12".to_string()), &fr), Some("Station 2"));
+        assert_eq!(interned.get(&field(), &TranslationKey::Record("stop_x".to_string()), &fr), None);
+        assert_eq!(interned.get(&field(), &TranslationKey::Record("stop_12".to_string()), &LanguageTag::parse("ja").unwrap()), None);
+    }
+
+    #[test]
+    fn converts_back_in_less_memory() {
+        let result = result();
+        let interned = InternedTranslations::from(&result);
+
+        assert_eq!(interned.to_translation_result().translations, result.translations);
+        assert!(interned.memory_usage() * 2 < result.memory_usage());
+        assert!(std::mem::size_of::<CompactKey>() * 4 < std::mem::size_of::<TranslationLookup>());
+    }
+}
@@ -0,0 +1,194 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use language_tags::LanguageTag;
+use crate::{TranslatableField, TranslationKey, TranslationMap, TranslationResult};
+
+// How the translations of a field refer to what they translate
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyStrategy {
+    // Only record_id and record_sub_id, so lookups need no original value
+    Record,
+    // Only field_value, so lookups must pass the original value
+    Value,
+    Mixed,
+}
+
+impl KeyStrategy {
+    pub fn of(key: &TranslationKey) -> KeyStrategy {
+        match key {
+            TranslationKey::Record(_) | TranslationKey::RecordSub(_) => KeyStrategy::Record,
+            TranslationKey::Value(_) => KeyStrategy::Value,
+        }
+    }
+
+    pub fn combine(self, other: KeyStrategy) -> KeyStrategy {
+        match self == other {
+            true => self,
+            false => KeyStrategy::Mixed,
+        }
+    }
+}
+
+// How many translations of one field and language are keyed by record and how many by value. Counting lets
+// removals update the summaries without scanning the remaining translations. Entries never count zero of both.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KeyCounts {
+    records: usize,
+    values: usize,
+}
+
+impl KeyCounts {
+    fn count(&mut self, key: &TranslationKey) -> &mut usize {
+        match key {
+            TranslationKey::Record(_) | TranslationKey::RecordSub(_) => &mut self.records,
+            TranslationKey::Value(_) => &mut self.values,
+        }
+    }
+
+    fn strategy(self) -> Option<KeyStrategy> {
+        match (self.records > 0, self.values > 0) {
+            (true, true) => Some(KeyStrategy::Mixed),
+            (true, false) => Some(KeyStrategy::Record),
+            (false, true) => Some(KeyStrategy::Value),
+            (false, false) => None,
+        }
+    }
+
+    fn add(self, other: KeyCounts) -> KeyCounts {
+        KeyCounts { records: self.records + other.records, values: self.values + other.values }
+    }
+}
+
+// Per field, then per language, so a row only clones its field and language the first time the pair is seen
+pub(crate) type KeyCountMap = TranslationMap<TranslatableField, TranslationMap<LanguageTag, KeyCounts>>;
+
+// Counts one more translation
+pub(crate) fn count_key(counts: &mut KeyCountMap, field: &TranslatableField, language: &LanguageTag, key: &TranslationKey) {
+    let languages = match counts.get_mut(field) {
+        Some(languages) => languages,
+        None => counts.entry(field.clone()).or_default(),
+    };
+    let pair = match languages.get_mut(language) {
+        Some(pair) => pair,
+        None => languages.entry(language.clone()).or_default(),
+    };
+
+    *pair.count(key) += 1;
+}
+
+// Counts one translation less, dropping the pair when none are left. Returns whether it was the last of the pair.
+pub(crate) fn uncount_key(counts: &mut KeyCountMap, field: &TranslatableField, language: &LanguageTag, key: &TranslationKey) -> bool {
+    let Some(languages) = counts.get_mut(field) else {
+        return false;
+    };
+    let Some(pair) = languages.get_mut(language) else {
+        return false;
+    };
+
+    let count = pair.count(key);
+    *count = count.saturating_sub(1);
+    if pair.strategy().is_some() {
+        return false;
+    }
+
+    languages.remove(language);
+    if languages.is_empty() {
+        counts.remove(field);
+    }
+    true
+}
+
+// Adds the counts of an indexer that saw other rows
+#[cfg(feature = "rayon")]
+pub(crate) fn merge_key_counts(counts: &mut KeyCountMap, other: KeyCountMap) {
+    for (field, languages) in other {
+        let into = counts.entry(field).or_default();
+
+        for (language, pair) in languages {
+            let merged = into.get(&language).copied().unwrap_or_default().add(pair);
+            into.insert(language, merged);
+        }
+    }
+}
+
+impl TranslationResult {
+    // None for fields without translations. Kept up to date while building and editing.
+    pub fn key_strategy(&self, field: &TranslatableField) -> Option<KeyStrategy> {
+        self.key_counts.get(field)?.values().copied().fold(KeyCounts::default(), KeyCounts::add).strategy()
+    }
+
+    // The key_strategy of every field with translations
+    pub fn key_strategies(&self) -> TranslationMap<TranslatableField, KeyStrategy> {
+        self.key_counts.keys()
+            .filter_map(|field| Some((field.clone(), self.key_strategy(field)?)))
+            .collect()
+    }
+
+    pub(crate) fn recompute_key_counts(&mut self) {
+        let mut counts = KeyCountMap::default();
+
+        for lookup in self.translations.keys() {
+            count_key(&mut counts, &lookup.field, &lookup.language, &lookup.key);
+        }
+
+        self.key_counts = counts;
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{RouteFields, StopFields, TripFields};
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            trips,trip_headsign,fr,Centre,,,Downtown\n\
+            routes,route_long_name,fr,Bleue,route_1,,\n\
+            routes,route_long_name,de,Blau,,,Blue\n")
+    }
+
+    #[test]
+    fn summarizes_keys_per_field() {
+        let result = result();
+
+        assert_eq!(result.key_strategy(&TranslatableField::Stops(StopFields::Name)), Some(KeyStrategy::Record));
+        assert_eq!(result.key_strategy(&TranslatableField::Trips(TripFields::Headsign)), Some(KeyStrategy::Value));
+        assert_eq!(result.key_strategy(&TranslatableField::Routes(RouteFields::LongName)), Some(KeyStrategy::Mixed));
+        assert_eq!(result.key_strategy(&TranslatableField::Stops(StopFields::Desc)), None);
+    }
+
+    #[test]
+    fn follows_inserts_and_removals() {
+        let mut result = result();
+        let stop_name = TranslatableField::Stops(StopFields::Name);
+
+        result.insert(stop_name.clone(), TranslationKey::Value("Station".to_string()), "fr".parse().unwrap(), "Gare");
+        assert_eq!(result.key_strategy(&stop_name), Some(KeyStrategy::Mixed));
+
+        result.remove(&stop_name, &TranslationKey::Value("Station".to_string()), &"fr".parse().unwrap());
+        assert_eq!(result.key_strategy(&stop_name), Some(KeyStrategy::Record));
+        result.remove(&stop_name, &TranslationKey::Record("stop_1".to_string()), &"fr".parse().unwrap());
+        assert_eq!(result.key_strategy(&stop_name), None);
+    }
+
+    #[test]
+    fn follows_retain() {
+        let mut result = result();
+
+        result.retain(|lookup, _| lookup.language.as_str() == "fr");
+        assert_eq!(result.key_strategy(&TranslatableField::Routes(RouteFields::LongName)), Some(KeyStrategy::Record));
+    }
+
+    #[test]
+    fn counts_duplicate_rows_once() {
+        let mut result = translations("stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,fr,Gare SNCF,stop_1,,\n");
+        let name = TranslatableField::Stops(StopFields::Name);
+
+        result.remove(&name, &TranslationKey::Record("stop_1".to_string()), &"fr".parse().unwrap());
+        assert_eq!(result.key_strategy(&name), None);
+        assert!(result.key_strategies().is_empty());
+        assert!(result.possible_translations.is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+use language_tags::LanguageTag;
+use crate::resolution::fallback_chain;
+
+// Cleans up a language as agencies write it: surrounding whitespace, underscores as in fr_CA, and
+// deprecated codes such as iw, which canonicalize to their preferred value. Never panics on any input.
+pub fn normalize_language(input: &str) -> Option<LanguageTag> {
+    let input = input.trim().replace('_', "-");
+
+    if input.is_empty() {
+        return None;
+    }
+
+    let tag = LanguageTag::parse(&input).ok()?;
+    // tags that don't canonicalize in a unique way are still usable as written
+    Some(tag.canonicalize().unwrap_or(tag))
+}
+
+// Whether a tag falls within a range, so fr matches fr-CA. LanguageTag::matches panics when the range
+// carries extensions or private use subtags, here such a range matches nothing.
+pub fn language_matches(range: &LanguageTag, tag: &LanguageTag) -> bool {
+    range.is_language_range() && range.matches(tag)
+}
+
+// RFC 4647 lookup: the available language equal to the requested one, or else to the longest prefix of it, so
+// zh-Hant-TW falls back to zh-Hant and then zh. The same fallback TranslationResult::resolve and fallback_languages use.
+pub fn best_language<'a, I>(requested: &LanguageTag, available: I) -> Option<&'a LanguageTag>
+where
+    I: IntoIterator<Item = &'a LanguageTag>,
+{
+    let available = available.into_iter().collect::<Vec<&LanguageTag>>();
+
+    fallback_chain(requested.as_str()).find_map(|range| available.iter().copied().find(|language| language.as_str().eq_ignore_ascii_case(range)))
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_languages() {
+        assert_eq!(normalize_language(" fr_ca ").unwrap().as_str(), "fr-CA");
+        assert_eq!(normalize_language("iw").unwrap().as_str(), "he");
+        assert_eq!(normalize_language(""), None);
+        assert_eq!(normalize_language("not a language"), None);
+    }
+
+    #[test]
+    fn matches_ranges_without_panicking() {
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        assert!(language_matches(&fr, &LanguageTag::parse("fr-CA").unwrap()));
+        assert!(!language_matches(&LanguageTag::parse("fr-u-ca-gregory").unwrap(), &fr));
+    }
+
+    #[test]
+    fn looks_up_the_longest_available_prefix() {
+        let fr = LanguageTag::parse("fr").unwrap();
+        let available = [LanguageTag::parse("zh").unwrap(), LanguageTag::parse("zh-Hant").unwrap(), fr.clone()];
+
+        assert_eq!(best_language(&LanguageTag::parse("zh-Hant-TW").unwrap(), &available), Some(&available[1]));
+        assert_eq!(best_language(&LanguageTag::parse("ZH-hant").unwrap(), &available), Some(&available[1]));
+        assert_eq!(best_language(&LanguageTag::parse("fr-x-a-b").unwrap(), &available), Some(&fr));
+        assert_eq!(best_language(&LanguageTag::parse("de").unwrap(), &available), None);
+    }
+
+    #[test]
+    fn lists_sorted_canonical_languages() {
+        let result = crate::test_util::translations("stops,stop_name,iw,a,stop_1,,\n\
+            stops,stop_name,he,b,stop_2,,\n\
+            stops,stop_name,de,c,stop_1,,\n");
+
+        let written = result.languages_as_written().iter().map(|language| language.as_str()).collect::<Vec<&str>>();
+        let available = result.available_languages().iter().map(|language| language.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(written, vec!["de", "he", "iw"]);
+        assert_eq!(available, vec!["de", "he"]);
+    }
+}
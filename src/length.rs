@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize};
+use crate::language::language_matches;
+use crate::{enum_to_table_and_field, key_struct_to_options, Notice, Severity, TranslatableField, TranslationKey, TranslationLookup, TranslationResult};
+
+// The most characters a field can show, such as 60 for headsigns on on-board signs. A budget for a language range
+// takes over from the one without a language, for example for signs that fit fewer CJK characters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LengthBudget {
+    pub field: TranslatableField,
+    pub max_chars: usize,
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LengthOverrun {
+    pub field: TranslatableField,
+    pub key: TranslationKey,
+    pub translation: String,
+    pub chars: usize,
+    pub max_chars: usize,
+}
+
+// Translations over their budget, by language
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LengthReport {
+    pub languages: BTreeMap<String, Vec<LengthOverrun>>,
+}
+
+impl LengthReport {
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+    }
+
+    pub fn notices(&self) -> Vec<Notice> {
+        self.languages.iter()
+            .flat_map(|(language, overruns)| overruns.iter().map(move |overrun| {
+                let (table_name, field_name) = enum_to_table_and_field(&overrun.field);
+                Notice {
+                    field: Some(overrun.field.clone()),
+                    key: Some(overrun.key.clone()),
+                    language: Some(language.clone()),
+                    ..Notice::new("length_exceeded", Severity::Warning, format!("{}.{} {} translation \"{}\" is {} characters, over the budget of {}",
+                        table_name, field_name, language, overrun.translation, overrun.chars, overrun.max_chars))
+                }
+            }))
+            .collect()
+    }
+}
+
+// Characters as shown. Format characters such as bidi isolates and zero-width joiners take up no space.
+fn display_chars(text: &str) -> usize {
+    text.chars().filter(|character| !matches!(character, '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2069}' | '\u{FEFF}')).count()
+}
+
+// The budget for a translation: the one with the most specific matching language, else the one without a language
+fn budget_for(budgets: &[(Option<LanguageTag>, &LengthBudget)], lookup: &TranslationLookup) -> Option<usize> {
+    budgets.iter()
+        .filter(|(language, budget)| budget.field == lookup.field && language.as_ref().is_none_or(|language| language_matches(language, &lookup.language)))
+        .max_by_key(|(language, _)| language.as_ref().map_or(0, |language| language.as_str().len() + 1))
+        .map(|(_, budget)| budget.max_chars)
+}
+
+pub fn check_lengths(result: &TranslationResult, budgets: &[LengthBudget]) -> LengthReport {
+    // budgets with a language that does not parse are skipped, rather than applying to every language
+    let budgets = budgets.iter()
+        .filter_map(|budget| match &budget.language {
+            Some(language) => Some((Some(LanguageTag::parse(language).ok()?), budget)),
+            None => Some((None, budget)),
+        })
+        .collect::<Vec<_>>();
+    let mut report = LengthReport::default();
+
+    for (lookup, translation) in result.translations.iter() {
+        let Some(max_chars) = budget_for(&budgets, lookup) else {
+            continue;
+        };
+        let chars = display_chars(translation);
+
+        if chars > max_chars {
+            report.languages.entry(lookup.language.to_string()).or_default().push(LengthOverrun {
+                field: lookup.field.clone(),
+                key: lookup.key.clone(),
+                translation: translation.to_string(),
+                chars,
+                max_chars,
+            });
+        }
+    }
+
+    for overruns in report.languages.values_mut() {
+        overruns.sort_by_cached_key(|overrun| (enum_to_table_and_field(&overrun.field), key_struct_to_options(&overrun.key)));
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::TripFields;
+
+    fn headsign() -> TranslatableField {
+        TranslatableField::Trips(TripFields::Headsign)
+    }
+
+    fn report() -> LengthReport {
+        let result = translations("trips,trip_headsign,fr,Gare Centrale via Boulevard Saint-Michel,trip_1,,\n\
+            trips,trip_headsign,fr,Centre,trip_2,,\n\
+            trips,trip_headsign,ja,\u{4E2D}\u{592E}\u{99C5}\u{65B9}\u{9762},trip_1,,\n\
+            stops,stop_name,fr,Gare Centrale via Boulevard Saint-Michel,stop_1,,\n");
+        let budgets = vec![
+            LengthBudget { field: headsign(), max_chars: 20, language: None },
+            LengthBudget { field: headsign(), max_chars: 4, language: Some("ja".to_string()) },
+        ];
+
+        check_lengths(&result, &budgets)
+    }
+
+    #[test]
+    fn reports_translations_over_budget() {
+        let report = report();
+
+        assert_eq!(report.languages.keys().collect::<Vec<_>>(), vec!["fr", "ja"]);
+        assert_eq!(report.languages["fr"], vec![LengthOverrun {
+            field: headsign(),
+            key: TranslationKey::Record("trip_1".to_string()),
+            translation: "Gare Centrale via Boulevard Saint-Michel".to_string(),
+            chars: 40,
+            max_chars: 20,
+        }]);
+    }
+
+    #[test]
+    fn counts_characters_against_a_language_budget() {
+        // five characters, within the budget for every language but over the one for ja
+        assert_eq!(report().languages["ja"][0].chars, 5);
+        assert_eq!(report().languages["ja"][0].max_chars, 4);
+    }
+
+    #[test]
+    fn reports_overruns_as_notices() {
+        assert!(report().notices().iter().all(|notice| notice.code == "length_exceeded"));
+        assert_eq!(report().notices().len(), 2);
+    }
+}
@@ -1,272 +1,246 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
-use language_tags::LanguageTag;
-use serde::{Deserialize, Serialize};
-use derivative::Derivative;
-use std::error::Error;
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum RecordIdTypes {
-    RecordSubId((String, String)),
-    RecordId(String)
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum TranslatableField {
-    Agency(AgencyFields),
-    Areas(AreaFields),
-    Calendar(CalendarFields),
-    FareProducts(FareProductFields),
-    FeedInfo(FeedInfoFields),
-    Routes(RouteFields),
-    StopTimes(StopTimeFields),
-    Stops(StopFields),
-    Trips(TripFields),
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum TranslationKey {
-    Record(String),
-    RecordSub((String, String)),
-    Value(String),
-}
-
-#[derive(Debug, Hash, Eq, PartialEq, Clone)]
-pub struct TranslationLookup {
-    pub language: LanguageTag,
-    pub field: TranslatableField,
-    pub key: TranslationKey,
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum StopTimeFields {
-    Headsign,
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum RouteFields {
-    Desc,
-    LongName,
-    ShortName,
-    Url,
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum CalendarFields {
-    ServiceId,
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum FeedInfoFields {
-    PublisherName,
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum AreaFields {
-    Name,
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum AgencyFields {
-    Name,
-    FareUrl,
-    Url,
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum FareProductFields {
-    ProductName,
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum TripFields {
-    Headsign,
-    ShortName
-}
-
-#[derive(Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Clone)]
-pub enum StopFields {
-    Code,
-    Name,
-    TtsName,
-    PlatformCode,
-    Desc,
-}
-
-#[derive(Debug, Eq, PartialEq, Clone)]
-pub struct TranslationResult {
-    pub avaliable_languages: Vec<LanguageTag>,
-    pub translations: HashMap<TranslationLookup, String>,
-    pub possible_translations: Vec<(TranslatableField, LanguageTag)>,
-}
-
-pub fn table_and_field_to_enum(table_name: &str, field_name: &str) -> Option<TranslatableField> {
-    match table_name {
-        "agency" => {
-            match field_name {
-                "agency_name" => Some(TranslatableField::Agency(AgencyFields::Name)),
-                "agency_url" => Some(TranslatableField::Agency(AgencyFields::Url)),
-                "agency_fare_url" => Some(TranslatableField::Agency(AgencyFields::FareUrl)),
-                _ => None
-              }
-        },
-        "areas" => {
-            match field_name {
-                "area_name" => Some(TranslatableField::Areas(AreaFields::Name)),
-                _ => None
-              }
-        },
-        "routes" => {
-            match field_name {
-                "route_long_name" => Some(TranslatableField::Routes(RouteFields::LongName)),
-                "route_short_name" => Some(TranslatableField::Routes(RouteFields::ShortName)),
-                "route_url" => Some(TranslatableField::Routes(RouteFields::Url)),
-                _ => None
-              }
-        },
-        "stop_times" => {
-            match field_name {
-                "stop_headsign" => Some(TranslatableField::StopTimes(StopTimeFields::Headsign)),
-                _ => None
-              }
-        },
-        "stops" => {
-            match field_name {
-                "stop_code" => Some(TranslatableField::Stops(StopFields::Code)),
-                "stop_name" => Some(TranslatableField::Stops(StopFields::Name)),
-                "tts_stop_name" => Some(TranslatableField::Stops(StopFields::TtsName)),
-                "stop_desc" => Some(TranslatableField::Stops(StopFields::Desc)),
-                "platform_code" => Some(TranslatableField::Stops(StopFields::PlatformCode)),
-                _ => None
-            }
-        },
-        "trips" => {
-            match field_name {
-                "trip_headsign" => Some(TranslatableField::Trips(TripFields::Headsign)),
-                "trip_short_name" => Some(TranslatableField::Trips(TripFields::ShortName)),
-                _ => None
-            }
-        },
-        "calendar" => {
-            match field_name {
-                "service_id" => Some(TranslatableField::Calendar(CalendarFields::ServiceId)),
-                _ => None
-            }
-        },
-        "fare_products" => {
-            match field_name {
-                "fare_product_name" => Some(TranslatableField::FareProducts(FareProductFields::ProductName)),
-                _ => None
-            }
-        },
-        "feed_info" => {
-            match field_name {
-                "feed_publisher_name" => Some(TranslatableField::FeedInfo(FeedInfoFields::PublisherName)),
-            _ => None
-            }
-        }
-        _ => None
-    }
-}
-
-#[derive(Derivative)]
-#[derivative(Default(bound = ""))]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-pub struct RawTranslation {
-    pub table_name: String,
-    pub field_name: String,
-    pub language: String,
-    pub translation: String,
-    pub record_id: Option<String>,
-    pub record_sub_id: Option<String>,
-    pub field_value: Option<String>,
-}
-
-fn key_options_to_struct(record_id: Option<String>, record_sub_id: Option<String>, field_value: Option<String>) -> Option<TranslationKey> {
-    //https://gtfs.org/schedule/reference/#translationstxt
-    //If both referencing methods (record_id, record_sub_id) and field_value are used to translate the same value in 2 different rows, the translation provided with (record_id, record_sub_id) takes precedence.
-    match (record_id, record_sub_id, field_value) {
-        (Some(record_id), Some(record_sub_id), _) => Some(TranslationKey::RecordSub((record_id, record_sub_id))),
-        (Some(record_id), _, _) => Some(TranslationKey::Record(record_id)),
-        (_, _, Some(field_value)) => Some(TranslationKey::Value(field_value)),
-        _ => None
-    } 
-}
-pub fn translate_raw_translations(raw_translations: Vec<RawTranslation>) -> TranslationResult {
-    let mut res:HashMap<TranslationLookup, String> = HashMap::new();
-        let mut possible_translations:HashSet<(TranslatableField, LanguageTag)> = HashSet::new();
-
-        for row in raw_translations {
-            if let Ok(language_tag) = LanguageTag::parse(row.language.as_str()) {
-            if let Some(field) = table_and_field_to_enum(row.table_name.as_str(), row.field_name.as_str()) {
-                if let Some(key) = key_options_to_struct(row.record_id, row.record_sub_id, row.field_value) {
-                    res.insert(TranslationLookup {
-                        language: language_tag.clone(),
-                        field: field.clone(),
-                        key: key
-                    }, row.translation);
-                    possible_translations.insert((field, language_tag));
-                }
-            }
-
-            }
-        }
-
-        let possible_translations = possible_translations.into_iter().collect::<Vec<(TranslatableField, LanguageTag)>>();
-        let mut avaliable_languages: HashSet<LanguageTag> = HashSet::new();
-
-        for summary_item in possible_translations.iter() {
-           avaliable_languages.insert(summary_item.1.clone());
-        }
-
-        let avaliable_languages = avaliable_languages.into_iter().collect::<Vec<LanguageTag>>();
-
-        TranslationResult {
-            avaliable_languages: avaliable_languages,
-            possible_translations: possible_translations,
-            translations: res
-        }
-}
-
-pub fn translation_csv_text_to_translations(data: &str) -> Result<TranslationResult, Box<dyn std::error::Error>> {
-    let mut rdr = csv::Reader::from_reader(data.as_bytes());
-    let mut iter = rdr.into_deserialize::<RawTranslation>();
-
-    let mut pre_translations: Vec<RawTranslation> = vec![];
-
-    for row in iter {
-        if let Ok(row) = row {
-            pre_translations.push(row);
-        }
-    }
-
-    Ok(translate_raw_translations(pre_translations))
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tokio::*;
-    use std::path::Path;
-    use std::fs::File;
-    use std::io::prelude::*;
-
-    #[tokio::test]
-    async fn test() {
-        let feed = "https://odp-pref-tottori.tori-info.co.jp/bus_data/1.zip";
-
-        let response = reqwest::get(feed).await.unwrap();
-
-        println!("Download finished!");
-
-        let path = Path::new("./1.zip");
-
-        let mut file = match File::create(&path) {
-            Err(why) => panic!("couldn't create {}", why),
-            Ok(file) => file,
-        };
-
-        let content =  response.text().await.unwrap();
-        file.write_all(content.as_bytes()).unwrap();
-    }
-}
+// The translatable fields, translation keys, key precedence, language fallback and the embedded lookup only need
+// core and alloc, so controllers without an operating system can build the crate with default-features = false.
+// TranslationResult is keyed by LanguageTag and needs the std feature, and reading translations.txt needs parse.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod embedded;
+mod field;
+pub use field::*;
+mod resolution;
+
+#[cfg(feature = "std")]
+mod model;
+#[cfg(feature = "std")]
+pub use model::*;
+
+#[cfg(feature = "parse")]
+mod bidi;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "parse")]
+mod cleanup;
+#[cfg(feature = "parse")]
+mod coverage;
+#[cfg(feature = "parse")]
+mod decompress;
+#[cfg(feature = "parse")]
+mod delta;
+#[cfg(feature = "parse")]
+mod diff;
+#[cfg(feature = "std")]
+mod display;
+#[cfg(feature = "std")]
+mod edit;
+#[cfg(feature = "std")]
+mod filter;
+#[cfg(feature = "parse")]
+mod glossary;
+#[cfg(feature = "parse")]
+mod html;
+#[cfg(feature = "std")]
+mod instrument;
+#[cfg(feature = "std")]
+mod interned;
+#[cfg(feature = "std")]
+mod key_strategy;
+#[cfg(feature = "std")]
+mod language;
+#[cfg(feature = "parse")]
+mod length;
+#[cfg(feature = "parse")]
+mod limits;
+#[cfg(feature = "std")]
+mod matrix;
+#[cfg(feature = "std")]
+mod memory;
+#[cfg(feature = "std")]
+mod merge;
+#[cfg(feature = "std")]
+mod multi_feed;
+#[cfg(feature = "parse")]
+mod policy;
+#[cfg(feature = "std")]
+mod provenance;
+#[cfg(feature = "std")]
+mod pseudolocale;
+#[cfg(feature = "std")]
+mod realtime;
+#[cfg(feature = "std")]
+mod record_index;
+#[cfg(feature = "std")]
+mod row;
+#[cfg(feature = "parse")]
+mod tokens;
+#[cfg(feature = "std")]
+mod translator;
+#[cfg(feature = "parse")]
+mod script;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(feature = "parse")]
+mod summary;
+#[cfg(feature = "parse")]
+mod transcode;
+#[cfg(feature = "parse")]
+mod validate;
+#[cfg(feature = "std")]
+mod value_index;
+#[cfg(feature = "parse")]
+pub mod writer;
+#[cfg(feature = "parse")]
+pub mod export;
+#[cfg(feature = "parse")]
+pub mod import;
+#[cfg(feature = "parse")]
+pub mod parse;
+#[cfg(feature = "parse")]
+pub mod report;
+#[cfg(feature = "zip")]
+pub mod gtfs_zip;
+#[cfg(feature = "zip")]
+mod localize;
+#[cfg(feature = "tokio")]
+mod async_parse;
+#[cfg(any(all(test, feature = "parse"), feature = "test-util"))]
+pub mod test_util;
+#[cfg(feature = "apply")]
+pub mod apply;
+#[cfg(feature = "concurrent")]
+mod concurrent;
+#[cfg(feature = "db")]
+pub mod db;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fst")]
+mod frozen;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "mmap")]
+mod mapped;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "node")]
+pub mod node;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "schemars")]
+mod schema;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "sync")]
+pub mod sync;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "std")]
+pub mod universe;
+
+#[cfg(feature = "parse")]
+pub use bidi::{check_bidi, is_rtl, isolate_ltr_runs};
+#[cfg(feature = "std")]
+pub use builder::TranslationBuilder;
+#[cfg(feature = "std")]
+pub use cache::CachedTranslations;
+#[cfg(feature = "parse")]
+pub use cleanup::{clean_text, TextChange, TextCleanup};
+#[cfg(feature = "parse")]
+pub use coverage::{coverage_report, CoverageRow};
+#[cfg(feature = "parse")]
+pub use decompress::Compression;
+#[cfg(feature = "parse")]
+pub use delta::{DeltaReport, TranslationLookupSpec};
+#[cfg(feature = "parse")]
+pub use diff::{diff, DiffEntry, DiffGroup, TranslationDiff};
+#[cfg(feature = "std")]
+pub use display::StopNames;
+#[cfg(feature = "parse")]
+pub use glossary::{check_glossary, GlossaryEntry};
+#[cfg(feature = "parse")]
+pub use html::{apply_html_policy, HtmlPolicy};
+#[cfg(feature = "std")]
+pub use interned::{InternedKey, InternedLookup, InternedTranslations, Interner, Symbol};
+#[cfg(feature = "std")]
+pub use key_strategy::KeyStrategy;
+#[cfg(feature = "std")]
+pub use language::{best_language, language_matches, normalize_language};
+#[cfg(feature = "parse")]
+pub use length::{check_lengths, LengthBudget, LengthOverrun, LengthReport};
+#[cfg(feature = "parse")]
+pub use limits::LimitExceeded;
+#[cfg(feature = "std")]
+pub use matrix::{TranslationMatrix, TranslationMatrixRow};
+#[cfg(feature = "std")]
+pub use memory::DuplicateTextStats;
+#[cfg(feature = "std")]
+pub use merge::{ConflictPolicy, MergeConflict, MergeError};
+#[cfg(feature = "std")]
+pub use multi_feed::{LanguageCoverage, MultiFeedTranslations};
+#[cfg(feature = "parse")]
+pub use policy::{enforce_policy, PolicyReport, PolicyRequirement, PolicyViolation, TranslationPolicy};
+#[cfg(feature = "std")]
+pub use provenance::{Origin, Provenance, ProvenanceSource};
+#[cfg(feature = "std")]
+pub use pseudolocale::{generate_pseudolocale, pseudolocalize, PseudolocaleOptions};
+#[cfg(feature = "std")]
+pub use realtime::{translated_string_to_raw, TranslatedString, Translation};
+#[cfg(feature = "std")]
+pub use record_index::RecordIndex;
+#[cfg(feature = "std")]
+pub use row::{fallback_languages, translate_row, TranslateRow};
+#[cfg(feature = "parse")]
+pub use tokens::check_tokens;
+#[cfg(feature = "std")]
+pub use translator::{FillReport, Translator};
+#[cfg(feature = "parse")]
+pub use script::check_scripts;
+#[cfg(feature = "serde")]
+pub use serialization::SERIALIZATION_VERSION;
+#[cfg(feature = "std")]
+pub use shared::SharedTranslations;
+#[cfg(feature = "std")]
+pub use value_index::ValueIndex;
+#[cfg(feature = "serde")]
+pub use snapshot::SNAPSHOT_FORMAT_VERSION;
+#[cfg(feature = "parse")]
+pub use summary::{KeyTypeCounts, TranslationSummary};
+#[cfg(feature = "parse")]
+pub use transcode::AUTO_ENCODING;
+#[cfg(feature = "parse")]
+pub use validate::{validate, validate_with, Notice, NoticeConfig, Severity};
+#[cfg(feature = "parse")]
+pub use parse::{default_header_aliases, detect_dialect, from_reader, parse_translations, Dialect, ParseOptions, ParseStats, RawTranslationIter, RowError, UnknownColumns};
+#[cfg(feature = "tokio")]
+pub use async_parse::from_async_reader;
+#[cfg(feature = "apply")]
+pub use apply::{apply_gtfs, apply_raw_gtfs};
+#[cfg(feature = "fst")]
+pub use frozen::FrozenTranslations;
+#[cfg(feature = "metrics")]
+pub use metrics::{metrics_snapshot, reset_metrics, LookupCounts, MetricsSnapshot, MissedKey, OTHER_LANGUAGES};
+#[cfg(feature = "mmap")]
+pub use mapped::{MappedTranslations, INDEX_FORMAT_VERSION};
+#[cfg(feature = "http")]
+pub use http::{from_url, ConditionalRequest, FetchResult};
+#[cfg(feature = "rayon")]
+pub use parallel::from_reader_parallel;
+#[cfg(feature = "zip")]
+pub use gtfs_zip::{from_gtfs_archive, from_gtfs_zip, from_gtfs_zip_bytes, from_gtfs_zip_path, GtfsZipTranslations};
+#[cfg(feature = "zip")]
+pub use localize::localize_feed;
+#[cfg(feature = "concurrent")]
+pub use concurrent::ConcurrentTranslations;
+#[cfg(feature = "schemars")]
+pub use schema::json_schemas;
+#[cfg(feature = "watch")]
+pub use watch::{watch_translations, ReloadEvent, TranslationWatcher};
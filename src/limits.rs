@@ -0,0 +1,138 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+use crate::ParseOptions;
+
+// A cap from ParseOptions that the input went over. Parsing stops at the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitExceeded {
+    Rows { max_rows: usize },
+    FieldLength { max_field_len: usize, line: Option<u64> },
+    TotalBytes { max_total_bytes: u64 },
+    ArchiveBytes { max_archive_bytes: u64 },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::Rows { max_rows } => write!(f, "input has more than {} rows", max_rows),
+            LimitExceeded::FieldLength { max_field_len, line: Some(line) } => write!(f, "line {}: field longer than {} bytes", line, max_field_len),
+            LimitExceeded::FieldLength { max_field_len, line: None } => write!(f, "field longer than {} bytes", max_field_len),
+            LimitExceeded::TotalBytes { max_total_bytes } => write!(f, "input is larger than {} bytes", max_total_bytes),
+            LimitExceeded::ArchiveBytes { max_archive_bytes } => write!(f, "archive is larger than {} bytes", max_archive_bytes),
+        }
+    }
+}
+
+impl Error for LimitExceeded {}
+
+// Fails reads past max_total_bytes. It sits after decompression so a small archive cannot expand without bound.
+pub(crate) struct LimitedReader<R: Read> {
+    inner: R,
+    read: u64,
+    max_total_bytes: Option<u64>,
+}
+
+impl<R: Read> LimitedReader<R> {
+    pub(crate) fn new(inner: R, max_total_bytes: Option<u64>) -> LimitedReader<R> {
+        LimitedReader { inner, read: 0, max_total_bytes }
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(max_total_bytes) = self.max_total_bytes else {
+            return self.inner.read(buf);
+        };
+
+        // one byte past the cap is enough to tell an input of exactly max_total_bytes from a larger one
+        let allowed = (max_total_bytes + 1).saturating_sub(self.read).min(buf.len() as u64) as usize;
+        let n = self.inner.read(&mut buf[..allowed])?;
+        self.read += n as u64;
+
+        if self.read > max_total_bytes {
+            return Err(io::Error::other(LimitExceeded::TotalBytes { max_total_bytes }));
+        }
+
+        Ok(n)
+    }
+}
+
+// Checked before a record is converted. rows_read does not include the record yet.
+pub(crate) fn check_record(record: &csv::StringRecord, options: &ParseOptions, rows_read: usize) -> Result<(), LimitExceeded> {
+    if let Some(max_rows) = options.max_rows {
+        if rows_read >= max_rows {
+            return Err(LimitExceeded::Rows { max_rows });
+        }
+    }
+
+    if let Some(max_field_len) = options.max_field_len {
+        if record.iter().any(|field| field.len() > max_field_len) {
+            return Err(LimitExceeded::FieldLength { max_field_len, line: record.position().map(|position| position.line()) });
+        }
+    }
+
+    if let (Some(max_total_bytes), Some(position)) = (options.max_total_bytes, record.position()) {
+        if position.byte() + record.as_byte_record().as_slice().len() as u64 > max_total_bytes {
+            return Err(LimitExceeded::TotalBytes { max_total_bytes });
+        }
+    }
+
+    Ok(())
+}
+
+// Limit errors raised inside a reader come back wrapped in io and csv errors, this digs them out again
+pub(crate) fn surface_limit(err: Box<dyn Error>) -> Box<dyn Error> {
+    let limit = match err.downcast_ref::<csv::Error>() {
+        Some(err) => match err.kind() {
+            csv::ErrorKind::Io(err) => err.get_ref().and_then(|inner| inner.downcast_ref::<LimitExceeded>()),
+            _ => None,
+        },
+        None => err.downcast_ref::<io::Error>().and_then(|err| err.get_ref()).and_then(|inner| inner.downcast_ref::<LimitExceeded>()),
+    };
+
+    match limit {
+        Some(limit) => Box::new(limit.clone()),
+        None => err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_reader;
+    use crate::test_util::HEADER;
+
+    fn data() -> String {
+        format!("{}stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,fr,Gare du Nord,stop_2,,\n", HEADER)
+    }
+
+    fn limit(options: ParseOptions) -> LimitExceeded {
+        *from_reader(data().as_bytes(), &options).unwrap_err().downcast::<LimitExceeded>().unwrap()
+    }
+
+    #[test]
+    fn stops_at_the_row_limit() {
+        assert_eq!(limit(ParseOptions { max_rows: Some(1), ..ParseOptions::default() }), LimitExceeded::Rows { max_rows: 1 });
+    }
+
+    #[test]
+    fn stops_at_the_field_length_limit_with_its_line() {
+        assert_eq!(limit(ParseOptions { max_field_len: Some(10), ..ParseOptions::default() }), LimitExceeded::FieldLength { max_field_len: 10, line: Some(3) });
+    }
+
+    #[test]
+    fn stops_at_the_byte_limit() {
+        assert_eq!(limit(ParseOptions { max_total_bytes: Some(100), ..ParseOptions::default() }), LimitExceeded::TotalBytes { max_total_bytes: 100 });
+        // within the header
+        assert_eq!(limit(ParseOptions { max_total_bytes: Some(20), ..ParseOptions::default() }), LimitExceeded::TotalBytes { max_total_bytes: 20 });
+    }
+
+    #[test]
+    fn accepts_input_exactly_at_the_limits() {
+        let data = data();
+        let exact = ParseOptions { max_rows: Some(2), max_field_len: Some(12), max_total_bytes: Some(data.len() as u64), ..ParseOptions::default() };
+
+        assert_eq!(from_reader(data.as_bytes(), &exact).unwrap().0.translations.len(), 2);
+    }
+}
@@ -0,0 +1,214 @@
+use std::error::Error;
+use std::io::{Read, Seek, Write};
+use language_tags::LanguageTag;
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+use crate::gtfs_zip::find_entry;
+use crate::row::RowTranslator;
+use crate::{enum_to_table_and_field, from_reader, ParseOptions, TranslatableField, TranslationResult, TRANSLATABLE_FIELDS};
+
+// The columns that reference a row of a table in translations.txt, as record_id and record_sub_id
+pub(crate) fn record_columns(table_name: &str) -> (Option<&'static str>, Option<&'static str>) {
+    match table_name {
+        "agency" => (Some("agency_id"), None),
+        "areas" => (Some("area_id"), None),
+        "fare_products" => (Some("fare_product_id"), None),
+        "routes" => (Some("route_id"), None),
+        "stop_times" => (Some("trip_id"), Some("stop_sequence")),
+        "stops" => (Some("stop_id"), None),
+        "trips" => (Some("trip_id"), None),
+        _ => (None, None),
+    }
+}
+
+// The translatable columns of a table. calendar.service_id is an id other files refer to, so it is never replaced.
+pub(crate) fn translatable_columns(table_name: &str) -> Vec<(TranslatableField, &'static str)> {
+    TRANSLATABLE_FIELDS.iter()
+        .filter(|field| !matches!(field, TranslatableField::Calendar(_)))
+        .map(|field| (field.clone(), enum_to_table_and_field(field)))
+        .filter(|(_, (table, _))| *table == table_name)
+        .map(|(field, (_, column))| (field, column))
+        .collect()
+}
+
+// Rewrites one file of the feed, keeping every column and row as it was apart from the translated values
+fn localize_table<R: Read, W: Write>(reader: R, writer: W, table_name: &str, result: &TranslationResult, language: &LanguageTag) -> Result<usize, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(writer);
+
+    let headers = rdr.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|header| header.trim_start_matches('\u{feff}').trim() == name);
+
+    let (record_id, record_sub_id) = record_columns(table_name);
+    let (record_id, record_sub_id) = (record_id.and_then(column), record_sub_id.and_then(column));
+    let fields = translatable_columns(table_name).into_iter()
+        .filter_map(|(field, name)| column(name).map(|index| (field, index)))
+        .collect::<Vec<(TranslatableField, usize)>>();
+
+    wtr.write_record(&headers)?;
+
+    let mut translator = RowTranslator::new(result, language);
+    let mut record = csv::StringRecord::new();
+
+    while rdr.read_record(&mut record)? {
+        let read = |index: Option<usize>| index.and_then(|index| record.get(index)).filter(|value| !value.is_empty());
+        let mut row = record.iter().map(String::from).collect::<Vec<String>>();
+
+        for (field, index) in fields.iter() {
+            if let Some(value) = row.get_mut(*index) {
+                translator.replace(field, read(record_id), read(record_sub_id), value);
+            }
+        }
+
+        wtr.write_record(&row)?;
+    }
+
+    wtr.flush()?;
+
+    Ok(translator.replaced)
+}
+
+// Sets feed_info.feed_lang, as the feed is in the target language afterwards
+fn localize_feed_info<R: Read, W: Write>(reader: R, writer: W, result: &TranslationResult, language: &LanguageTag) -> Result<usize, Box<dyn Error>> {
+    let mut localized = vec![];
+    let replaced = localize_table(reader, &mut localized, "feed_info", result, language)?;
+
+    let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(localized.as_slice());
+    let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(writer);
+    let headers = rdr.headers()?.clone();
+    let feed_lang = headers.iter().position(|header| header.trim() == "feed_lang");
+
+    wtr.write_record(&headers)?;
+
+    for record in rdr.records() {
+        let mut row = record?.iter().map(String::from).collect::<Vec<String>>();
+
+        if let Some(value) = feed_lang.and_then(|index| row.get_mut(index)) {
+            *value = language.to_string();
+        }
+
+        wtr.write_record(&row)?;
+    }
+
+    wtr.flush()?;
+
+    Ok(replaced)
+}
+
+// Writes a copy of a GTFS zip in one language for consumers that don't read translations.txt. Translated values
+// replace the originals in every supported table, translations.txt is left out and the other files are copied as is.
+// Returns how many values were replaced.
+pub fn localize_feed<R: Read + Seek, W: Write + Seek>(zip_in: R, language: &LanguageTag, zip_out: W) -> Result<usize, Box<dyn Error>> {
+    let mut archive = ZipArchive::new(zip_in)?;
+
+    let translations_path = find_entry(&archive, "translations.txt");
+    let result = match &translations_path {
+        Some(path) => from_reader(archive.by_name(path)?, &ParseOptions::default())?.0,
+        None => TranslationResult::default(),
+    };
+    // the feed's own files are in the folder translations.txt is in, when it is nested
+    let base = translations_path.as_deref().and_then(|path| path.strip_suffix("translations.txt")).unwrap_or_default().to_string();
+
+    let mut writer = ZipWriter::new(zip_out);
+    let mut replaced = 0;
+
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index)?;
+        let name = entry.name().to_string();
+
+        if Some(&name) == translations_path.as_ref() {
+            continue;
+        }
+
+        let table_name = name.strip_prefix(base.as_str()).and_then(|file| file.strip_suffix(".txt")).filter(|table| !table.contains('/'));
+
+        match table_name {
+            Some("feed_info") => {
+                writer.start_file(name, FileOptions::default())?;
+                replaced += localize_feed_info(entry, &mut writer, &result, language)?;
+            }
+            Some(table_name) if !translatable_columns(table_name).is_empty() => {
+                let table_name = table_name.to_string();
+                writer.start_file(name, FileOptions::default())?;
+                replaced += localize_table(entry, &mut writer, &table_name, &result, language)?;
+            }
+            _ => writer.raw_copy_file(entry)?,
+        }
+    }
+
+    writer.finish()?;
+
+    Ok(replaced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::test_util::HEADER;
+
+    fn feed(files: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut input = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            input.start_file(*name, FileOptions::default()).unwrap();
+            input.write_all(contents.as_bytes()).unwrap();
+        }
+        input.finish().unwrap()
+    }
+
+    fn read(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> String {
+        let mut contents = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut contents).unwrap();
+        contents
+    }
+
+    #[test]
+    fn writes_monolingual_feed() {
+        let translations = format!("{}stops,stop_name,fr,Gare,stop_1,,\n\
+            stop_times,stop_headsign,fr,Centre,trip_1,1,\n\
+            feed_info,feed_publisher_name,fr,Transports,,,Transit\n", HEADER);
+        let input = feed(&[
+            ("stops.txt", "stop_id,stop_name,stop_lat,stop_lon\nstop_1,Station,1.0,2.0\nstop_2,Square,1.0,2.0\n"),
+            ("stop_times.txt", "trip_id,stop_id,stop_sequence,stop_headsign\ntrip_1,stop_1,1,Downtown\n"),
+            ("feed_info.txt", "feed_publisher_name,feed_publisher_url,feed_lang\nTransit,https://example.com,en\n"),
+            ("shapes.txt", "shape_id,shape_pt_lat,shape_pt_lon,shape_pt_sequence\ns,1.0,2.0,1\n"),
+            ("translations.txt", &translations),
+        ]);
+
+        let mut output = Cursor::new(Vec::new());
+        assert_eq!(localize_feed(input, &LanguageTag::parse("fr").unwrap(), &mut output).unwrap(), 3);
+
+        let mut archive = ZipArchive::new(output).unwrap();
+        assert_eq!(read(&mut archive, "stops.txt"), "stop_id,stop_name,stop_lat,stop_lon\nstop_1,Gare,1.0,2.0\nstop_2,Square,1.0,2.0\n");
+        assert_eq!(read(&mut archive, "stop_times.txt"), "trip_id,stop_id,stop_sequence,stop_headsign\ntrip_1,stop_1,1,Centre\n");
+        assert_eq!(read(&mut archive, "feed_info.txt"), "feed_publisher_name,feed_publisher_url,feed_lang\nTransports,https://example.com,fr\n");
+        assert_eq!(read(&mut archive, "shapes.txt"), "shape_id,shape_pt_lat,shape_pt_lon,shape_pt_sequence\ns,1.0,2.0,1\n");
+        assert!(archive.by_name("translations.txt").is_err());
+    }
+
+    #[test]
+    fn copies_a_feed_without_translations() {
+        let input = feed(&[("stops.txt", "stop_id,stop_name\nstop_1,Station\n")]);
+
+        let mut output = Cursor::new(Vec::new());
+        assert_eq!(localize_feed(input, &LanguageTag::parse("fr").unwrap(), &mut output).unwrap(), 0);
+        assert_eq!(read(&mut ZipArchive::new(output).unwrap(), "stops.txt"), "stop_id,stop_name\nstop_1,Station\n");
+    }
+
+    #[test]
+    fn rejects_input_that_is_not_a_zip() {
+        let input = Cursor::new(b"stop_id,stop_name\n".to_vec());
+
+        assert!(localize_feed(input, &LanguageTag::parse("fr").unwrap(), Cursor::new(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn falls_back_to_shorter_languages() {
+        let translations = format!("{}stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,fr-CA,Carré,,,Square\n", HEADER);
+        let input = feed(&[("stops.txt", "stop_id,stop_name\nstop_1,Station\nstop_2,Square\n"), ("translations.txt", &translations)]);
+
+        let mut output = Cursor::new(Vec::new());
+        assert_eq!(localize_feed(input, &LanguageTag::parse("fr-CA").unwrap(), &mut output).unwrap(), 2);
+        assert_eq!(read(&mut ZipArchive::new(output).unwrap(), "stops.txt"), "stop_id,stop_name\nstop_1,Gare\nstop_2,Carré\n");
+    }
+}
@@ -0,0 +1,199 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use fst::Map;
+use language_tags::LanguageTag;
+use memmap2::Mmap;
+use crate::frozen::encode_key;
+use crate::{resolution_keys, FrozenTranslations, TranslatableField, TranslationKey};
+
+const INDEX_MAGIC: &[u8; 8] = b"GTFSTIDX";
+
+// Byte 8 of every index file, after the magic. Readers refuse files written with a different layout.
+pub const INDEX_FORMAT_VERSION: u8 = 1;
+
+// magic, version, 7 reserved bytes, then the lengths of the fst, text and span sections as little endian u64
+const HEADER_LEN: usize = 40;
+const SPAN_LEN: usize = 8;
+
+// One section of the mapped file, handed to the fst crate as its backing bytes
+#[derive(Clone)]
+struct Section {
+    mmap: Arc<Mmap>,
+    range: Range<usize>,
+}
+
+impl AsRef<[u8]> for Section {
+    fn as_ref(&self) -> &[u8] {
+        &self.mmap[self.range.clone()]
+    }
+}
+
+// A FrozenTranslations read straight from a memory-mapped index file. Pages are loaded by the OS as lookups
+// touch them, so sets larger than RAM can be queried, and processes mapping the same file share its pages.
+pub struct MappedTranslations {
+    mmap: Arc<Mmap>,
+    index: Map<Section>,
+    text: Range<usize>,
+    spans: Range<usize>,
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().expect("8 bytes"))
+}
+
+// The range of a section of len bytes starting at start, or None when a corrupt length would run past the end
+fn section(start: usize, len: u64) -> Option<Range<usize>> {
+    Some(start..start.checked_add(usize::try_from(len).ok()?)?)
+}
+
+impl FrozenTranslations {
+    pub fn write_index<W: Write>(&self, mut writer: W) -> Result<(), Box<dyn Error>> {
+        let fst = self.index.as_fst().as_bytes();
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(INDEX_MAGIC);
+        header.push(INDEX_FORMAT_VERSION);
+        header.extend_from_slice(&[0; 7]);
+        header.extend_from_slice(&(fst.len() as u64).to_le_bytes());
+        header.extend_from_slice(&(self.text.len() as u64).to_le_bytes());
+        header.extend_from_slice(&((self.spans.len() * SPAN_LEN) as u64).to_le_bytes());
+
+        writer.write_all(&header)?;
+        writer.write_all(fst)?;
+        writer.write_all(self.text.as_bytes())?;
+
+        for (start, end) in self.spans.iter() {
+            writer.write_all(&start.to_le_bytes())?;
+            writer.write_all(&end.to_le_bytes())?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn write_index_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        self.write_index(std::io::BufWriter::new(File::create(path)?))
+    }
+}
+
+impl MappedTranslations {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<MappedTranslations, Box<dyn Error>> {
+        let file = File::open(path)?;
+        // Safety: the index is only read through the map. Files are written once and replaced rather than
+        // edited in place; truncating a mapped file from another process is undefined behaviour.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        MappedTranslations::from_mmap(Arc::new(mmap))
+    }
+
+    fn from_mmap(mmap: Arc<Mmap>) -> Result<MappedTranslations, Box<dyn Error>> {
+        if mmap.len() < HEADER_LEN || &mmap[..8] != INDEX_MAGIC {
+            return Err("not a translations index file".into());
+        }
+
+        if mmap[8] != INDEX_FORMAT_VERSION {
+            return Err(format!("unsupported index format version {}, expected {}", mmap[8], INDEX_FORMAT_VERSION).into());
+        }
+
+        let truncated = || "truncated translations index file";
+        let fst = section(HEADER_LEN, read_u64(&mmap, 16)).ok_or_else(truncated)?;
+        let text = section(fst.end, read_u64(&mmap, 24)).ok_or_else(truncated)?;
+        let spans = section(text.end, read_u64(&mmap, 32)).ok_or_else(truncated)?;
+
+        if spans.end != mmap.len() || spans.len() % SPAN_LEN != 0 {
+            return Err(truncated().into());
+        }
+
+        // the text is checked for UTF-8 span by span as lookups read it, so opening does not page in the whole file
+
+        let index = Map::new(Section { mmap: mmap.clone(), range: fst })?;
+
+        Ok(MappedTranslations { mmap, index, text, spans })
+    }
+
+    fn translation(&self, index: u64) -> Option<&str> {
+        let at = usize::try_from(index).ok()?.checked_mul(SPAN_LEN)?.checked_add(self.spans.start)?;
+        let span = self.mmap.get(at..at.checked_add(SPAN_LEN)?)?;
+        let start = u32::from_le_bytes(span[..4].try_into().ok()?) as usize;
+        let end = u32::from_le_bytes(span[4..].try_into().ok()?) as usize;
+
+        std::str::from_utf8(self.mmap[self.text.clone()].get(start..end)?).ok()
+    }
+
+    pub fn get(&self, field: &TranslatableField, key: &TranslationKey, language: &LanguageTag) -> Option<&str> {
+        self.index.get(encode_key(field, key, language.as_str())).and_then(|index| self.translation(index))
+    }
+
+    // Same precedence as TranslationResult::resolve
+    pub fn resolve(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>, language: &LanguageTag) -> Option<&str> {
+        resolution_keys(record_id, record_sub_id, original_value).iter().find_map(|key| self.get(field, key, language))
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::StopFields;
+
+    fn frozen() -> FrozenTranslations {
+        translations("stops,stop_name,fr,Gare Centrale,stop_1,,\n\
+            stops,stop_name,fr,Gare Centrale,stop_2,,\n\
+            trips,trip_headsign,es,Centro,,,Downtown\n").freeze().unwrap()
+    }
+
+    // one file per test and case, so no test rewrites a file that is still mapped
+    fn index_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("gtfs-translations-{}-{}.idx", std::process::id(), name))
+    }
+
+    #[test]
+    fn writes_and_maps_index() {
+        let path = index_path("valid");
+        frozen().write_index_file(&path).unwrap();
+
+        let mapped = MappedTranslations::open(&path).unwrap();
+        let fr = LanguageTag::parse("fr").unwrap();
+        assert_eq!(mapped.len(), 3);
+        assert_eq!(mapped.get(&TranslatableField::Stops(StopFields::Name), &TranslationKey::Record("stop_2".to_string()), &fr), Some("Gare Centrale"));
+        assert_eq!(mapped.resolve(&TranslatableField::Trips(crate::TripFields::Headsign), None, None, Some("Downtown"), &LanguageTag::parse("es").unwrap()), Some("Centro"));
+
+        drop(mapped);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_corrupt_index_files() {
+        let valid = index_path("source");
+        frozen().write_index_file(&valid).unwrap();
+        let bytes = std::fs::read(&valid).unwrap();
+        std::fs::remove_file(&valid).unwrap();
+
+        let mut unknown_magic = bytes.clone();
+        unknown_magic[8] = 0;
+        let path = index_path("version");
+        std::fs::write(&path, &unknown_magic).unwrap();
+        assert!(MappedTranslations::open(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        // a text length that would wrap the section offsets around
+        let mut wrapping = bytes.clone();
+        wrapping[24..32].copy_from_slice(&u64::MAX.to_le_bytes());
+        let path = index_path("wrapping");
+        std::fs::write(&path, &wrapping).unwrap();
+        assert_eq!(MappedTranslations::open(&path).err().unwrap().to_string(), "truncated translations index file");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use language_tags::LanguageTag;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::{enum_to_table_and_field, TranslatableField, TranslationResult};
+
+// possible_translations as a table, with one row per field and one column per language
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TranslationMatrix {
+    // Sorted, in the order of the columns of every row
+    pub languages: Vec<String>,
+    // Sorted by table and field
+    pub rows: Vec<TranslationMatrixRow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TranslationMatrixRow {
+    pub table_name: String,
+    pub field_name: String,
+    // Whether the field has translations in the language of the same column
+    pub translated: Vec<bool>,
+}
+
+impl TranslationMatrix {
+    pub fn is_translated(&self, table_name: &str, field_name: &str, language: &str) -> bool {
+        let Some(column) = self.languages.iter().position(|other| other == language) else {
+            return false;
+        };
+
+        self.rows.iter()
+            .find(|row| row.table_name == table_name && row.field_name == field_name)
+            .is_some_and(|row| row.translated[column])
+    }
+}
+
+impl TranslationResult {
+    // Exact matches only, so fr does not include fr-CA. In the order of possible_translations.
+    pub fn fields_translated_in(&self, language: &LanguageTag) -> Vec<&TranslatableField> {
+        self.possible_translations.iter()
+            .filter(|(_, other)| other == language)
+            .map(|(field, _)| field)
+            .collect()
+    }
+
+    pub fn languages_covering(&self, field: &TranslatableField) -> Vec<&LanguageTag> {
+        self.possible_translations.iter()
+            .filter(|(other, _)| other == field)
+            .map(|(_, language)| language)
+            .collect()
+    }
+
+    pub fn translation_matrix(&self) -> TranslationMatrix {
+        let mut languages = self.possible_translations.iter().map(|(_, language)| language.to_string()).collect::<Vec<String>>();
+        languages.sort();
+        languages.dedup();
+
+        let mut rows = BTreeMap::new();
+
+        for (field, language) in self.possible_translations.iter() {
+            let translated = rows.entry(enum_to_table_and_field(field)).or_insert_with(|| vec![false; languages.len()]);
+
+            if let Ok(column) = languages.binary_search(&language.to_string()) {
+                translated[column] = true;
+            }
+        }
+
+        TranslationMatrix {
+            languages,
+            rows: rows.into_iter()
+                .map(|((table_name, field_name), translated)| TranslationMatrixRow {
+                    table_name: table_name.to_string(),
+                    field_name: field_name.to_string(),
+                    translated,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{RouteFields, StopFields};
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n\
+            routes,route_long_name,fr,Bleue,route_1,,\n")
+    }
+
+    #[test]
+    fn lists_fields_translated_in_a_language() {
+        let result = result();
+        let mut fields = result.fields_translated_in(&LanguageTag::parse("fr").unwrap());
+        fields.sort_by_key(|field| enum_to_table_and_field(field));
+
+        assert_eq!(fields, vec![&TranslatableField::Routes(RouteFields::LongName), &TranslatableField::Stops(StopFields::Name)]);
+    }
+
+    #[test]
+    fn lists_languages_covering_a_field() {
+        let result = result();
+        let mut languages = result.languages_covering(&TranslatableField::Stops(StopFields::Name)).into_iter().map(|language| language.as_str()).collect::<Vec<&str>>();
+        languages.sort();
+
+        assert_eq!(languages, vec!["de", "fr"]);
+    }
+
+    #[test]
+    fn builds_a_sorted_matrix() {
+        let matrix = result().translation_matrix();
+
+        assert_eq!(matrix.languages, vec!["de", "fr"]);
+        assert_eq!(matrix.rows[0].table_name, "routes");
+        assert_eq!(matrix.rows[0].translated, vec![false, true]);
+        assert!(matrix.is_translated("stops", "stop_name", "de"));
+        assert!(!matrix.is_translated("routes", "route_long_name", "de"));
+        assert!(!matrix.is_translated("routes", "route_long_name", "nl"));
+    }
+}
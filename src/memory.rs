@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::sync::Arc;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use crate::{InternedTranslations, TranslationHasher, TranslationKey, TranslationResult};
+
+// Reference counts of an Arc allocation
+pub(crate) const ARC_HEADER: usize = 2 * size_of::<usize>();
+
+// hashbrown keeps one control byte per bucket next to the buckets themselves
+pub(crate) fn map_bytes<K, V, S>(map: &HashMap<K, V, S>) -> usize {
+    map.capacity() * (size_of::<(K, V)>() + 1)
+}
+
+pub(crate) fn vec_bytes<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * size_of::<T>()
+}
+
+// Hands out one Arc per distinct text, so translations that read the same share the allocation
+#[derive(Debug, Default)]
+pub(crate) struct TextPool(HashSet<Arc<str>, TranslationHasher>);
+
+impl TextPool {
+    pub(crate) fn share(&mut self, text: &str) -> Arc<str> {
+        if let Some(shared) = self.0.get(text) {
+            return shared.clone();
+        }
+
+        let shared = Arc::<str>::from(text);
+        self.0.insert(shared.clone());
+        shared
+    }
+}
+
+// How much of the translated text is repeated and how much of it is stored once. Many records share strings such
+// as "Centre-ville". InternedTranslations and FrozenTranslations always store each distinct string once;
+// TranslationResult does for what it parsed or merged, and text inserted afterwards is shared by
+// share_duplicate_text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DuplicateTextStats {
+    pub values: usize,
+    pub distinct_values: usize,
+    pub value_bytes: usize,
+    pub distinct_value_bytes: usize,
+    // Text bytes actually held, which is distinct_value_bytes once every duplicate is shared
+    pub stored_bytes: usize,
+}
+
+impl DuplicateTextStats {
+    pub(crate) fn from_values<'a, I: IntoIterator<Item = &'a str>>(values: I) -> DuplicateTextStats {
+        let mut stats = DuplicateTextStats::default();
+        let mut seen: HashSet<&str> = HashSet::new();
+
+        for value in values {
+            stats.values += 1;
+            stats.value_bytes += value.len();
+
+            if seen.insert(value) {
+                stats.distinct_values += 1;
+                stats.distinct_value_bytes += value.len();
+            }
+        }
+
+        stats.stored_bytes = stats.distinct_value_bytes;
+        stats
+    }
+
+    // Bytes of text that sharing saves over a copy per value
+    pub fn saved_bytes(&self) -> usize {
+        self.value_bytes - self.stored_bytes
+    }
+
+    // Bytes that sharing the duplicates not shared yet would save on top
+    pub fn unshared_bytes(&self) -> usize {
+        self.stored_bytes - self.distinct_value_bytes
+    }
+}
+
+fn key_bytes(key: &TranslationKey) -> usize {
+    match key {
+        TranslationKey::Record(record_id) => record_id.capacity(),
+        TranslationKey::RecordSub((record_id, record_sub_id)) => record_id.capacity() + record_sub_id.capacity(),
+        TranslationKey::Value(field_value) => field_value.capacity(),
+    }
+}
+
+impl TranslationResult {
+    // Estimated heap bytes held by the translations and summaries. Allocator overhead is not counted,
+    // so this is a lower bound that is mostly useful for comparing representations.
+    #[allow(deprecated)]
+    pub fn memory_usage(&self) -> usize {
+        let entries = self.translations.keys()
+            .map(|lookup| lookup.language.as_str().len() + key_bytes(&lookup.key))
+            .sum::<usize>();
+        // a shared text is counted with the first key holding it
+        let text = shared_texts(self).map(|translation| ARC_HEADER + translation.len()).sum::<usize>();
+
+        let summaries = vec_bytes(&self.avaliable_languages)
+            + vec_bytes(&self.possible_translations)
+            + self.avaliable_languages.iter().map(|language| language.as_str().len()).sum::<usize>()
+            + self.possible_translations.iter().map(|(_, language)| language.as_str().len()).sum::<usize>();
+
+        map_bytes(&self.translations) + entries + text + summaries
+    }
+}
+
+// Each allocation behind the translations once
+fn shared_texts(result: &TranslationResult) -> impl Iterator<Item = &Arc<str>> {
+    let mut seen = HashSet::new();
+    result.translations.values().filter(move |translation| seen.insert(Arc::as_ptr(translation)))
+}
+
+impl TranslationResult {
+    pub fn duplicate_text_stats(&self) -> DuplicateTextStats {
+        DuplicateTextStats {
+            stored_bytes: shared_texts(self).map(|translation| translation.len()).sum(),
+            ..DuplicateTextStats::from_values(self.translations.values().map(AsRef::as_ref))
+        }
+    }
+
+    // Makes translations that read the same share one Arc, for text inserted or rewritten since parsing.
+    // Returns the bytes this freed.
+    pub fn share_duplicate_text(&mut self) -> usize {
+        let before = self.duplicate_text_stats().stored_bytes;
+        let mut texts = TextPool::default();
+
+        for translation in self.translations.values_mut() {
+            *translation = texts.share(translation);
+        }
+
+        before - self.duplicate_text_stats().stored_bytes
+    }
+}
+
+impl InternedTranslations {
+    // Values are interned, so distinct_value_bytes is what this copy actually holds
+    pub fn duplicate_text_stats(&self) -> DuplicateTextStats {
+        DuplicateTextStats::from_values(self.iter().map(|(_, translation)| translation))
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use language_tags::LanguageTag;
+    use crate::test_util::translations;
+    use crate::{StopFields, TranslatableField};
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare Centrale,stop_1,,\n\
+            stops,stop_name,fr,Gare Centrale,stop_2,,\n\
+            trips,trip_headsign,fr,Centre-ville,trip_1,,\n\
+            trips,trip_headsign,fr,Centre-ville,trip_2,,\n\
+            trips,trip_headsign,fr,Centre-ville,trip_3,,\n")
+    }
+
+    #[test]
+    fn parsing_stores_each_value_once() {
+        let stats = result().duplicate_text_stats();
+
+        assert_eq!(stats, DuplicateTextStats { values: 5, distinct_values: 2, value_bytes: 62, distinct_value_bytes: 25, stored_bytes: 25 });
+        assert_eq!(stats.saved_bytes(), 37);
+        assert_eq!(stats.unshared_bytes(), 0);
+    }
+
+    #[test]
+    fn shares_text_inserted_after_parsing() {
+        let mut result = result();
+        let field = TranslatableField::Stops(StopFields::Name);
+        let fr = LanguageTag::parse("fr").unwrap();
+        result.insert(field.clone(), TranslationKey::Record("stop_3".to_string()), fr.clone(), "Gare Centrale");
+
+        let before = result.memory_usage();
+        assert_eq!(result.duplicate_text_stats().unshared_bytes(), 13);
+        assert_eq!(result.share_duplicate_text(), 13);
+
+        assert_eq!(result.duplicate_text_stats().unshared_bytes(), 0);
+        assert_eq!(result.memory_usage(), before - 13 - ARC_HEADER);
+        assert_eq!(result.get(&field, &TranslationKey::Record("stop_3".to_string()), &fr), Some("Gare Centrale"));
+        assert_eq!(result.share_duplicate_text(), 0);
+    }
+
+    #[test]
+    fn interned_translations_count_the_same() {
+        let result = result();
+
+        assert_eq!(InternedTranslations::from(&result).duplicate_text_stats(), result.duplicate_text_stats());
+    }
+
+    #[test]
+    fn saves_nothing_without_duplicates() {
+        assert_eq!(translations("stops,stop_name,fr,Gare,stop_1,,\n").duplicate_text_stats().saved_bytes(), 0);
+    }
+}
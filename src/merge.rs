@@ -0,0 +1,128 @@
+use std::error::Error;
+use std::fmt;
+use language_tags::LanguageTag;
+use crate::{TranslationLookup, TranslationResult};
+
+// Which translation survives when both sets translate the same field, key and language differently
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictPolicy {
+    PreferSelf,
+    PreferOther,
+    // Nothing is merged if any translation conflicts
+    Error,
+    // The other set wins for these languages and this one for the rest, e.g. an override file that only corrects French
+    PreferOtherFor(Vec<LanguageTag>),
+}
+
+impl ConflictPolicy {
+    pub(crate) fn prefers_other(&self, language: &LanguageTag) -> bool {
+        match self {
+            ConflictPolicy::PreferSelf | ConflictPolicy::Error => false,
+            ConflictPolicy::PreferOther => true,
+            ConflictPolicy::PreferOtherFor(languages) => languages.contains(language),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub lookup: TranslationLookup,
+    pub kept: String,
+    pub discarded: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeError {
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} translations differ between the merged sets", self.conflicts.len())
+    }
+}
+
+impl Error for MergeError {}
+
+impl TranslationResult {
+    // Layers other on top of this set. Translations only one side has are always kept, and the conflicts
+    // that the policy settled come back sorted so they can be reported.
+    pub fn merge(&mut self, mut other: TranslationResult, policy: &ConflictPolicy) -> Result<Vec<MergeConflict>, MergeError> {
+        let mut conflicts = other.translations.iter()
+            .filter_map(|(lookup, theirs)| {
+                let ours = self.translations.get(lookup).filter(|ours| *ours != theirs)?;
+                let (kept, discarded) = if policy.prefers_other(&lookup.language) { (theirs, ours) } else { (ours, theirs) };
+
+                Some(MergeConflict { lookup: lookup.clone(), kept: kept.to_string(), discarded: discarded.to_string() })
+            })
+            .collect::<Vec<MergeConflict>>();
+
+        conflicts.sort_by_key(|conflict| format!("{:?}", conflict.lookup));
+
+        if *policy == ConflictPolicy::Error && !conflicts.is_empty() {
+            return Err(MergeError { conflicts });
+        }
+
+        for (lookup, translation) in other.translations {
+            if !self.translations.contains_key(&lookup) || policy.prefers_other(&lookup.language) {
+                // the translation keeps the provenance it had on its own side
+                match other.provenance.remove(&lookup) {
+                    Some(provenance) => self.provenance.insert(lookup.clone(), provenance),
+                    None => self.provenance.remove(&lookup),
+                };
+                self.insert_lookup(lookup, translation);
+            }
+        }
+
+        Ok(conflicts)
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{StopFields, TranslatableField};
+
+    fn agency() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare Centrale,stop_1,,\nstops,stop_name,de,Hauptbanhof,stop_1,,\n")
+    }
+
+    fn overrides() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare centrale,stop_1,,\nstops,stop_name,de,Hauptbahnhof,stop_1,,\nstops,stop_name,nl,Centraal,stop_1,,\n")
+    }
+
+    fn resolve(result: &TranslationResult, language: &str) -> Option<String> {
+        result.resolve(&TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, None, &LanguageTag::parse(language).unwrap()).map(str::to_string)
+    }
+
+    #[test]
+    fn refuses_conflicts_without_changing_anything() {
+        let agency = agency();
+        let mut strict = agency.clone();
+
+        assert_eq!(strict.merge(overrides(), &ConflictPolicy::Error).unwrap_err().conflicts.len(), 2);
+        assert_eq!(strict, agency);
+    }
+
+    #[test]
+    fn prefers_the_other_side_for_chosen_languages() {
+        let de = LanguageTag::parse("de").unwrap();
+        let mut merged = agency();
+        let conflicts = merged.merge(overrides(), &ConflictPolicy::PreferOtherFor(vec![de.clone()])).unwrap();
+
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts.iter().find(|conflict| conflict.lookup.language == de).unwrap().discarded, "Hauptbanhof");
+        assert_eq!(resolve(&merged, "de").as_deref(), Some("Hauptbahnhof"));
+        assert_eq!(resolve(&merged, "fr").as_deref(), Some("Gare Centrale"));
+    }
+
+    #[test]
+    fn adds_translations_without_a_conflict() {
+        let mut merged = agency();
+        merged.merge(overrides(), &ConflictPolicy::PreferOtherFor(vec![])).unwrap();
+
+        assert_eq!(resolve(&merged, "nl").as_deref(), Some("Centraal"));
+        assert!(merged.languages_as_written().contains(&LanguageTag::parse("nl").unwrap()));
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use language_tags::LanguageTag;
+use serde::Serialize;
+use crate::{TranslatableField, TranslationKey};
+
+// Keys beyond this many distinct misses are still counted per language but not tracked on their own,
+// so a scraper walking every record id cannot grow the table without bound
+const MAX_TRACKED_MISSES: usize = 10_000;
+const TOP_MISSED: usize = 20;
+// Languages past this many are counted together under OTHER_LANGUAGES, as requests can ask for any tag
+const MAX_TRACKED_LANGUAGES: usize = 256;
+pub const OTHER_LANGUAGES: &str = "other";
+// Misses are spread over this many tables so lookups on different threads rarely wait on each other
+const MISS_SHARDS: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LookupCounts {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissedKey {
+    pub field: TranslatableField,
+    pub key: TranslationKey,
+    pub language: String,
+    pub misses: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MetricsSnapshot {
+    // Counted per requested language, so a fallback from fr-CA to fr is a miss for fr-CA and a hit for fr.
+    // Languages after the first MAX_TRACKED_LANGUAGES are added up under OTHER_LANGUAGES.
+    pub languages: BTreeMap<String, LookupCounts>,
+    // The most missed keys, most missed first
+    pub top_missed: Vec<MissedKey>,
+}
+
+#[derive(Default)]
+struct AtomicCounts {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl AtomicCounts {
+    fn record(&self, found: bool) {
+        match found {
+            true => self.hits.fetch_add(1, Ordering::Relaxed),
+            false => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    fn load(&self) -> LookupCounts {
+        LookupCounts {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn clear(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+type MissTable = HashMap<(TranslatableField, TranslationKey, String), u64>;
+
+// Known languages only take the read lock, the write lock is for the first lookup of a language
+struct Registry {
+    languages: RwLock<HashMap<String, AtomicCounts>>,
+    other: AtomicCounts,
+    hasher: RandomState,
+    missed: [Mutex<MissTable>; MISS_SHARDS],
+}
+
+impl Registry {
+    fn new() -> Registry {
+        Registry {
+            languages: RwLock::default(),
+            other: AtomicCounts::default(),
+            hasher: RandomState::new(),
+            missed: std::array::from_fn(|_| Mutex::default()),
+        }
+    }
+
+    fn count_language(&self, language: &LanguageTag, found: bool) {
+        {
+            let languages = self.languages.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+            if let Some(counts) = languages.get(language.as_str()) {
+                counts.record(found);
+                return;
+            }
+        }
+
+        let mut languages = self.languages.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if !languages.contains_key(language.as_str()) && languages.len() >= MAX_TRACKED_LANGUAGES {
+            self.other.record(found);
+            return;
+        }
+        languages.entry(language.to_string()).or_default().record(found);
+    }
+
+    fn record(&self, field: &TranslatableField, key: Option<&TranslationKey>, language: &LanguageTag, found: bool) {
+        self.count_language(language, found);
+
+        if found {
+            return;
+        }
+        let Some(key) = key else {
+            return;
+        };
+
+        let shard = self.hasher.hash_one((field, key, language.as_str())) as usize % MISS_SHARDS;
+        let mut missed = self.missed[shard].lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let is_full = missed.len() >= MAX_TRACKED_MISSES / MISS_SHARDS;
+
+        match missed.get_mut(&(field.clone(), key.clone(), language.to_string())) {
+            Some(misses) => *misses += 1,
+            None if !is_full => {
+                missed.insert((field.clone(), key.clone(), language.to_string()), 1);
+            }
+            None => {}
+        }
+    }
+
+    fn snapshot(&self) -> MetricsSnapshot {
+        let mut top_missed = vec![];
+        for shard in self.missed.iter() {
+            let missed = shard.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            top_missed.extend(missed.iter().map(|((field, key, language), misses)| MissedKey { field: field.clone(), key: key.clone(), language: language.clone(), misses: *misses }));
+        }
+        top_missed.sort_by(|a, b| b.misses.cmp(&a.misses).then_with(|| format!("{:?}", (&a.field, &a.key, &a.language)).cmp(&format!("{:?}", (&b.field, &b.key, &b.language)))));
+        top_missed.truncate(TOP_MISSED);
+
+        let mut languages = self.languages.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .map(|(language, counts)| (language.clone(), counts.load()))
+            .collect::<BTreeMap<String, LookupCounts>>();
+
+        let other = self.other.load();
+        if other != LookupCounts::default() {
+            languages.insert(OTHER_LANGUAGES.to_string(), other);
+        }
+
+        MetricsSnapshot { languages, top_missed }
+    }
+
+    fn reset(&self) {
+        self.languages.write().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+        self.other.clear();
+        for shard in self.missed.iter() {
+            shard.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+        }
+    }
+}
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::new)
+}
+
+pub(crate) fn record_lookup(field: &TranslatableField, key: Option<&TranslationKey>, language: &LanguageTag, found: bool) {
+    registry().record(field, key, language, found);
+}
+
+// Lookups recorded by TranslationResult::resolve since the start of the process or the last reset
+pub fn metrics_snapshot() -> MetricsSnapshot {
+    registry().snapshot()
+}
+
+pub fn reset_metrics() {
+    registry().reset();
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::StopFields;
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let result = translations("stops,stop_name,fr,Gare,stop_1,,\n");
+        let field = TranslatableField::Stops(StopFields::Name);
+        // a language no other test looks up, as the registry is shared by the whole test binary
+        let language = LanguageTag::parse("x-metrics").unwrap();
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        result.resolve(&field, Some("stop_1"), None, None, &fr);
+        for _ in 0..3 {
+            result.resolve(&field, Some("stop_9"), None, None, &language);
+        }
+
+        let snapshot = metrics_snapshot();
+        assert_eq!(snapshot.languages["x-metrics"], LookupCounts { hits: 0, misses: 3 });
+        assert!(snapshot.languages["fr"].hits >= 1);
+        assert!(snapshot.top_missed.contains(&MissedKey {
+            field,
+            key: TranslationKey::Record("stop_9".to_string()),
+            language: "x-metrics".to_string(),
+            misses: 3,
+        }));
+    }
+
+    #[test]
+    fn buckets_languages_past_the_cap() {
+        let registry = Registry::new();
+        let field = TranslatableField::Stops(StopFields::Name);
+
+        for i in 0..MAX_TRACKED_LANGUAGES + 2 {
+            registry.record(&field, None, &LanguageTag::parse(&format!("x-l{}", i)).unwrap(), false);
+        }
+        registry.record(&field, None, &LanguageTag::parse("x-l0").unwrap(), true);
+
+        let snapshot = registry.snapshot();
+        assert_eq!(snapshot.languages.len(), MAX_TRACKED_LANGUAGES + 1);
+        assert_eq!(snapshot.languages["x-l0"], LookupCounts { hits: 1, misses: 1 });
+        assert_eq!(snapshot.languages[OTHER_LANGUAGES], LookupCounts { hits: 0, misses: 2 });
+    }
+
+    #[test]
+    fn resets_to_empty() {
+        let registry = Registry::new();
+        registry.record(&TranslatableField::Stops(StopFields::Name), None, &LanguageTag::parse("fr").unwrap(), true);
+
+        registry.reset();
+        assert_eq!(registry.snapshot(), MetricsSnapshot::default());
+    }
+}
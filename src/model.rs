@@ -0,0 +1,448 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use language_tags::LanguageTag;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::key_strategy::{count_key, KeyCountMap};
+use crate::memory::TextPool;
+#[cfg(feature = "rayon")]
+use crate::key_strategy::{merge_key_counts, uncount_key};
+use crate::resolution::{resolution_order, resolve_with_fallback};
+use crate::{instrument, normalize_language, table_and_field_to_enum, AgencyFields, Provenance, ProvenanceSource, RouteFields, TranslatableField, TranslationKey};
+#[cfg(any(feature = "wasm", feature = "node"))]
+use crate::{enum_to_table_and_field, TRANSLATABLE_FIELDS};
+#[cfg(feature = "parse")]
+use crate::{from_reader, Dialect, ParseOptions, ParseStats};
+
+// Hasher of the translation maps. The fast-hash feature swaps SipHash for aHash, which is much quicker on
+// these short keys. Build maps with default() or with_capacity_and_hasher so code compiles either way.
+#[cfg(feature = "fast-hash")]
+pub type TranslationHasher = ahash::RandomState;
+#[cfg(not(feature = "fast-hash"))]
+pub type TranslationHasher = std::collections::hash_map::RandomState;
+
+pub type TranslationMap<K, V> = HashMap<K, V, TranslationHasher>;
+
+#[derive(Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RecordIdTypes {
+    RecordSubId((String, String)),
+    RecordId(String)
+}
+
+#[derive(Debug, Hash, Eq, PartialEq, Clone)]
+pub struct TranslationLookup {
+    pub language: LanguageTag,
+    pub field: TranslatableField,
+    pub key: TranslationKey,
+}
+
+// By field, then key, then language tag. LanguageTag has no order of its own, so its text is compared.
+impl Ord for TranslationLookup {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.field, &self.key, self.language.as_str()).cmp(&(&other.field, &other.key, other.language.as_str()))
+    }
+}
+
+impl PartialOrd for TranslationLookup {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "crate::serialization::SerializedTranslationResult"))]
+pub struct TranslationResult {
+    // Misspelled, kept so code using it still compiles. Sorted by tag.
+    #[deprecated(note = "use available_languages() or languages_as_written()")]
+    pub avaliable_languages: Vec<LanguageTag>,
+    // Translations that read the same share one Arc where they came in through parsing, merging or
+    // share_duplicate_text, so a feed full of "Centre-ville" keeps the text once
+    pub translations: TranslationMap<TranslationLookup, Arc<str>>,
+    pub possible_translations: Vec<(TranslatableField, LanguageTag)>,
+    // Metadata for the translations it is known for, such as the ones filled in by machine translation
+    pub(crate) provenance: TranslationMap<TranslationLookup, Provenance>,
+    // Record and value keys of each field and language, behind key_strategy and the pruning of the summaries
+    pub(crate) key_counts: KeyCountMap,
+}
+
+impl TranslationResult {
+    // Inserts a translation and registers its field and language in the summaries
+    #[allow(deprecated)]
+    pub(crate) fn insert_lookup(&mut self, lookup: TranslationLookup, translation: Arc<str>) -> Option<Arc<str>> {
+        let entry = match self.translations.entry(lookup) {
+            Entry::Occupied(mut entry) => return Some(entry.insert(translation)),
+            Entry::Vacant(entry) => entry,
+        };
+        let lookup = entry.key();
+
+        if let Err(index) = self.avaliable_languages.binary_search_by(|language| language.as_str().cmp(lookup.language.as_str())) {
+            self.avaliable_languages.insert(index, lookup.language.clone());
+        }
+
+        if !self.key_counts.get(&lookup.field).is_some_and(|languages| languages.contains_key(&lookup.language)) {
+            self.possible_translations.push((lookup.field.clone(), lookup.language.clone()));
+        }
+
+        count_key(&mut self.key_counts, &lookup.field, &lookup.language, &lookup.key);
+
+        entry.insert(translation);
+        None
+    }
+
+    // Every language with translations, canonicalized so iw and he are listed once as he, and sorted by tag.
+    // Translations stay under the tag the feed wrote, so look them up with languages_as_written.
+    pub fn available_languages(&self) -> Vec<LanguageTag> {
+        let mut languages = self.languages_as_written().iter()
+            .map(|language| normalize_language(language.as_str()).unwrap_or_else(|| language.clone()))
+            .collect::<Vec<LanguageTag>>();
+
+        languages.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        languages.dedup();
+        languages
+    }
+
+    // The languages of the translations as they are keyed, sorted by tag
+    #[allow(deprecated)]
+    pub fn languages_as_written(&self) -> &[LanguageTag] {
+        &self.avaliable_languages
+    }
+
+    pub fn get(&self, field: &TranslatableField, key: &TranslationKey, language: &LanguageTag) -> Option<&str> {
+        self.translations.get(&TranslationLookup {
+            language: language.clone(),
+            field: field.clone(),
+            key: key.clone(),
+        }).map(AsRef::as_ref)
+    }
+
+    // Tries (record_id, record_sub_id), then record_id, then the original field value, which is the precedence the spec gives
+    pub fn resolve(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>, language: &LanguageTag) -> Option<&str> {
+        let keys = resolution_keys(record_id, record_sub_id, original_value);
+        let found = keys.iter().find_map(|key| self.get(field, key, language));
+
+        instrument::lookup(field, keys.first(), language, found.is_some());
+        found
+    }
+
+    // resolve for the language and then each shorter tag it falls back to, so fr-CA uses fr where there is no
+    // Canadian French translation. EmbeddedTranslations::resolve_with_fallback answers the same way.
+    pub fn resolve_with_fallback(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>, language: &LanguageTag) -> Option<&str> {
+        let found = resolve_with_fallback(language.as_str(), record_id, record_sub_id, original_value, |range, key| {
+            let language = self.languages_as_written().iter().find(|available| available.as_str().eq_ignore_ascii_case(range))?;
+            self.get(field, &TranslationKey::from(key), language)
+        });
+
+        let key = resolution_order(record_id, record_sub_id, original_value).next().map(TranslationKey::from);
+        instrument::lookup(field, key.as_ref(), language, found.is_some());
+        found
+    }
+}
+
+// Fields holding links, which machine and pseudo translation leave alone
+pub(crate) fn is_url(field: &TranslatableField) -> bool {
+    matches!(field, TranslatableField::Agency(AgencyFields::Url) | TranslatableField::Agency(AgencyFields::FareUrl) | TranslatableField::Routes(RouteFields::Url))
+}
+
+// Accepts "stops.stop_name" or just "stop_name", which is unique across tables
+#[cfg(any(feature = "wasm", feature = "node"))]
+pub(crate) fn field_from_name(name: &str) -> Option<TranslatableField> {
+    TRANSLATABLE_FIELDS.iter().find(|field| {
+        let (table_name, field_name) = enum_to_table_and_field(field);
+        name == field_name || name.split_once('.') == Some((table_name, field_name))
+    }).cloned()
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RawTranslation {
+    pub table_name: String,
+    pub field_name: String,
+    pub language: String,
+    pub translation: String,
+    pub record_id: Option<String>,
+    pub record_sub_id: Option<String>,
+    pub field_value: Option<String>,
+    // Columns outside the spec, filled when parsing with UnknownColumns::Preserve
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub extras: HashMap<String, String>,
+}
+
+// Borrowed form of RawTranslation. The streaming parser deserializes into this straight from the csv record,
+// so only the parts of a row that end up in the index get their own allocation.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct RawTranslationRef<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub table_name: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub field_name: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub language: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub translation: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub record_id: Option<Cow<'a, str>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub record_sub_id: Option<Cow<'a, str>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub field_value: Option<Cow<'a, str>>,
+}
+
+impl RawTranslationRef<'_> {
+    pub fn into_owned(self) -> RawTranslation {
+        RawTranslation {
+            table_name: self.table_name.into_owned(),
+            field_name: self.field_name.into_owned(),
+            language: self.language.into_owned(),
+            translation: self.translation.into_owned(),
+            record_id: self.record_id.map(Cow::into_owned),
+            record_sub_id: self.record_sub_id.map(Cow::into_owned),
+            field_value: self.field_value.map(Cow::into_owned),
+            extras: HashMap::new(),
+        }
+    }
+}
+
+impl From<RawTranslation> for RawTranslationRef<'static> {
+    fn from(row: RawTranslation) -> Self {
+        RawTranslationRef {
+            table_name: Cow::Owned(row.table_name),
+            field_name: Cow::Owned(row.field_name),
+            language: Cow::Owned(row.language),
+            translation: Cow::Owned(row.translation),
+            record_id: row.record_id.map(Cow::Owned),
+            record_sub_id: row.record_sub_id.map(Cow::Owned),
+            field_value: row.field_value.map(Cow::Owned),
+        }
+    }
+}
+
+// The keys resolve tries, most specific first
+pub(crate) fn resolution_keys(record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>) -> Vec<TranslationKey> {
+    resolution_order(record_id, record_sub_id, original_value).map(TranslationKey::from).collect()
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn key_options_to_struct(record_id: Option<String>, record_sub_id: Option<String>, field_value: Option<String>) -> Option<TranslationKey> {
+    //https://gtfs.org/schedule/reference/#translationstxt
+    //If both referencing methods (record_id, record_sub_id) and field_value are used to translate the same value in 2 different rows, the translation provided with (record_id, record_sub_id) takes precedence.
+    match (record_id, record_sub_id, field_value) {
+        (Some(record_id), Some(record_sub_id), _) => Some(TranslationKey::RecordSub((record_id, record_sub_id))),
+        (Some(record_id), _, _) => Some(TranslationKey::Record(record_id)),
+        (_, _, Some(field_value)) => Some(TranslationKey::Value(field_value)),
+        _ => None
+    } 
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn key_struct_to_options(key: &TranslationKey) -> (Option<String>, Option<String>, Option<String>) {
+    match key {
+        TranslationKey::Record(record_id) => (Some(record_id.clone()), None, None),
+        TranslationKey::RecordSub((record_id, record_sub_id)) => (Some(record_id.clone()), Some(record_sub_id.clone()), None),
+        TranslationKey::Value(field_value) => (None, None, Some(field_value.clone())),
+    }
+}
+
+pub fn translate_raw_translations(raw_translations: Vec<RawTranslation>) -> TranslationResult {
+    index_raw_translations(raw_translations).0.finish()
+}
+
+#[cfg(feature = "parse")]
+pub(crate) fn translate_raw_translations_with_stats<I: IntoIterator<Item = RawTranslation>>(raw_translations: I, stats: &mut ParseStats) -> TranslationResult {
+    let (indexer, skipped) = index_raw_translations(raw_translations);
+
+    stats.rows_skipped += skipped;
+    stats.duplicate_rows = indexer.duplicates;
+    indexer.finish()
+}
+
+// The indexed rows and how many of them were dropped
+fn index_raw_translations<I: IntoIterator<Item = RawTranslation>>(raw_translations: I) -> (RawTranslationIndexer, usize) {
+    let raw_translations = raw_translations.into_iter();
+    let mut indexer = RawTranslationIndexer::with_capacity(raw_translations.size_hint().0);
+    let mut skipped = 0;
+
+    for row in raw_translations {
+        if indexer.push(row).is_err() {
+            skipped += 1;
+        }
+    }
+
+    (indexer, skipped)
+}
+
+// Builds a TranslationResult one row at a time, for parsers that cannot hand over an iterator.
+// The summaries are counted as rows come in, so finish does not scan the translations again.
+#[derive(Debug, Default)]
+pub(crate) struct RawTranslationIndexer {
+    res: TranslationMap<TranslationLookup, Arc<str>>,
+    // one Arc per distinct text, so equal rows share it
+    texts: TextPool,
+    provenance: TranslationMap<TranslationLookup, Provenance>,
+    // the field and language pairs and the languages of the summaries are the keys of the counts
+    key_counts: KeyCountMap,
+    // Set to record the provenance of every row, with the line of the row being pushed
+    source: Option<ProvenanceSource>,
+    pub(crate) row: Option<u64>,
+    // Rows that replaced an earlier row with the same field, key and language
+    pub(crate) duplicates: usize,
+}
+
+impl RawTranslationIndexer {
+    pub(crate) fn with_capacity(rows: usize) -> RawTranslationIndexer {
+        RawTranslationIndexer {
+            res: TranslationMap::with_capacity_and_hasher(rows, TranslationHasher::default()),
+            ..RawTranslationIndexer::default()
+        }
+    }
+
+    #[cfg(feature = "parse")]
+    pub(crate) fn with_provenance(mut self, source: Option<ProvenanceSource>) -> RawTranslationIndexer {
+        self.source = source;
+        self
+    }
+
+    // Returns why the row was dropped
+    pub(crate) fn push(&mut self, row: RawTranslation) -> Result<(), String> {
+        self.push_ref(row.into())
+    }
+
+    pub(crate) fn push_ref(&mut self, row: RawTranslationRef<'_>) -> Result<(), String> {
+        let language_tag = LanguageTag::parse(&row.language)
+            .map_err(|_| format!("invalid language tag {:?}", row.language))?;
+        let field = table_and_field_to_enum(&row.table_name, &row.field_name)
+            .ok_or_else(|| format!("untranslatable field {}.{}", row.table_name, row.field_name))?;
+        // same precedence as key_options_to_struct, copying only the parts the key keeps
+        let key = match (row.record_id, row.record_sub_id, row.field_value) {
+            (Some(record_id), Some(record_sub_id), _) => TranslationKey::RecordSub((record_id.into_owned(), record_sub_id.into_owned())),
+            (Some(record_id), _, _) => TranslationKey::Record(record_id.into_owned()),
+            (_, _, Some(field_value)) => TranslationKey::Value(field_value.into_owned()),
+            _ => return Err("row has neither record_id nor field_value".to_string()),
+        };
+
+        let lookup = TranslationLookup {
+            language: language_tag,
+            field,
+            key
+        };
+
+        if let Some(source) = &self.source {
+            self.provenance.insert(lookup.clone(), source.at_row(self.row));
+        }
+
+        match self.res.entry(lookup) {
+            Entry::Occupied(mut entry) => {
+                entry.insert(self.texts.share(&row.translation));
+                self.duplicates += 1;
+            }
+            Entry::Vacant(entry) => {
+                let lookup = entry.key();
+                count_key(&mut self.key_counts, &lookup.field, &lookup.language, &lookup.key);
+                entry.insert(self.texts.share(&row.translation));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Takes over the rows of an indexer that saw later rows of the same file, so its translations win
+    #[cfg(feature = "rayon")]
+    pub(crate) fn merge(&mut self, mut later: RawTranslationIndexer) {
+        self.duplicates += later.duplicates;
+
+        // rows both saw are counted once
+        for lookup in later.res.keys().filter(|lookup| self.res.contains_key(*lookup)) {
+            uncount_key(&mut later.key_counts, &lookup.field, &lookup.language, &lookup.key);
+            self.duplicates += 1;
+        }
+
+        // each indexer shared text within its own rows
+        self.res.extend(later.res.into_iter().map(|(lookup, translation)| (lookup, self.texts.share(&translation))));
+        self.provenance.extend(later.provenance);
+        merge_key_counts(&mut self.key_counts, later.key_counts);
+    }
+
+    pub(crate) fn finish(self) -> TranslationResult {
+        let possible_translations = self.key_counts.iter()
+            .flat_map(|(field, languages)| languages.keys().map(move |language| (field.clone(), language.clone())))
+            .collect::<Vec<(TranslatableField, LanguageTag)>>();
+
+        let mut languages = self.key_counts.values().flat_map(|languages| languages.keys()).cloned().collect::<HashSet<LanguageTag>>().into_iter().collect::<Vec<LanguageTag>>();
+        languages.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        #[allow(deprecated)]
+        TranslationResult {
+            avaliable_languages: languages,
+            possible_translations,
+            translations: self.res,
+            provenance: self.provenance,
+            key_counts: self.key_counts,
+        }
+    }
+}
+
+#[cfg(feature = "parse")]
+pub fn translation_csv_text_to_translations(data: &str) -> Result<TranslationResult, Box<dyn std::error::Error>> {
+    let options = ParseOptions {
+        dialect: Some(Dialect::Standard),
+        ..ParseOptions::default()
+    };
+
+    from_reader(data.as_bytes(), &options).map(|(result, _)| result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::{enum_to_table_and_field, StopFields, StopTimeFields};
+    use std::fs::File;
+    use std::io::prelude::*;
+
+    #[tokio::test]
+    async fn test() {
+        let feed = "https://odp-pref-tottori.tori-info.co.jp/bus_data/1.zip";
+
+        let response = reqwest::get(feed).await.unwrap();
+
+        println!("Download finished!");
+
+        let path = Path::new("./1.zip");
+
+        let mut file = match File::create(path) {
+            Err(why) => panic!("couldn't create {}", why),
+            Ok(file) => file,
+        };
+
+        let content =  response.text().await.unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn sorts_lookups_by_names() {
+        let lookup = |field: TranslatableField, key: &str, language: &str| TranslationLookup {
+            language: LanguageTag::parse(language).unwrap(),
+            field,
+            key: TranslationKey::Record(key.to_string()),
+        };
+
+        let mut lookups = [
+            lookup(TranslatableField::Stops(StopFields::Name), "stop_1", "fr"),
+            lookup(TranslatableField::StopTimes(StopTimeFields::Headsign), "trip_1", "fr"),
+            lookup(TranslatableField::Stops(StopFields::Desc), "stop_1", "fr"),
+            lookup(TranslatableField::Stops(StopFields::Name), "stop_1", "de"),
+        ];
+        lookups.sort();
+
+        let order = lookups.iter().map(|lookup| (enum_to_table_and_field(&lookup.field).1, lookup.language.as_str())).collect::<Vec<_>>();
+        assert_eq!(order, vec![("stop_headsign", "fr"), ("stop_desc", "fr"), ("stop_name", "de"), ("stop_name", "fr")]);
+        assert!(TranslationKey::Record("b".to_string()) < TranslationKey::Value("a".to_string()));
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use language_tags::LanguageTag;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use crate::{TranslatableField, TranslationKey, TranslationResult};
+
+// How much of one language the feeds translate
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LanguageCoverage {
+    pub feeds: Vec<String>,
+    pub translations: usize,
+}
+
+// The translations of many feeds, each under its feed id. Record ids are only unique within a feed,
+// so every lookup names the feed it is for.
+#[derive(Debug, Clone, Default)]
+pub struct MultiFeedTranslations {
+    feeds: BTreeMap<String, TranslationResult>,
+}
+
+impl MultiFeedTranslations {
+    pub fn new() -> MultiFeedTranslations {
+        MultiFeedTranslations::default()
+    }
+
+    // Adds or replaces a feed, returning the translations it replaced
+    pub fn insert_feed(&mut self, feed_id: impl Into<String>, translations: TranslationResult) -> Option<TranslationResult> {
+        self.feeds.insert(feed_id.into(), translations)
+    }
+
+    pub fn remove_feed(&mut self, feed_id: &str) -> Option<TranslationResult> {
+        self.feeds.remove(feed_id)
+    }
+
+    pub fn feed(&self, feed_id: &str) -> Option<&TranslationResult> {
+        self.feeds.get(feed_id)
+    }
+
+    pub fn feed_ids(&self) -> impl Iterator<Item = &str> + '_ {
+        self.feeds.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.feeds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.feeds.is_empty()
+    }
+
+    pub fn get(&self, feed_id: &str, field: &TranslatableField, key: &TranslationKey, language: &LanguageTag) -> Option<&str> {
+        self.feeds.get(feed_id)?.get(field, key, language)
+    }
+
+    pub fn resolve(&self, feed_id: &str, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>, language: &LanguageTag) -> Option<&str> {
+        self.feeds.get(feed_id)?.resolve(field, record_id, record_sub_id, original_value, language)
+    }
+
+    // Every language any feed has, sorted
+    pub fn languages(&self) -> Vec<LanguageTag> {
+        let mut languages = self.feeds.values().flat_map(|feed| feed.languages_as_written().iter().cloned()).collect::<Vec<LanguageTag>>();
+        languages.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        languages.dedup();
+        languages
+    }
+
+    // For each language, the feeds that have it and how many translations they have in it together
+    pub fn coverage(&self) -> BTreeMap<String, LanguageCoverage> {
+        let mut coverage: BTreeMap<String, LanguageCoverage> = BTreeMap::new();
+
+        for (feed_id, feed) in self.feeds.iter() {
+            for language in feed.languages_as_written().iter() {
+                coverage.entry(language.to_string()).or_default().feeds.push(feed_id.clone());
+            }
+
+            for lookup in feed.translations.keys() {
+                coverage.entry(lookup.language.to_string()).or_default().translations += 1;
+            }
+        }
+
+        coverage
+    }
+}
+
+impl FromIterator<(String, TranslationResult)> for MultiFeedTranslations {
+    fn from_iter<I: IntoIterator<Item = (String, TranslationResult)>>(iter: I) -> Self {
+        MultiFeedTranslations {
+            feeds: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::StopFields;
+
+    fn feeds() -> MultiFeedTranslations {
+        let a = translations("stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,de,Bahnhof,stop_1,,\n");
+        let b = translations("stops,stop_name,fr,Quai,stop_1,,\n");
+
+        [("a".to_string(), a), ("b".to_string(), b)].into_iter().collect()
+    }
+
+    #[test]
+    fn routes_lookups_by_feed() {
+        let feeds = feeds();
+        let field = TranslatableField::Stops(StopFields::Name);
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        assert_eq!(feeds.resolve("a", &field, Some("stop_1"), None, None, &fr), Some("Gare"));
+        assert_eq!(feeds.resolve("b", &field, Some("stop_1"), None, None, &fr), Some("Quai"));
+    }
+
+    #[test]
+    fn finds_nothing_in_an_unknown_feed() {
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        assert_eq!(feeds().resolve("c", &TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, None, &fr), None);
+    }
+
+    #[test]
+    fn summarizes_languages_across_feeds() {
+        let feeds = feeds();
+        assert_eq!(feeds.languages(), vec![LanguageTag::parse("de").unwrap(), LanguageTag::parse("fr").unwrap()]);
+
+        let coverage = feeds.coverage();
+        assert_eq!(coverage["fr"], LanguageCoverage { feeds: vec!["a".to_string(), "b".to_string()], translations: 2 });
+        assert_eq!(coverage["de"].feeds, vec!["a".to_string()]);
+    }
+}
@@ -0,0 +1,78 @@
+use language_tags::LanguageTag;
+use napi::{Error, Result};
+use napi_derive::napi;
+use crate::{fallback_languages, field_from_name, from_reader, parse_translations, ParseOptions, TranslationResult};
+
+// The Node face of a TranslationResult, with the same API as the wasm build so either can back a TypeScript tool
+#[napi(js_name = "Translations")]
+pub struct NodeTranslations {
+    result: TranslationResult,
+}
+
+#[napi(js_name = "parseTranslations")]
+pub fn parse_translations_node(csv: String) -> Result<NodeTranslations> {
+    parse_translations(&csv)
+        .map(|(result, _)| NodeTranslations { result })
+        .map_err(|err| Error::from_reason(err.to_string()))
+}
+
+#[napi(js_name = "parseTranslationsFile")]
+pub fn parse_translations_file(path: String) -> Result<NodeTranslations> {
+    let file = std::fs::File::open(path).map_err(|err| Error::from_reason(err.to_string()))?;
+
+    from_reader(file, &ParseOptions::default())
+        .map(|(result, _)| NodeTranslations { result })
+        .map_err(|err| Error::from_reason(err.to_string()))
+}
+
+#[napi]
+impl NodeTranslations {
+    #[napi]
+    pub fn get_translation(&self, field: String, record_id: String, language: String) -> Option<String> {
+        self.resolve(field, Some(record_id), None, None, language)
+    }
+
+    // undefined for the key parts a value does not have, with fallback from fr-CA to fr
+    #[napi]
+    pub fn resolve(&self, field: String, record_id: Option<String>, record_sub_id: Option<String>, original_value: Option<String>, language: String) -> Option<String> {
+        let field = field_from_name(&field)?;
+        let language = LanguageTag::parse(&language).ok()?;
+
+        fallback_languages(&self.result, &language).iter()
+            .find_map(|language| self.result.resolve(&field, record_id.as_deref(), record_sub_id.as_deref(), original_value.as_deref(), language))
+            .map(String::from)
+    }
+
+    #[napi]
+    pub fn languages(&self) -> Vec<String> {
+        self.result.languages_as_written().iter().map(|language| language.to_string()).collect()
+    }
+
+    #[napi(getter)]
+    pub fn length(&self) -> u32 {
+        self.result.translations.len() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+
+    fn translations() -> NodeTranslations {
+        parse_translations_node(format!("{}stops,stop_name,fr,Gare,stop_1,,\n", HEADER)).ok().unwrap()
+    }
+
+    #[test]
+    fn resolves_like_the_backend() {
+        let translations = translations();
+
+        assert_eq!(translations.length(), 1);
+        assert_eq!(translations.get_translation("stop_name".to_string(), "stop_1".to_string(), "fr-CA".to_string()).as_deref(), Some("Gare"));
+    }
+
+    #[test]
+    fn finds_nothing_for_an_unknown_field() {
+        assert_eq!(translations().get_translation("stops.stop_name".to_string(), "stop_2".to_string(), "fr".to_string()), None);
+    }
+}
@@ -0,0 +1,231 @@
+use std::error::Error;
+use std::io::{Cursor, Read, SeekFrom};
+use std::time::Instant;
+use rayon::prelude::*;
+use crate::decompress::decompress;
+use crate::instrument;
+use crate::limits::{check_record, surface_limit, LimitExceeded, LimitedReader};
+use crate::parse::{csv_reader_builder, index_record, normalize_headers, resolve_dialect, rows_for_bytes, sniff_head, RecordConverter};
+use crate::transcode::transcode;
+use crate::{ParseOptions, ParseStats, RawTranslationIndexer, RowError, TranslationResult};
+
+const MIN_CHUNK_SIZE: usize = 1 << 20;
+
+// A run of whole records, with the position of its first record in the file and the index of the record after its last
+struct Chunk {
+    position: csv::Position,
+    end_record: u64,
+}
+
+enum ChunkError {
+    Limit(LimitExceeded),
+    Csv(csv::Error),
+}
+
+// Cuts the body at line breaks that are outside quoted fields, following the quoting rules of the csv reader:
+// a quote only opens a field at its start, and inside quotes "" and the escape character do not close it.
+// Records are counted like the csv reader does, which skips blank lines and also ends records at a lone \r.
+fn split_records(data: &[u8], body: &csv::Position, options: &ParseOptions, delimiter: u8, chunk_size: usize) -> Vec<Chunk> {
+    let mut chunks = vec![];
+    let mut position = body.clone();
+    let mut chunk_start = position.clone();
+    let mut in_quotes = false;
+    let mut field_start = true;
+    let mut empty_record = true;
+    let mut i = body.byte() as usize;
+
+    while i < data.len() {
+        let byte = data[i];
+
+        if in_quotes {
+            if Some(byte) == options.escape && i + 1 < data.len() {
+                i += 1;
+            } else if byte == b'"' {
+                in_quotes = false;
+            }
+
+            if data[i] == b'\n' {
+                position.set_line(position.line() + 1);
+            }
+        } else if byte == b'\n' || byte == b'\r' {
+            if !empty_record {
+                position.set_record(position.record() + 1);
+            }
+            empty_record = true;
+
+            if byte == b'\n' {
+                position.set_line(position.line() + 1);
+
+                if (i + 1) as u64 - chunk_start.byte() >= chunk_size as u64 {
+                    position.set_byte((i + 1) as u64);
+                    chunks.push(Chunk { position: chunk_start, end_record: position.record() });
+                    chunk_start = position.clone();
+                }
+            }
+        } else {
+            empty_record = false;
+            in_quotes = byte == b'"' && field_start && options.quoting;
+        }
+
+        field_start = !in_quotes && (byte == delimiter || byte == b'\n' || byte == b'\r');
+        i += 1;
+    }
+
+    if (chunk_start.byte() as usize) < data.len() {
+        chunks.push(Chunk { position: chunk_start, end_record: u64::MAX });
+    }
+
+    chunks
+}
+
+// Every chunk gets its own reader over the whole file, seeked to the chunk. Reading the header first
+// keeps the field count check, and seeking with the real position keeps lines and bytes in errors right.
+fn parse_chunk(data: &[u8], chunk: &Chunk, converter: &RecordConverter, options: &ParseOptions, delimiter: u8) -> Result<(RawTranslationIndexer, ParseStats), ChunkError> {
+    let mut rdr = csv_reader_builder(options, delimiter).from_reader(Cursor::new(data));
+    rdr.seek_raw(SeekFrom::Start(chunk.position.byte()), chunk.position.clone()).map_err(ChunkError::Csv)?;
+
+    let mut indexer = RawTranslationIndexer::with_capacity(chunk.end_record.saturating_sub(chunk.position.record()).min(rows_for_bytes(data.len() as u64) as u64) as usize)
+        .with_provenance(options.provenance.clone());
+    let mut stats = ParseStats::default();
+    let mut record = csv::StringRecord::new();
+
+    // counting records rather than bytes, as the reader skips over blank lines at the end of a chunk into the next one
+    while rdr.position().record() < chunk.end_record {
+        match rdr.read_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => {
+                // max_rows is checked against the total once all chunks are in
+                check_record(&record, options, 0).map_err(ChunkError::Limit)?;
+                index_record(converter, Ok(&record), &mut indexer, &mut stats);
+            }
+            Err(err) if err.is_io_error() => return Err(ChunkError::Csv(err)),
+            Err(err) => index_record(converter, Err(RowError::from(err)), &mut indexer, &mut stats),
+        }
+    }
+
+    Ok((indexer, stats))
+}
+
+fn parse_parallel<R: Read>(reader: R, options: &ParseOptions, chunk_size: usize) -> Result<(TranslationResult, ParseStats), Box<dyn Error>> {
+    let (reader, compression) = decompress(reader)?;
+    let reader = LimitedReader::new(reader, options.max_total_bytes);
+    let (mut reader, encoding) = transcode(reader, options.encoding.as_deref())?;
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data)?;
+
+    let mut stats = ParseStats {
+        compression,
+        encoding,
+        ..ParseStats::default()
+    };
+
+    let (delimiter, skip) = sniff_head(&data, options, &mut stats);
+    let data = &data[skip..];
+
+    let mut rdr = csv_reader_builder(options, delimiter).from_reader(data);
+    let headers = normalize_headers(rdr.headers()?, options, &mut stats);
+    let body = rdr.position().clone();
+
+    let dialect = resolve_dialect(&headers, options)?;
+    let converter = RecordConverter::new(dialect, &headers, options)?;
+    stats.dialect = Some(dialect);
+    stats.unknown_columns = converter.unknown_columns();
+
+    let chunks = split_records(data, &body, options, delimiter, chunk_size);
+
+    let parsed = chunks.par_iter()
+        .map(|chunk| parse_chunk(data, chunk, &converter, options, delimiter))
+        .collect::<Vec<_>>();
+
+    // chunks are merged in file order so that a later row replaces an earlier one, as in from_reader
+    let mut indexer = RawTranslationIndexer::with_capacity(options.expected_rows.unwrap_or_else(|| rows_for_bytes(data.len() as u64)));
+
+    for chunk in parsed {
+        let (chunk_indexer, chunk_stats) = match chunk {
+            Ok(chunk) => chunk,
+            Err(ChunkError::Limit(limit)) => return Err(Box::new(limit)),
+            Err(ChunkError::Csv(err)) => return Err(Box::new(err)),
+        };
+
+        indexer.merge(chunk_indexer);
+        stats.rows_read += chunk_stats.rows_read;
+        stats.rows_skipped += chunk_stats.rows_skipped;
+        stats.errors.extend(chunk_stats.errors);
+        stats.text_changes.extend(chunk_stats.text_changes);
+    }
+
+    if let Some(max_rows) = options.max_rows {
+        if stats.rows_read > max_rows {
+            return Err(Box::new(LimitExceeded::Rows { max_rows }));
+        }
+    }
+
+    stats.duplicate_rows = indexer.duplicates;
+    Ok((indexer.finish(), stats))
+}
+
+// Same result as from_reader, but the file is read into memory and its records are parsed on the rayon pool.
+// Worth it for files of millions of rows, such as national aggregated feeds.
+pub fn from_reader_parallel<R: Read>(reader: R, options: &ParseOptions) -> Result<(TranslationResult, ParseStats), Box<dyn Error>> {
+    let _span = instrument::parse_span("from_reader_parallel");
+    let started = Instant::now();
+    let parsed = parse_parallel(reader, options, MIN_CHUNK_SIZE).map_err(surface_limit)?;
+
+    instrument::parsed(&parsed.1, parsed.0.translations.len(), started.elapsed());
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_reader;
+    use crate::test_util::HEADER;
+
+    fn sorted(result: &TranslationResult) -> (Vec<String>, Vec<String>) {
+        let mut languages = result.languages_as_written().iter().map(|language| language.to_string()).collect::<Vec<String>>();
+        let mut possible = result.possible_translations.iter().map(|(field, language)| format!("{:?} {}", field, language)).collect::<Vec<String>>();
+        languages.sort();
+        possible.sort();
+        (languages, possible)
+    }
+
+    // Rows with quoted newlines, duplicate keys and the odd invalid row, so chunks split inside all of them
+    fn data() -> String {
+        let mut data = String::from(HEADER);
+
+        for i in 0..500 {
+            data.push_str(&format!("stops,stop_name,fr,\"Gare {}\nquai \"\"{}\"\"\",stop_{},,\n", i, i % 7, i % 120));
+            data.push_str(&format!("stops,stop_name,de,Bahnhof {},stop_{},,\n", i, i % 90));
+            if i % 50 == 0 {
+                data.push_str("stops,stop_name,not a language,x,stop_1,,\n");
+                data.push_str("stops,stop_name,de\r\n\n");
+            }
+        }
+
+        data
+    }
+
+    #[test]
+    fn matches_sequential_parse() {
+        let data = data();
+        let (expected, expected_stats) = from_reader(data.as_bytes(), &ParseOptions::default()).unwrap();
+
+        for chunk_size in [1, 64, 4096, MIN_CHUNK_SIZE] {
+            let (result, stats) = parse_parallel(data.as_bytes(), &ParseOptions::default(), chunk_size).unwrap();
+
+            assert_eq!(result.translations, expected.translations);
+            assert_eq!(sorted(&result), sorted(&expected));
+            assert_eq!(result.key_counts, expected.key_counts);
+            assert_eq!(stats, expected_stats);
+        }
+    }
+
+    #[test]
+    fn stops_at_limits_like_sequential_parse() {
+        let options = ParseOptions { max_total_bytes: Some(100), ..ParseOptions::default() };
+
+        assert!(from_reader(data().as_bytes(), &options).is_err());
+        assert!(parse_parallel(data().as_bytes(), &options, 64).is_err());
+    }
+}
@@ -0,0 +1,676 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use crate::decompress::{decompress, Compression};
+use crate::instrument;
+use crate::limits::{check_record, surface_limit, LimitedReader};
+use crate::transcode::transcode;
+use crate::cleanup::{clean_column, clean_text, TextChange, TextCleanup};
+use crate::html::{apply_html_policy, HtmlPolicy};
+use crate::provenance::ProvenanceSource;
+use crate::import::legacy::{is_legacy_header, LegacyColumns, LEGACY_TRANSLATABLE_FIELDS};
+use crate::import::wide_csv::WideColumns;
+use crate::{translate_raw_translations_with_stats, RawTranslation, RawTranslationIndexer, RawTranslationRef, TranslatableField, TranslationResult};
+
+// The shapes of translations.txt seen in the wild
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Dialect {
+    // table_name, field_name, language, translation, record_id, record_sub_id, field_value
+    Standard,
+    // trans_id, lang, translation
+    Legacy,
+    // table, field, record_id, original and one column per language
+    Wide,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ParseStats {
+    pub dialect: Option<Dialect>,
+    pub compression: Option<Compression>,
+    // Whether the input started with a UTF-8 byte order mark
+    pub bom: bool,
+    pub delimiter: Option<u8>,
+    // Encoding the input was transcoded from, None for UTF-8
+    pub encoding: Option<String>,
+    // Data rows in the file
+    pub rows_read: usize,
+    // Rows that did not become a translation: unreadable rows, unknown fields, invalid languages or missing keys
+    pub rows_skipped: usize,
+    // Header names that were rewritten, as (header in the file, column it was read as)
+    pub aliases_applied: Vec<(String, String)>,
+    // Columns the dialect does not read, such as proprietary additions
+    pub unknown_columns: Vec<String>,
+    // Why each skipped row was skipped, with its position in the file
+    pub errors: Vec<RowError>,
+    // Rows that replaced an earlier row for the same field, key and language
+    pub duplicate_rows: usize,
+    // Values ParseOptions::text_cleanup rewrote
+    pub text_changes: Vec<TextChange>,
+}
+
+pub fn detect_dialect(headers: &csv::StringRecord) -> Option<Dialect> {
+    let has = |names: &[&str]| headers.iter().any(|header| names.contains(&header.trim().to_lowercase().as_str()));
+
+    if is_legacy_header(headers) {
+        Some(Dialect::Legacy)
+    } else if has(&["table_name"]) && has(&["field_name"]) && has(&["language"]) && has(&["translation"]) {
+        Some(Dialect::Standard)
+    } else if has(&["table_name", "table"]) && has(&["field_name", "field"]) {
+        Some(Dialect::Wide)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    // Parse as this dialect instead of sniffing the header row
+    pub dialect: Option<Dialect>,
+    // Fields a legacy trans_id may appear in, see import::legacy
+    pub legacy_fields: Vec<TranslatableField>,
+    // Drop a leading UTF-8 byte order mark, which otherwise ends up in the first header name
+    pub strip_bom: bool,
+    // Field delimiter, or None to pick between comma, semicolon, tab and pipe from the header row
+    pub delimiter: Option<u8>,
+    // Accept rows with more or fewer fields than the header
+    pub flexible: bool,
+    // Treat double quotes as quoting. Turn off for files with stray quotes inside unquoted fields
+    pub quoting: bool,
+    // Escape character inside quoted fields, for files that use \" instead of ""
+    pub escape: Option<u8>,
+    // Source encoding label such as "windows-1252" or "shift_jis", or AUTO_ENCODING to guess.
+    // Anything other than UTF-8 needs the encoding feature.
+    pub encoding: Option<String>,
+    // Non-standard header names mapped to the column they stand for, matched case-insensitively after trimming
+    pub header_aliases: HashMap<String, String>,
+    pub unknown_columns: UnknownColumns,
+    // Caps for untrusted input, parsing fails with LimitExceeded past any of them
+    pub max_rows: Option<usize>,
+    pub max_field_len: Option<usize>,
+    // Counted after decompression
+    pub max_total_bytes: Option<u64>,
+    // Cap on a downloaded GTFS zip, which max_total_bytes does not cover as the archive holds the whole feed.
+    // Unset, downloads that set max_total_bytes stop at http::DEFAULT_MAX_ARCHIVE_BYTES.
+    pub max_archive_bytes: Option<u64>,
+    // Roughly how many rows to expect, so the index is allocated once. Zip entries fill this in from their size.
+    pub expected_rows: Option<usize>,
+    // Record a Provenance for every row, see TranslationResult::get_with_meta
+    pub provenance: Option<ProvenanceSource>,
+    // Clean-up applied to translations and field values before they are indexed, reported in ParseStats
+    pub text_cleanup: TextCleanup,
+    // HTML entities and tags in translations, reported in ParseStats when they are changed
+    pub html: HtmlPolicy,
+}
+
+// What to do with columns outside the dialect. Their names are reported in ParseStats either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownColumns {
+    #[default]
+    Ignore,
+    // Keep their values in RawTranslation::extras
+    Preserve,
+}
+
+// Columns any dialect reads. Headers matching one of these case-insensitively are rewritten to it.
+const KNOWN_COLUMNS: [&str; 14] = [
+    "table_name", "field_name", "language", "translation", "record_id", "record_sub_id", "field_value",
+    "trans_id", "lang", "table", "field", "original", "source", "feed_lang",
+];
+
+pub fn default_header_aliases() -> HashMap<String, String> {
+    [("lang", "language"), ("trans", "translation"), ("lang_code", "language"), ("language_code", "language"), ("value", "field_value")]
+        .iter()
+        .map(|(alias, column)| (alias.to_string(), column.to_string()))
+        .collect()
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            dialect: None,
+            legacy_fields: LEGACY_TRANSLATABLE_FIELDS.to_vec(),
+            strip_bom: true,
+            delimiter: None,
+            flexible: false,
+            quoting: true,
+            escape: None,
+            encoding: None,
+            header_aliases: default_header_aliases(),
+            unknown_columns: UnknownColumns::Ignore,
+            max_rows: None,
+            max_field_len: None,
+            max_total_bytes: None,
+            max_archive_bytes: None,
+            expected_rows: None,
+            provenance: None,
+            text_cleanup: TextCleanup::default(),
+            html: HtmlPolicy::Preserve,
+        }
+    }
+}
+
+// Trims header names, lowercases known column names and applies the alias map. Legacy files use `lang`
+// as a real column, so aliases are not applied to headers that already look like the legacy dialect.
+pub(crate) fn normalize_headers(headers: &csv::StringRecord, options: &ParseOptions, stats: &mut ParseStats) -> csv::StringRecord {
+    let cleaned = headers.iter()
+        .map(|header| {
+            let trimmed = header.trim();
+            let lowered = trimmed.to_lowercase();
+
+            if KNOWN_COLUMNS.contains(&lowered.as_str()) || options.header_aliases.contains_key(&lowered) { lowered } else { trimmed.to_string() }
+        })
+        .collect::<csv::StringRecord>();
+
+    let legacy = options.dialect == Some(Dialect::Legacy) || (options.dialect.is_none() && is_legacy_header(&cleaned));
+
+    let normalized = cleaned.iter()
+        .map(|header| match options.header_aliases.get(header) {
+            Some(column) if !legacy => column.as_str(),
+            _ => header,
+        })
+        .collect::<csv::StringRecord>();
+
+    for (original, normalized) in headers.iter().zip(normalized.iter()) {
+        if original != normalized {
+            stats.aliases_applied.push((original.to_string(), normalized.to_string()));
+        }
+    }
+
+    normalized
+}
+
+// A typical translations.txt row is 40 to 60 bytes, guessing low only costs a rehash or two
+#[cfg(any(feature = "zip", feature = "rayon"))]
+pub(crate) fn rows_for_bytes(bytes: u64) -> usize {
+    (bytes / 48) as usize
+}
+
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+const CANDIDATE_DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+// Picks the candidate that occurs most often outside quotes on the first line, preferring comma on ties
+pub(crate) fn sniff_delimiter(head: &[u8]) -> u8 {
+    let mut counts = [0usize; CANDIDATE_DELIMITERS.len()];
+    let mut in_quotes = false;
+
+    for byte in head {
+        if !in_quotes && (*byte == b'\n' || *byte == b'\r') {
+            break;
+        }
+
+        if *byte == b'"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes {
+            if let Some(i) = CANDIDATE_DELIMITERS.iter().position(|delimiter| delimiter == byte) {
+                counts[i] += 1;
+            }
+        }
+    }
+
+    let (best, count) = counts.iter().enumerate().fold((0, 0), |best, (i, count)| if *count > best.1 { (i, *count) } else { best });
+
+    if count == 0 { b',' } else { CANDIDATE_DELIMITERS[best] }
+}
+
+// Works out the BOM and delimiter from the start of the input.
+// Returns the delimiter and how many bytes to skip before handing the input to the csv reader.
+pub(crate) fn sniff_head(head: &[u8], options: &ParseOptions, stats: &mut ParseStats) -> (u8, usize) {
+    let bom = head.starts_with(UTF8_BOM);
+
+    let delimiter = options.delimiter.unwrap_or_else(|| sniff_delimiter(if bom { &head[UTF8_BOM.len()..] } else { head }));
+
+    stats.bom = bom;
+    stats.delimiter = Some(delimiter);
+
+    (delimiter, if bom && options.strip_bom { UTF8_BOM.len() } else { 0 })
+}
+
+pub(crate) fn csv_reader_builder(options: &ParseOptions, delimiter: u8) -> csv::ReaderBuilder {
+    let mut builder = csv::ReaderBuilder::new();
+    builder.delimiter(delimiter).flexible(options.flexible).quoting(options.quoting).escape(options.escape);
+    builder
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RowError {
+    // 1-based line of the record in the file, including the header
+    pub line: Option<u64>,
+    // Byte offset of the start of the record
+    pub byte: Option<u64>,
+    pub message: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for RowError {}
+
+impl From<csv::Error> for RowError {
+    fn from(err: csv::Error) -> Self {
+        RowError {
+            line: err.position().map(|position| position.line()),
+            byte: err.position().map(|position| position.byte()),
+            message: err.to_string(),
+        }
+    }
+}
+
+// Lazily deserializes rows of a standard translations.txt, so large files never sit in memory as a Vec
+pub struct RawTranslationIter<R: Read> {
+    rows: csv::DeserializeRecordsIntoIter<R, RawTranslation>,
+}
+
+impl<R: Read> RawTranslationIter<R> {
+    pub fn new(reader: R) -> RawTranslationIter<R> {
+        RawTranslationIter::from_csv_reader(csv::Reader::from_reader(reader))
+    }
+
+    pub(crate) fn from_csv_reader(rdr: csv::Reader<R>) -> RawTranslationIter<R> {
+        RawTranslationIter {
+            rows: rdr.into_deserialize(),
+        }
+    }
+}
+
+impl<R: Read> Iterator for RawTranslationIter<R> {
+    type Item = Result<RawTranslation, RowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next().map(|row| row.map_err(RowError::from))
+    }
+}
+
+impl FromIterator<RawTranslation> for TranslationResult {
+    fn from_iter<I: IntoIterator<Item = RawTranslation>>(iter: I) -> Self {
+        translate_raw_translations_with_stats(iter, &mut ParseStats::default())
+    }
+}
+
+// Feeds one record into the indexer. A bad record is counted and reported and does not affect the records after it.
+pub(crate) fn index_record(converter: &RecordConverter, record: Result<&csv::StringRecord, RowError>, indexer: &mut RawTranslationIndexer, stats: &mut ParseStats) {
+    stats.rows_read += 1;
+
+    let position = record.as_ref().ok().and_then(|record| record.position().cloned());
+    indexer.row = position.as_ref().map(|position| position.line());
+
+    let dropped = match record.and_then(|record| converter.index(record, indexer, &mut stats.text_changes)) {
+        Ok(dropped) => dropped,
+        Err(err) => {
+            stats.rows_skipped += 1;
+            stats.errors.push(err);
+            return;
+        }
+    };
+
+    for message in dropped {
+        stats.rows_skipped += 1;
+        stats.errors.push(RowError {
+            line: position.as_ref().map(|position| position.line()),
+            byte: position.as_ref().map(|position| position.byte()),
+            message,
+        });
+    }
+}
+
+enum DialectColumns {
+    Standard(csv::StringRecord),
+    Legacy(LegacyColumns),
+    Wide(WideColumns),
+}
+
+// Turns one csv record into translations.txt rows according to the dialect of the file
+pub(crate) struct RecordConverter {
+    columns: DialectColumns,
+    // Columns the dialect does not read, with their position
+    unknown: Vec<(usize, String)>,
+    preserve_unknown: bool,
+    cleanup: TextCleanup,
+    html: HtmlPolicy,
+}
+
+const STANDARD_COLUMNS: [&str; 7] = ["table_name", "field_name", "language", "translation", "record_id", "record_sub_id", "field_value"];
+const LEGACY_COLUMNS: [&str; 3] = ["trans_id", "lang", "translation"];
+
+impl RecordConverter {
+    pub(crate) fn new(dialect: Dialect, headers: &csv::StringRecord, options: &ParseOptions) -> Result<RecordConverter, Box<dyn Error>> {
+        options.text_cleanup.check()?;
+
+        let outside = |known: &[&str]| headers.iter().enumerate()
+            .filter(|(_, header)| !known.contains(header))
+            .map(|(column, header)| (column, header.to_string()))
+            .collect::<Vec<(usize, String)>>();
+
+        let (columns, unknown) = match dialect {
+            Dialect::Standard => (DialectColumns::Standard(headers.clone()), outside(&STANDARD_COLUMNS)),
+            Dialect::Legacy => (DialectColumns::Legacy(LegacyColumns::new(headers, &options.legacy_fields)?), outside(&LEGACY_COLUMNS)),
+            // the extra columns of a wide file are languages, or unknown when their header is not one
+            Dialect::Wide => {
+                let columns = WideColumns::new(headers)?;
+                let unknown = columns.unknown.clone();
+                (DialectColumns::Wide(columns), unknown)
+            }
+        };
+
+        Ok(RecordConverter {
+            columns,
+            unknown,
+            preserve_unknown: options.unknown_columns == UnknownColumns::Preserve,
+            cleanup: options.text_cleanup,
+            html: options.html,
+        })
+    }
+
+    pub(crate) fn unknown_columns(&self) -> Vec<String> {
+        self.unknown.iter().map(|(_, header)| header.clone()).collect()
+    }
+
+    // Indexes the rows of a record and returns why any of them were dropped. Standard rows are borrowed
+    // from the record, as extras never reach the index anyway.
+    pub(crate) fn index(&self, record: &csv::StringRecord, indexer: &mut RawTranslationIndexer, changes: &mut Vec<TextChange>) -> Result<Vec<String>, RowError> {
+        let results = match &self.columns {
+            DialectColumns::Standard(headers) => {
+                let mut row = record.deserialize::<RawTranslationRef>(Some(headers))?;
+                self.clean(&mut row.translation, row.field_value.as_mut(), indexer.row, changes);
+                vec![indexer.push_ref(row)]
+            }
+            _ => self.convert(record)?.into_iter()
+                .map(|mut row| {
+                    let mut translation = Cow::Owned(std::mem::take(&mut row.translation));
+                    let mut field_value = row.field_value.take().map(Cow::Owned);
+                    self.clean(&mut translation, field_value.as_mut(), indexer.row, changes);
+                    row.translation = translation.into_owned();
+                    row.field_value = field_value.map(Cow::into_owned);
+                    indexer.push(row)
+                })
+                .collect(),
+        };
+
+        Ok(results.into_iter().filter_map(Result::err).collect())
+    }
+
+    fn clean(&self, translation: &mut Cow<'_, str>, field_value: Option<&mut Cow<'_, str>>, line: Option<u64>, changes: &mut Vec<TextChange>) {
+        if self.html != HtmlPolicy::Preserve {
+            clean_column(translation, "translation", line, |text| apply_html_policy(text, self.html), changes);
+        }
+
+        if !self.cleanup.is_enabled() {
+            return;
+        }
+
+        clean_column(translation, "translation", line, |text| clean_text(text, &self.cleanup), changes);
+        if let Some(field_value) = field_value {
+            clean_column(field_value, "field_value", line, |text| clean_text(text, &self.cleanup), changes);
+        }
+    }
+
+    pub(crate) fn convert(&self, record: &csv::StringRecord) -> Result<Vec<RawTranslation>, RowError> {
+        let mut rows = match &self.columns {
+            DialectColumns::Standard(headers) => vec![record.deserialize::<RawTranslation>(Some(headers))?],
+            DialectColumns::Legacy(columns) => columns.rows(record),
+            DialectColumns::Wide(columns) => columns.rows(record),
+        };
+
+        if self.preserve_unknown && !self.unknown.is_empty() {
+            let extras = self.unknown.iter()
+                .filter_map(|(column, header)| record.get(*column).map(|value| (header.clone(), value.to_string())))
+                .collect::<HashMap<String, String>>();
+
+            for row in rows.iter_mut() {
+                row.extras = extras.clone();
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+pub(crate) fn resolve_dialect(headers: &csv::StringRecord, options: &ParseOptions) -> Result<Dialect, Box<dyn Error>> {
+    match options.dialect {
+        Some(dialect) => Ok(dialect),
+        None => detect_dialect(headers).ok_or_else(|| "unrecognised translations.txt header".into()),
+    }
+}
+
+// Streams translations from any reader: a file, a zip entry or a response body
+// gzip and zstd input is decompressed transparently when the matching feature is enabled.
+pub fn from_reader<R: Read>(reader: R, options: &ParseOptions) -> Result<(TranslationResult, ParseStats), Box<dyn Error>> {
+    let _span = instrument::parse_span("from_reader");
+    let started = Instant::now();
+    let parsed = read_translations(reader, options).map_err(surface_limit)?;
+
+    instrument::parsed(&parsed.1, parsed.0.translations.len(), started.elapsed());
+    Ok(parsed)
+}
+
+fn read_translations<R: Read>(reader: R, options: &ParseOptions) -> Result<(TranslationResult, ParseStats), Box<dyn Error>> {
+    let (reader, compression) = decompress(reader)?;
+    let reader = LimitedReader::new(reader, options.max_total_bytes);
+    let (reader, encoding) = transcode(reader, options.encoding.as_deref())?;
+    let mut reader = BufReader::new(reader);
+
+    let mut stats = ParseStats {
+        compression,
+        encoding,
+        ..ParseStats::default()
+    };
+
+    let (delimiter, skip) = sniff_head(reader.fill_buf()?, options, &mut stats);
+    reader.consume(skip);
+
+    let mut rdr = csv_reader_builder(options, delimiter).from_reader(reader);
+    let headers = normalize_headers(rdr.headers()?, options, &mut stats);
+
+    let dialect = resolve_dialect(&headers, options)?;
+    let converter = RecordConverter::new(dialect, &headers, options)?;
+    stats.dialect = Some(dialect);
+    stats.unknown_columns = converter.unknown_columns();
+    let mut indexer = RawTranslationIndexer::with_capacity(options.expected_rows.unwrap_or_default()).with_provenance(options.provenance.clone());
+
+    let mut record = csv::StringRecord::new();
+
+    // read_record reuses one buffer and carries on after a malformed record
+    loop {
+        match rdr.read_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => {
+                check_record(&record, options, stats.rows_read)?;
+                index_record(&converter, Ok(&record), &mut indexer, &mut stats);
+            }
+            Err(err) if err.is_io_error() => return Err(err.into()),
+            Err(err) => index_record(&converter, Err(RowError::from(err)), &mut indexer, &mut stats),
+        }
+    }
+
+    stats.duplicate_rows = indexer.duplicates;
+    Ok((indexer.finish(), stats))
+}
+
+// Sniffs the header row and parses the file with the matching dialect
+pub fn parse_translations(data: &str) -> Result<(TranslationResult, ParseStats), Box<dyn Error>> {
+    from_reader(data.as_bytes(), &ParseOptions::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+
+    #[test]
+    fn parses_the_standard_layout_and_skips_bad_rows() {
+        let (standard, stats) = parse_translations(&format!("{}stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,not a language,Gare,stop_1,,\n", HEADER)).unwrap();
+
+        assert_eq!(stats, ParseStats { dialect: Some(Dialect::Standard), rows_read: 2, rows_skipped: 1, delimiter: Some(b','), errors: stats.errors.clone(), ..ParseStats::default() });
+        assert_eq!(stats.errors[0].line, Some(3));
+        assert_eq!(standard.translations.len(), 1);
+    }
+
+    #[test]
+    fn detects_the_legacy_layout() {
+        let (legacy, stats) = parse_translations("trans_id,lang,translation\nCentral,fr,Centrale\n").unwrap();
+
+        assert_eq!(stats.dialect, Some(Dialect::Legacy));
+        assert_eq!(legacy.translations.len(), LEGACY_TRANSLATABLE_FIELDS.len());
+    }
+
+    #[test]
+    fn detects_the_wide_layout() {
+        let (wide, stats) = parse_translations("table,field,record_id,original,fr,de,comment\nstops,stop_name,stop_1,Station,Gare,Bahnhof,ok\n").unwrap();
+
+        assert_eq!(stats.dialect, Some(Dialect::Wide));
+        assert_eq!(stats.unknown_columns, vec!["comment".to_string()]);
+        assert_eq!(wide.translations.len(), 2);
+    }
+
+    #[test]
+    fn rejects_an_unknown_header() {
+        assert!(parse_translations("a,b\n1,2\n").is_err());
+    }
+
+    #[test]
+    fn forced_dialect_skips_what_it_cannot_read() {
+        let options = ParseOptions { dialect: Some(Dialect::Standard), ..ParseOptions::default() };
+        let (forced, stats) = from_reader("a,b\n1,2\n".as_bytes(), &options).unwrap();
+
+        assert_eq!(stats, ParseStats { dialect: Some(Dialect::Standard), rows_read: 1, rows_skipped: 1, delimiter: Some(b','), unknown_columns: vec!["a".to_string(), "b".to_string()], errors: stats.errors.clone(), ..ParseStats::default() });
+        assert!(forced.translations.is_empty());
+    }
+
+    #[test]
+    fn iterates_rows_lazily() {
+        let data = format!("{}stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,de\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n", HEADER);
+
+        let rows = RawTranslationIter::new(data.as_bytes()).collect::<Vec<Result<RawTranslation, RowError>>>();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1].as_ref().unwrap_err().line, Some(3));
+
+        let result = RawTranslationIter::new(data.as_bytes()).filter_map(Result::ok).collect::<TranslationResult>();
+        assert_eq!(result.translations.len(), 2);
+    }
+
+    const SEMICOLONS: &str = "\u{feff}table_name;field_name;language;translation;record_id;record_sub_id;field_value\n\
+        stops;stop_name;fr;Gare, Nord;stop_1;;\n\
+        stops;stop_name;de;Bahnhof;stop_2\n";
+
+    #[test]
+    fn tolerates_bom_and_semicolons() {
+        let (result, stats) = parse_translations(SEMICOLONS).unwrap();
+
+        assert!(stats.bom);
+        assert_eq!(stats.delimiter, Some(b';'));
+        assert_eq!(result.translations.len(), 1);
+    }
+
+    #[test]
+    fn flexible_reads_short_rows() {
+        let options = ParseOptions { flexible: true, ..ParseOptions::default() };
+        let (result, stats) = from_reader(SEMICOLONS.as_bytes(), &options).unwrap();
+
+        assert_eq!(result.translations.len(), 2);
+        assert_eq!(stats.rows_skipped, 0);
+    }
+
+    const ALIASED: &str = "Table_Name ,FIELD_NAME,lang,trans,record_id,record_sub_id,field_value\nstops,stop_name,fr,Gare,stop_1,,\n";
+
+    #[test]
+    fn applies_header_aliases() {
+        let (result, stats) = parse_translations(ALIASED).unwrap();
+
+        assert_eq!(stats.dialect, Some(Dialect::Standard));
+        assert_eq!(result.translations.len(), 1);
+        assert_eq!(stats.aliases_applied, vec![
+            ("Table_Name ".to_string(), "table_name".to_string()),
+            ("FIELD_NAME".to_string(), "field_name".to_string()),
+            ("lang".to_string(), "language".to_string()),
+            ("trans".to_string(), "translation".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn applies_header_aliases_to_the_legacy_layout() {
+        let (legacy, stats) = parse_translations("TRANS_ID,lang,translation\nCentral,fr,Centrale\n").unwrap();
+
+        assert_eq!(stats.dialect, Some(Dialect::Legacy));
+        assert_eq!(stats.aliases_applied, vec![("TRANS_ID".to_string(), "trans_id".to_string())]);
+        assert!(!legacy.translations.is_empty());
+    }
+
+    #[test]
+    fn reads_aliases_as_languages_without_them() {
+        // without the aliases lang and trans look like language columns of a wide file
+        let options = ParseOptions { header_aliases: HashMap::new(), ..ParseOptions::default() };
+        let (_, stats) = from_reader(ALIASED.as_bytes(), &options).unwrap();
+
+        assert_eq!(stats.dialect, Some(Dialect::Wide));
+    }
+
+    const NOTED: &str = "table_name,field_name,language,translation,record_id,record_sub_id,field_value,agency_note\n\
+        stops,stop_name,fr,Gare,stop_1,,,checked\n";
+
+    #[test]
+    fn reports_unknown_columns() {
+        let (result, stats) = parse_translations(NOTED).unwrap();
+
+        assert_eq!(stats.unknown_columns, vec!["agency_note".to_string()]);
+        assert_eq!(result.translations.len(), 1);
+        assert!(RawTranslationIter::new(NOTED.as_bytes()).next().unwrap().unwrap().extras.is_empty());
+    }
+
+    #[test]
+    fn preserves_unknown_columns_when_asked() {
+        let options = ParseOptions { unknown_columns: UnknownColumns::Preserve, ..ParseOptions::default() };
+        let converter = RecordConverter::new(Dialect::Standard, &csv::StringRecord::from(NOTED.lines().next().unwrap().split(',').collect::<Vec<&str>>()), &options).unwrap();
+        let record = csv::StringRecord::from(NOTED.lines().nth(1).unwrap().split(',').collect::<Vec<&str>>());
+
+        let row = converter.convert(&record).unwrap().remove(0);
+        assert_eq!(row.extras.get("agency_note").map(String::as_str), Some("checked"));
+    }
+
+    #[test]
+    fn isolates_bad_records() {
+        let data = [HEADER.as_bytes(), b"stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,fr,Gare Sud\n\
+            stops,stop_name,fr,Gare \xff,stop_3,,\n\
+            stops,stop_name,fr,Gare Nord,stop_4,,\n\
+            stops,stop_color,fr,Rouge,stop_5,,\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n"].concat();
+
+        let (result, stats) = from_reader(&data[..], &ParseOptions::default()).unwrap();
+
+        assert_eq!(result.translations.len(), 3);
+        assert_eq!(stats.rows_skipped, stats.errors.len());
+        assert_eq!(stats.errors.iter().map(|err| err.line).collect::<Vec<Option<u64>>>(), vec![Some(3), Some(4), Some(6)]);
+        assert!(stats.errors.iter().all(|err| err.byte.is_some()));
+        assert!(stats.errors[2].message.contains("stops.stop_color"));
+    }
+
+    #[test]
+    fn borrows_standard_rows() {
+        let headers = csv::StringRecord::from(vec!["table_name", "field_name", "language", "translation", "record_id", "record_sub_id", "field_value"]);
+        let record = csv::StringRecord::from(vec!["stops", "stop_name", "fr", "Gare", "stop_1", "", ""]);
+
+        let row = record.deserialize::<RawTranslationRef>(Some(&headers)).unwrap();
+        assert!(matches!(row.translation, std::borrow::Cow::Borrowed("Gare")));
+        assert_eq!(row.record_sub_id, None);
+
+        let mut indexer = RawTranslationIndexer::default();
+        assert!(indexer.push_ref(row.clone()).is_ok());
+        assert_eq!(row.into_owned().record_id.as_deref(), Some("stop_1"));
+        assert_eq!(indexer.finish().translations.len(), 1);
+    }
+}
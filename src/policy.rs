@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::error::Error;
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize};
+use crate::universe::TranslationUniverse;
+use crate::{enum_to_table_and_field, table_and_field_to_enum, TranslatableField, TranslationResult, TRANSLATABLE_FIELDS};
+
+// Which fields a feed must have translated into which languages, such as every stop name in French and English
+// in a bilingual jurisdiction. Meant to be kept as a JSON or YAML document next to the feed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslationPolicy {
+    pub requirements: Vec<PolicyRequirement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRequirement {
+    pub table_name: String,
+    // Every translatable field of the table when empty
+    #[serde(default)]
+    pub field_names: Vec<String>,
+    pub languages: Vec<String>,
+}
+
+// A value in the feed that lacks some of the translations the policy asks for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    pub table_name: String,
+    pub field_name: String,
+    pub record_id: Option<String>,
+    pub record_sub_id: Option<String>,
+    pub original_value: String,
+    pub missing_languages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyReport {
+    pub passed: bool,
+    // How many feed values the policy covers
+    pub checked: usize,
+    pub violations: Vec<PolicyViolation>,
+}
+
+// The fields and languages of each requirement, failing on names that are not translatable fields or languages so
+// that a typo in the document can't make a policy pass
+fn resolve_requirements(policy: &TranslationPolicy) -> Result<HashMap<TranslatableField, Vec<LanguageTag>>, Box<dyn Error>> {
+    let mut required: HashMap<TranslatableField, Vec<LanguageTag>> = HashMap::new();
+
+    for requirement in policy.requirements.iter() {
+        let fields = match requirement.field_names.is_empty() {
+            true => TRANSLATABLE_FIELDS.iter().filter(|field| enum_to_table_and_field(field).0 == requirement.table_name).cloned().collect(),
+            false => requirement.field_names.iter()
+                .map(|field_name| table_and_field_to_enum(&requirement.table_name, field_name)
+                    .ok_or_else(|| format!("unknown translatable field {}.{}", requirement.table_name, field_name)))
+                .collect::<Result<Vec<TranslatableField>, String>>()?,
+        };
+
+        if fields.is_empty() {
+            return Err(format!("table {} has no translatable fields", requirement.table_name).into());
+        }
+
+        let languages = requirement.languages.iter()
+            .map(|language| LanguageTag::parse(language).map_err(|err| format!("invalid language tag {}: {}", language, err)))
+            .collect::<Result<Vec<LanguageTag>, String>>()?;
+
+        for field in fields {
+            let entry = required.entry(field).or_default();
+            for language in languages.iter() {
+                if !entry.contains(language) {
+                    entry.push(language.clone());
+                }
+            }
+        }
+    }
+
+    Ok(required)
+}
+
+// Checks every value of the feed the policy covers and lists the ones missing a required language. The report
+// passes when there are none.
+pub fn enforce_policy(policy: &TranslationPolicy, result: &TranslationResult, universe: &TranslationUniverse) -> Result<PolicyReport, Box<dyn Error>> {
+    let required = resolve_requirements(policy)?;
+    let mut report = PolicyReport::default();
+
+    for entry in universe.entries.iter() {
+        let Some(languages) = required.get(&entry.field) else {
+            continue;
+        };
+
+        report.checked += 1;
+        let missing_languages = languages.iter()
+            .filter(|language| result.translate_entry(entry, language).is_none())
+            .map(|language| language.to_string())
+            .collect::<Vec<String>>();
+
+        if !missing_languages.is_empty() {
+            let (table_name, field_name) = enum_to_table_and_field(&entry.field);
+            report.violations.push(PolicyViolation {
+                table_name: table_name.to_string(),
+                field_name: field_name.to_string(),
+                record_id: entry.record_id.clone(),
+                record_sub_id: entry.record_sub_id.clone(),
+                original_value: entry.original_value.clone(),
+                missing_languages,
+            });
+        }
+    }
+
+    report.violations.sort_by(|a, b| (&a.table_name, &a.field_name, &a.record_id, &a.record_sub_id, &a.original_value)
+        .cmp(&(&b.table_name, &b.field_name, &b.record_id, &b.record_sub_id, &b.original_value)));
+    report.passed = report.violations.is_empty();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{RouteFields, StopFields};
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,en,Station,stop_1,,\n\
+            stops,stop_name,fr,Place,stop_2,,\n")
+    }
+
+    fn universe() -> TranslationUniverse {
+        let mut universe = TranslationUniverse::new();
+        universe.push(TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, "Gare");
+        universe.push(TranslatableField::Stops(StopFields::Name), Some("stop_2"), None, "Place");
+        universe.push(TranslatableField::Routes(RouteFields::LongName), Some("route_1"), None, "Ligne 1");
+        universe
+    }
+
+    #[test]
+    fn reports_missing_required_languages() {
+        let policy: TranslationPolicy = serde_json::from_str(r#"{"requirements": [{"table_name": "stops", "field_names": ["stop_name"], "languages": ["fr", "en"]}]}"#).unwrap();
+        let report = enforce_policy(&policy, &result(), &universe()).unwrap();
+
+        // only the stop names are checked, not the route
+        assert!(!report.passed);
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!((report.violations[0].record_id.as_deref(), report.violations[0].missing_languages.clone()), (Some("stop_2"), vec!["en".to_string()]));
+    }
+
+    #[test]
+    fn passes_when_every_language_is_there() {
+        let policy = TranslationPolicy { requirements: vec![PolicyRequirement { table_name: "stops".to_string(), field_names: vec!["stop_name".to_string()], languages: vec!["fr".to_string()] }] };
+        let report = enforce_policy(&policy, &result(), &universe()).unwrap();
+
+        assert!(report.passed);
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let typo = TranslationPolicy { requirements: vec![PolicyRequirement { table_name: "stops".to_string(), field_names: vec!["stop_nam".to_string()], languages: vec![] }] };
+
+        assert!(enforce_policy(&typo, &result(), &universe()).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::{TranslatableField, TranslationKey, TranslationLookup, TranslationMap, TranslationResult};
+use language_tags::LanguageTag;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Origin {
+    Human,
+    Machine,
+}
+
+// Where a translation came from, so that layered sets can explain a disputed string.
+// Only translations that something is known about have an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Provenance {
+    pub origin: Origin,
+    pub source_file: Option<String>,
+    // Line of the row in the source file, counting the header as line 1
+    pub source_row: Option<u64>,
+    // Import run the translation arrived in, as named by the caller
+    pub batch_id: Option<String>,
+}
+
+impl Provenance {
+    pub fn machine() -> Provenance {
+        Provenance {
+            origin: Origin::Machine,
+            source_file: None,
+            source_row: None,
+            batch_id: None,
+        }
+    }
+}
+
+// Set ParseOptions::provenance to one of these to have every parsed row record where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvenanceSource {
+    pub origin: Origin,
+    pub source_file: Option<String>,
+    pub batch_id: Option<String>,
+}
+
+impl ProvenanceSource {
+    pub fn file(source_file: impl Into<String>) -> ProvenanceSource {
+        ProvenanceSource {
+            origin: Origin::Human,
+            source_file: Some(source_file.into()),
+            batch_id: None,
+        }
+    }
+
+    pub(crate) fn at_row(&self, source_row: Option<u64>) -> Provenance {
+        Provenance {
+            origin: self.origin,
+            source_file: self.source_file.clone(),
+            source_row,
+            batch_id: self.batch_id.clone(),
+        }
+    }
+}
+
+impl TranslationResult {
+    // Metadata for the translations it is known for, such as the ones filled in by machine translation or read
+    // with ParseOptions::provenance. Editing through insert and remove keeps it in step with the translations.
+    pub fn provenance(&self) -> &TranslationMap<TranslationLookup, Provenance> {
+        &self.provenance
+    }
+
+    pub fn get_with_meta(&self, field: &TranslatableField, key: &TranslationKey, language: &LanguageTag) -> Option<(&str, Option<&Provenance>)> {
+        let lookup = TranslationLookup {
+            language: language.clone(),
+            field: field.clone(),
+            key: key.clone(),
+        };
+
+        let translation = self.translations.get(&lookup)?;
+        Some((translation.as_ref(), self.provenance.get(&lookup)))
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+    use crate::{from_reader, ConflictPolicy, ParseOptions, StopFields};
+
+    fn parse(rows: &str, source: ProvenanceSource) -> TranslationResult {
+        let options = ParseOptions { provenance: Some(source), ..ParseOptions::default() };
+        from_reader(format!("{}{}", HEADER, rows).as_bytes(), &options).unwrap().0
+    }
+
+    fn layered() -> TranslationResult {
+        let mut agency = parse("stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,fr,Quai,stop_2,,\n", ProvenanceSource::file("translations.txt"));
+        let overrides = parse("stops,stop_name,fr,Gare SNCF,stop_1,,\n", ProvenanceSource {
+            batch_id: Some("2026-10-corrections".to_string()),
+            ..ProvenanceSource::file("overrides.txt")
+        });
+        agency.merge(overrides, &ConflictPolicy::PreferOther).unwrap();
+        agency
+    }
+
+    fn meta<'a>(result: &'a TranslationResult, record_id: &str) -> (&'a str, Option<&'a Provenance>) {
+        result.get_with_meta(&TranslatableField::Stops(StopFields::Name), &TranslationKey::Record(record_id.to_string()), &LanguageTag::parse("fr").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn overrides_bring_their_own_provenance() {
+        let result = layered();
+        let (translation, provenance) = meta(&result, "stop_1");
+        let provenance = provenance.unwrap();
+
+        assert_eq!(translation, "Gare SNCF");
+        assert_eq!((provenance.source_file.as_deref(), provenance.source_row, provenance.batch_id.as_deref()), (Some("overrides.txt"), Some(2), Some("2026-10-corrections")));
+    }
+
+    #[test]
+    fn keeps_the_provenance_of_untouched_rows() {
+        let result = layered();
+        let provenance = meta(&result, "stop_2").1.unwrap();
+
+        assert_eq!(provenance.source_row, Some(3));
+        assert_eq!(provenance.origin, Origin::Human);
+    }
+
+    #[test]
+    fn records_nothing_without_a_source() {
+        let result = crate::test_util::translations("stops,stop_name,fr,Gare,stop_1,,\n");
+
+        assert_eq!(meta(&result, "stop_1"), ("Gare", None));
+        assert!(result.provenance().is_empty());
+    }
+}
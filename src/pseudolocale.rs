@@ -0,0 +1,92 @@
+use language_tags::LanguageTag;
+use crate::universe::TranslationUniverse;
+use crate::{is_url, TranslationResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PseudolocaleOptions {
+    // en-XA is the pseudo-locale Android and ICU use for accented text
+    pub language: LanguageTag,
+    // Padding to add, as a share of the original length. German and Finnish run about 30% longer than English.
+    pub expansion: f32,
+    // Wraps every string in [ ] so that cut off text shows
+    pub brackets: bool,
+    pub accents: bool,
+}
+
+impl Default for PseudolocaleOptions {
+    fn default() -> Self {
+        PseudolocaleOptions {
+            language: LanguageTag::parse("en-XA").expect("en-XA is a valid language tag"),
+            expansion: 0.3,
+            brackets: true,
+            accents: true,
+        }
+    }
+}
+
+fn accent(c: char) -> char {
+    match c {
+        'a' => 'á', 'c' => 'ç', 'e' => 'é', 'i' => 'í', 'n' => 'ñ', 'o' => 'ö', 'u' => 'ü', 'y' => 'ý',
+        'A' => 'Å', 'C' => 'Ç', 'E' => 'É', 'I' => 'Î', 'N' => 'Ñ', 'O' => 'Ø', 'U' => 'Û', 'Y' => 'Ý',
+        _ => c,
+    }
+}
+
+pub fn pseudolocalize(text: &str, options: &PseudolocaleOptions) -> String {
+    let mut pseudo = String::with_capacity(text.len() * 2);
+
+    if options.brackets {
+        pseudo.push('[');
+    }
+
+    pseudo.extend(text.chars().map(|c| if options.accents { accent(c) } else { c }));
+
+    let padding = (text.chars().count() as f32 * options.expansion).ceil().max(0.0) as usize;
+    pseudo.extend(std::iter::repeat_n('~', padding));
+
+    if options.brackets {
+        pseudo.push(']');
+    }
+
+    pseudo
+}
+
+// A translation of every string in the feed into a made up language, for testing user interfaces for
+// truncated text and strings that are not translated at all. writer::to_translations_csv turns it into a translations.txt.
+pub fn generate_pseudolocale(universe: &TranslationUniverse, options: &PseudolocaleOptions) -> TranslationResult {
+    let mut result = TranslationResult::default();
+
+    // links have to keep working, so they are not pseudo-localized
+    for entry in universe.entries.iter().filter(|entry| !is_url(&entry.field)) {
+        result.insert(entry.field.clone(), entry.key(), options.language.clone(), pseudolocalize(&entry.original_value, options));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RouteFields, StopFields, TranslatableField};
+
+    #[test]
+    fn pseudolocalizes_the_universe() {
+        let mut universe = TranslationUniverse::new();
+        universe.push(TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, "Union Station");
+        universe.push(TranslatableField::Routes(RouteFields::Url), Some("route_1"), None, "https://example.com");
+
+        let options = PseudolocaleOptions::default();
+        let result = generate_pseudolocale(&universe, &options);
+
+        assert_eq!(result.translations.len(), 1);
+        assert_eq!(result.languages_as_written(), vec![options.language.clone()]);
+        assert_eq!(result.resolve(&TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, None, &options.language), Some("[Ûñíöñ Státíöñ~~~~]"));
+    }
+
+    #[test]
+    fn expands_without_brackets_or_accents() {
+        let options = PseudolocaleOptions { brackets: false, accents: false, expansion: 0.5, ..PseudolocaleOptions::default() };
+
+        assert_eq!(pseudolocalize("Gare", &options), "Gare~~");
+    }
+}
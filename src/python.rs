@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use language_tags::LanguageTag;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use crate::{enum_to_table_and_field, fallback_languages, from_gtfs_zip_path, from_reader, parse_translations, table_and_field_to_enum, ParseOptions, TranslationResult};
+
+// The Python face of a TranslationResult. maturin builds the module as a cdylib with pyo3/extension-module enabled.
+#[pyclass(name = "Translations", module = "gtfs_translations")]
+pub struct PyTranslations {
+    result: TranslationResult,
+}
+
+#[pymethods]
+impl PyTranslations {
+    // resolve("stops", "stop_name", "fr-CA", record_id="stop_1") falls back to fr like the backend does
+    #[pyo3(signature = (table_name, field_name, language, record_id=None, record_sub_id=None, original_value=None))]
+    pub fn resolve(&self, table_name: &str, field_name: &str, language: &str, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>) -> PyResult<Option<String>> {
+        let field = table_and_field_to_enum(table_name, field_name)
+            .ok_or_else(|| PyValueError::new_err(format!("untranslatable field {}.{}", table_name, field_name)))?;
+        let language = LanguageTag::parse(language).map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+        Ok(fallback_languages(&self.result, &language).iter()
+            .find_map(|language| self.result.resolve(&field, record_id, record_sub_id, original_value, language))
+            .map(String::from))
+    }
+
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages = self.result.languages_as_written().iter().map(|language| language.to_string()).collect::<Vec<String>>();
+        languages.sort();
+        languages
+    }
+
+    // {language: {"table.field": translations}}, which pandas.DataFrame turns into a coverage table
+    pub fn coverage(&self) -> HashMap<String, HashMap<String, usize>> {
+        let mut coverage: HashMap<String, HashMap<String, usize>> = HashMap::new();
+
+        for lookup in self.result.translations.keys() {
+            let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+            *coverage.entry(lookup.language.to_string()).or_default().entry(format!("{}.{}", table_name, field_name)).or_default() += 1;
+        }
+
+        coverage
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.result.translations.len()
+    }
+}
+
+#[pyfunction]
+pub fn parse(csv: &str) -> PyResult<PyTranslations> {
+    parse_translations(csv)
+        .map(|(result, _)| PyTranslations { result })
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+#[pyfunction]
+pub fn parse_file(path: &str) -> PyResult<PyTranslations> {
+    let file = std::fs::File::open(path).map_err(|err| PyIOError::new_err(err.to_string()))?;
+
+    from_reader(file, &ParseOptions::default())
+        .map(|(result, _)| PyTranslations { result })
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+// None when the feed has no translations.txt
+#[pyfunction]
+pub fn parse_zip(path: &str) -> PyResult<Option<PyTranslations>> {
+    from_gtfs_zip_path(path, &ParseOptions::default())
+        .map(|parsed| parsed.map(|parsed| PyTranslations { result: parsed.translations }))
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+#[pymodule]
+fn gtfs_translations(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyTranslations>()?;
+    module.add_function(wrap_pyfunction!(parse, module)?)?;
+    module.add_function(wrap_pyfunction!(parse_file, module)?)?;
+    module.add_function(wrap_pyfunction!(parse_zip, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+
+    fn translations() -> PyTranslations {
+        parse(&format!("{}stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,fr,Quai,stop_2,,\n\
+            routes,route_long_name,de,Blau,route_1,,\n", HEADER)).ok().unwrap()
+    }
+
+    #[test]
+    fn resolves_with_language_fallback() {
+        let translations = translations();
+
+        assert_eq!(translations.__len__(), 3);
+        assert_eq!(translations.resolve("stops", "stop_name", "fr-CA", Some("stop_1"), None, None).ok().flatten().as_deref(), Some("Gare"));
+    }
+
+    #[test]
+    fn rejects_an_unknown_field() {
+        assert!(translations().resolve("stops", "stop_colour", "fr", Some("stop_1"), None, None).is_err());
+    }
+
+    #[test]
+    fn reports_languages_and_coverage() {
+        let translations = translations();
+
+        assert_eq!(translations.languages(), vec!["de".to_string(), "fr".to_string()]);
+        assert_eq!(translations.coverage()["fr"]["stops.stop_name"], 2);
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::{enum_to_table_and_field, RawTranslation, TranslatableField, TranslationResult};
+
+// GTFS-Realtime's TranslatedString, with the fields and names of the protobuf message so converting to and from
+// the prost generated type is a field by field copy, without this crate depending on a protobuf stack
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TranslatedString {
+    pub translation: Vec<Translation>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Translation {
+    pub text: String,
+    // BCP-47 tag, absent for the text in the feed's own language
+    pub language: Option<String>,
+}
+
+impl TranslationResult {
+    // Every translation of one value as a TranslatedString, with the original first and without a language when given,
+    // then the languages in alphabetical order
+    pub fn translated_string(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>) -> TranslatedString {
+        let mut languages = self.languages_as_written().iter().collect::<Vec<_>>();
+        languages.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        let original = original_value.map(|text| Translation { text: text.to_string(), language: None });
+        let translated = languages.into_iter().filter_map(|language| {
+            self.resolve(field, record_id, record_sub_id, original_value, language).map(|text| Translation {
+                text: text.to_string(),
+                language: Some(language.to_string()),
+            })
+        });
+
+        TranslatedString {
+            translation: original.into_iter().chain(translated).collect(),
+        }
+    }
+}
+
+// The tagged translations of a realtime string as rows for the given field and key. Untagged text is the
+// original value rather than a translation, so it only becomes the field_value when none is given.
+pub fn translated_string_to_raw(translated: &TranslatedString, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, field_value: Option<&str>) -> Vec<RawTranslation> {
+    let (table_name, field_name) = enum_to_table_and_field(field);
+    let untagged = translated.translation.iter().find(|translation| translation.language.is_none()).map(|translation| translation.text.as_str());
+    let field_value = field_value.or(if record_id.is_none() { untagged } else { None });
+
+    translated.translation.iter()
+        .filter_map(|translation| Some(RawTranslation {
+            table_name: table_name.to_string(),
+            field_name: field_name.to_string(),
+            language: translation.language.clone()?,
+            translation: translation.text.clone(),
+            record_id: record_id.map(String::from),
+            record_sub_id: record_sub_id.map(String::from),
+            field_value: field_value.map(String::from),
+            extras: HashMap::new(),
+        }))
+        .collect()
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{translate_raw_translations, StopFields};
+
+    fn field() -> TranslatableField {
+        TranslatableField::Stops(StopFields::Name)
+    }
+
+    fn translated() -> TranslatedString {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,de,Bahnhof,,,Station\n\
+            stops,stop_name,nl,Perron,stop_2,,\n").translated_string(&field(), Some("stop_1"), None, Some("Station"))
+    }
+
+    #[test]
+    fn collects_every_language_of_a_record() {
+        // the untagged original first, then the languages in order; stop_2's nl name is not this record's
+        assert_eq!(translated().translation.iter().map(|translation| (translation.language.as_deref(), translation.text.as_str())).collect::<Vec<_>>(),
+            vec![(None, "Station"), (Some("de"), "Bahnhof"), (Some("fr"), "Gare")]);
+    }
+
+    #[test]
+    fn converts_to_rows_keyed_by_the_original() {
+        let rows = translated_string_to_raw(&translated(), &field(), None, None, None);
+
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.field_value.as_deref() == Some("Station")));
+    }
+
+    #[test]
+    fn round_trips_through_realtime() {
+        let ingested = translate_raw_translations(translated_string_to_raw(&translated(), &field(), None, None, None));
+
+        assert_eq!(ingested.translated_string(&field(), Some("stop_9"), None, Some("Station")), TranslatedString {
+            translation: vec![
+                Translation { text: "Station".to_string(), language: None },
+                Translation { text: "Bahnhof".to_string(), language: Some("de".to_string()) },
+                Translation { text: "Gare".to_string(), language: Some("fr".to_string()) },
+            ],
+        });
+    }
+}
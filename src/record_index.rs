@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet};
+use crate::{enum_to_table_and_field, TranslationHasher, TranslationKey, TranslationResult};
+
+// Which records of each table have a translation, so hot paths such as routing or search can skip resolving the
+// many records that have none with one hash probe. Built from a result and not updated by later edits.
+#[derive(Debug, Clone, Default)]
+pub struct RecordIndex {
+    // Record and RecordSub translations per record id
+    records: HashMap<&'static str, HashMap<String, usize, TranslationHasher>>,
+    // Tables with value keys, which may translate any record whose value matches
+    tables_with_values: HashSet<&'static str>,
+}
+
+impl RecordIndex {
+    pub fn new(result: &TranslationResult) -> RecordIndex {
+        let mut index = RecordIndex::default();
+
+        for lookup in result.translations.keys() {
+            let table_name = enum_to_table_and_field(&lookup.field).0;
+            let record_id = match &lookup.key {
+                TranslationKey::Record(record_id) | TranslationKey::RecordSub((record_id, _)) => record_id,
+                TranslationKey::Value(_) => {
+                    index.tables_with_values.insert(table_name);
+                    continue;
+                }
+            };
+
+            let records = index.records.entry(table_name).or_default();
+            match records.get_mut(record_id.as_str()) {
+                Some(count) => *count += 1,
+                None => {
+                    records.insert(record_id.clone(), 1);
+                }
+            }
+        }
+
+        index
+    }
+
+    // False only when resolving any field of the record in any language would find nothing. A table with value keys
+    // always answers true, as its records can't be told apart without their values.
+    pub fn record_has_any_translation(&self, table_name: &str, record_id: &str) -> bool {
+        self.tables_with_values.contains(table_name) || self.translation_count(table_name, record_id) > 0
+    }
+
+    // Translations keyed on the record, over all its fields and languages. For stop_times the record is the trip.
+    pub fn translation_count(&self, table_name: &str, record_id: &str) -> usize {
+        self.records.get(table_name).and_then(|records| records.get(record_id)).copied().unwrap_or_default()
+    }
+
+    pub fn translated_records(&self, table_name: &str) -> usize {
+        self.records.get(table_name).map_or(0, HashMap::len)
+    }
+}
+
+impl TranslationResult {
+    pub fn record_index(&self) -> RecordIndex {
+        RecordIndex::new(self)
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn index() -> RecordIndex {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_desc,fr,Quai A,stop_1,,\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n\
+            stop_times,stop_headsign,fr,Centre,trip_1,3,\n\
+            trips,trip_headsign,fr,Centre-ville,,,Downtown\n").record_index()
+    }
+
+    #[test]
+    fn answers_per_record_existence() {
+        let index = index();
+
+        assert!(index.record_has_any_translation("stops", "stop_1"));
+        assert!(!index.record_has_any_translation("stops", "stop_2"));
+        assert!(index.record_has_any_translation("stop_times", "trip_1"));
+        assert!(!index.record_has_any_translation("routes", "route_1"));
+    }
+
+    #[test]
+    fn assumes_any_record_of_a_table_translated_by_value() {
+        assert!(index().record_has_any_translation("trips", "trip_9"));
+    }
+
+    #[test]
+    fn counts_translations_and_records() {
+        let index = index();
+
+        assert_eq!(index.translation_count("stops", "stop_1"), 3);
+        assert_eq!(index.translation_count("stops", "stop_2"), 0);
+        assert_eq!(index.translated_records("stops"), 1);
+    }
+}
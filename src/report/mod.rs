@@ -0,0 +1,6 @@
+// Notices turned into something to show or hand to other tools
+mod sarif;
+mod text;
+
+pub use sarif::{sarif, SARIF_VERSION};
+pub use text::{render_html, render_text, render_text_with, RenderOptions};
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+use serde_json::{json, Value};
+use crate::{enum_to_table_and_field, key_struct_to_options, Notice, Severity};
+
+pub const SARIF_VERSION: &str = "2.1.0";
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+// A SARIF log of the notices, for code review and CI tools that show static analysis results. artifact_uri is the
+// path of the translations.txt the notices are about, such as feed/translations.txt, which row notices point into.
+// The field, key and language of a notice go in its properties, as SARIF has no place for them.
+pub fn sarif(notices: &[Notice], artifact_uri: &str) -> Value {
+    // one rule per code, at the severity it was first seen with
+    let mut rules = BTreeMap::new();
+    for notice in notices.iter() {
+        rules.entry(notice.code.as_str()).or_insert(notice.severity);
+    }
+    let rule_index = rules.keys().enumerate().map(|(index, code)| (*code, index)).collect::<BTreeMap<&str, usize>>();
+
+    let results = notices.iter().map(|notice| {
+        let mut location = json!({ "physicalLocation": { "artifactLocation": { "uri": artifact_uri } } });
+        if let Some(line) = notice.line {
+            location["physicalLocation"]["region"] = json!({ "startLine": line });
+        }
+
+        let mut result = json!({
+            "ruleId": notice.code,
+            "ruleIndex": rule_index[notice.code.as_str()],
+            "level": level(notice.severity),
+            "message": { "text": notice.message },
+            "locations": [location],
+        });
+
+        let mut properties = serde_json::Map::new();
+        if let Some(field) = &notice.field {
+            let (table_name, field_name) = enum_to_table_and_field(field);
+            properties.insert("table_name".to_string(), json!(table_name));
+            properties.insert("field_name".to_string(), json!(field_name));
+        }
+        if let Some(key) = &notice.key {
+            let (record_id, record_sub_id, field_value) = key_struct_to_options(key);
+            properties.insert("record_id".to_string(), json!(record_id));
+            properties.insert("record_sub_id".to_string(), json!(record_sub_id));
+            properties.insert("field_value".to_string(), json!(field_value));
+        }
+        if let Some(language) = &notice.language {
+            properties.insert("language".to_string(), json!(language));
+        }
+        if !properties.is_empty() {
+            result["properties"] = Value::Object(properties);
+        }
+        if let Some(suggestion) = &notice.suggestion {
+            result["fixes"] = json!([{ "description": { "text": format!("Replace with \"{}\"", suggestion) } }]);
+        }
+
+        result
+    }).collect::<Vec<Value>>();
+
+    let rules = rules.iter().map(|(code, severity)| json!({
+        "id": code,
+        "defaultConfiguration": { "level": level(*severity) },
+    })).collect::<Vec<Value>>();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+    use crate::{from_reader, validate, ParseOptions};
+
+    fn log() -> Value {
+        let data = format!("{}stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,not a language,x,stop_1,,\n\
+            trips,trip_headsign,fr,Downtown,,,Downtown\n", HEADER);
+        let (result, stats) = from_reader(data.as_bytes(), &ParseOptions::default()).unwrap();
+
+        sarif(&validate(&result, &stats), "feed/translations.txt")
+    }
+
+    #[test]
+    fn lists_a_rule_per_notice_code() {
+        let log = log();
+
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(log["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn locates_results_by_line() {
+        let result = &log()["runs"][0]["results"][0];
+
+        assert_eq!(result["level"], "warning");
+        assert_eq!(result["locations"][0]["physicalLocation"]["region"]["startLine"], 3);
+    }
+
+    #[test]
+    fn carries_the_key_of_a_result() {
+        let result = &log()["runs"][0]["results"][1];
+
+        assert_eq!(result["ruleId"], "translation_equals_original");
+        assert_eq!(result["properties"]["field_value"], "Downtown");
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Write;
+use crate::{enum_to_table_and_field, key_struct_to_options, Notice, Severity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    // ANSI colours for terminals
+    pub color: bool,
+    // Notices shown for each code, the rest are only counted
+    pub samples: usize,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions { color: false, samples: 3 }
+    }
+}
+
+// Notices of one code, errors first and then by code
+fn groups(notices: &[Notice]) -> Vec<(&str, Severity, Vec<&Notice>)> {
+    let mut groups: Vec<(&str, Severity, Vec<&Notice>)> = vec![];
+
+    for notice in notices.iter() {
+        match groups.iter_mut().find(|(code, _, _)| *code == notice.code) {
+            Some((_, severity, members)) => {
+                *severity = (*severity).max(notice.severity);
+                members.push(notice);
+            }
+            None => groups.push((notice.code.as_str(), notice.severity, vec![notice])),
+        }
+    }
+
+    groups.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    groups
+}
+
+// Where a notice points: its line, or else the field, record and language it is about
+fn location(notice: &Notice) -> String {
+    if let Some(line) = notice.line {
+        return format!("line {}", line);
+    }
+
+    let mut parts = vec![];
+    if let Some(field) = &notice.field {
+        let (table_name, field_name) = enum_to_table_and_field(field);
+        parts.push(format!("{}.{}", table_name, field_name));
+    }
+    if let Some(key) = &notice.key {
+        parts.push(match key_struct_to_options(key) {
+            (Some(record_id), Some(record_sub_id), _) => format!("{} #{}", record_id, record_sub_id),
+            (Some(record_id), None, _) => record_id,
+            (None, _, field_value) => format!("\"{}\"", field_value.unwrap_or_default()),
+        });
+    }
+    if let Some(language) = &notice.language {
+        parts.push(language.clone());
+    }
+    parts.join(" ")
+}
+
+fn totals(notices: &[Notice]) -> String {
+    let count = |severity: Severity| notices.iter().filter(|notice| notice.severity == severity).count();
+    format!("{} errors, {} warnings, {} info", count(Severity::Error), count(Severity::Warning), count(Severity::Info))
+}
+
+pub fn render_text(notices: &[Notice]) -> String {
+    render_text_with(notices, &RenderOptions::default())
+}
+
+// One block per code with its count and the first few notices, then the totals
+pub fn render_text_with(notices: &[Notice], options: &RenderOptions) -> String {
+    let paint = |text: String, severity: Severity| match (options.color, severity) {
+        (false, _) => text,
+        (true, Severity::Error) => format!("\x1b[1;31m{}\x1b[0m", text),
+        (true, Severity::Warning) => format!("\x1b[1;33m{}\x1b[0m", text),
+        (true, Severity::Info) => format!("\x1b[1;36m{}\x1b[0m", text),
+    };
+    let mut out = String::new();
+
+    for (code, severity, members) in groups(notices) {
+        let _ = writeln!(out, "{} {}", paint(format!("{} [{}]", severity, code), severity), members.len());
+
+        for notice in members.iter().take(options.samples) {
+            let location = location(notice);
+            if location.is_empty() {
+                let _ = writeln!(out, "  {}", notice.message);
+            } else {
+                let _ = writeln!(out, "  {}: {}", location, notice.message);
+            }
+            if let Some(suggestion) = &notice.suggestion {
+                let _ = writeln!(out, "    suggestion: {}", suggestion);
+            }
+        }
+
+        if members.len() > options.samples {
+            let _ = writeln!(out, "  ... and {} more", members.len() - options.samples);
+        }
+        out.push('\n');
+    }
+
+    out.push_str(&totals(notices));
+    out.push('\n');
+    out
+}
+
+// The escapes quick-xml would make, done here so the HTML report does not need the export-xml feature
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+// The same report as a standalone HTML fragment, one collapsible section per code with every notice in a table
+pub fn render_html(notices: &[Notice]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<div class=\"gtfs-translations-report\">");
+    let _ = writeln!(out, "<p>{}</p>", totals(notices));
+
+    for (code, severity, members) in groups(notices) {
+        let _ = writeln!(out, "<details class=\"{}\"><summary>{} [{}] {}</summary>", severity, severity, escape(code), members.len());
+        let _ = writeln!(out, "<table><tr><th>Where</th><th>Message</th><th>Suggestion</th></tr>");
+        for notice in members {
+            let _ = writeln!(out, "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(location(notice).as_str()), escape(notice.message.as_str()), escape(notice.suggestion.as_deref().unwrap_or_default()));
+        }
+        let _ = writeln!(out, "</table></details>");
+    }
+
+    out.push_str("</div>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+    use crate::{from_reader, validate, ParseOptions};
+
+    fn notices() -> Vec<Notice> {
+        let data = format!("{}stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,xx-,a,stop_1,,\n\
+            stops,stop_name,yy-,b,stop_1,,\n\
+            stops,stop_name,zz-,c,stop_1,,\n\
+            trips,trip_headsign,fr,Downtown,,,Downtown\n", HEADER);
+        let (result, stats) = from_reader(data.as_bytes(), &ParseOptions::default()).unwrap();
+
+        validate(&result, &stats)
+    }
+
+    #[test]
+    fn groups_counts_and_samples_notices() {
+        let text = render_text_with(&notices(), &RenderOptions { color: false, samples: 2 });
+        let lines = text.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines[0], "warning [skipped_row] 3");
+        assert!(lines[1].starts_with("  line 3: "));
+        assert_eq!(lines[3], "  ... and 1 more");
+        assert_eq!(lines[5], "info [translation_equals_original] 1");
+        assert_eq!(lines[6], "  trips.trip_headsign \"Downtown\" fr: trips.trip_headsign fr translation of \"Downtown\" is the original value");
+        assert_eq!(lines.last(), Some(&"0 errors, 3 warnings, 1 info"));
+    }
+
+    #[test]
+    fn colors_headings_by_severity() {
+        assert!(render_text_with(&notices(), &RenderOptions { color: true, samples: 1 }).contains("\x1b[1;33mwarning [skipped_row]\x1b[0m"));
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert!(render_html(&notices()).contains("&quot;Downtown&quot; fr"));
+    }
+}
@@ -0,0 +1,71 @@
+// Key precedence and language fallback, in core only so TranslationResult and the embedded lookup answer the same way
+use crate::embedded::EmbeddedKey;
+
+// The keys resolve tries, most specific first: (record_id, record_sub_id), then record_id, then the original
+// field value, which is the precedence the spec gives
+pub(crate) fn resolution_order<'a>(record_id: Option<&'a str>, record_sub_id: Option<&'a str>, original_value: Option<&'a str>) -> impl Iterator<Item = EmbeddedKey<'a>> {
+    let record_sub = match (record_id, record_sub_id) {
+        (Some(record_id), Some(record_sub_id)) => Some(EmbeddedKey::RecordSub(record_id, record_sub_id)),
+        _ => None,
+    };
+
+    [record_sub, record_id.map(EmbeddedKey::Record), original_value.map(EmbeddedKey::Value)].into_iter().flatten()
+}
+
+// The requested language and then each shorter tag it falls back to, longest first, so fr-CA tries fr next.
+// This is RFC 4647 lookup, which never leaves a singleton such as the x of private use dangling at the end.
+pub(crate) fn fallback_chain(language: &str) -> impl Iterator<Item = &str> {
+    let mut next = Some(language);
+
+    core::iter::from_fn(move || {
+        let language = next?;
+        next = shorten(language);
+        Some(language)
+    })
+}
+
+fn shorten(language: &str) -> Option<&str> {
+    let mut range = &language[..language.rfind('-')?];
+
+    while let Some(end) = range.rfind('-').filter(|end| range.len() - end == 2) {
+        range = &range[..end];
+    }
+
+    Some(range)
+}
+
+// The lookup of both resolve_with_fallback: every key in resolution_order for the requested language, then for each
+// shorter tag in turn, so a translation for fr-CA by value beats one for fr by record. lookup gets the tag to try.
+pub(crate) fn resolve_with_fallback<'a, T>(language: &str, record_id: Option<&'a str>, record_sub_id: Option<&'a str>, original_value: Option<&'a str>, mut lookup: impl FnMut(&str, EmbeddedKey<'a>) -> Option<T>) -> Option<T> {
+    fallback_chain(language).find_map(|language| resolution_order(record_id, record_sub_id, original_value).find_map(|key| lookup(language, key)))
+}
+
+// Whether a translation in the available language answers a lookup for the requested one. Tags compare without case.
+#[cfg(feature = "std")]
+pub(crate) fn falls_back_to(requested: &str, available: &str) -> bool {
+    fallback_chain(requested).any(|language| language.eq_ignore_ascii_case(available))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn orders_keys_and_languages() {
+        let keys = resolution_order(Some("trip_1"), Some("3"), Some("Downtown")).collect::<Vec<EmbeddedKey<'_>>>();
+        assert_eq!(keys, [EmbeddedKey::RecordSub("trip_1", "3"), EmbeddedKey::Record("trip_1"), EmbeddedKey::Value("Downtown")]);
+        assert_eq!(resolution_order(None, Some("3"), None).count(), 0);
+
+        #[cfg(feature = "std")]
+        {
+            assert!(falls_back_to("fr-CA", "FR"));
+            assert!(falls_back_to("fr", "fr"));
+            assert!(!falls_back_to("fra", "fr"));
+            assert!(!falls_back_to("fr", "fr-CA"));
+        }
+
+        assert_eq!(fallback_chain("zh-Hant-TW").collect::<Vec<&str>>(), ["zh-Hant-TW", "zh-Hant", "zh"]);
+        assert_eq!(fallback_chain("fr-x-a-b").collect::<Vec<&str>>(), ["fr-x-a-b", "fr"]);
+    }
+}
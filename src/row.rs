@@ -0,0 +1,159 @@
+use language_tags::LanguageTag;
+use crate::resolution::falls_back_to;
+use crate::{TranslatableField, TranslationResult};
+
+// Lets translate_row work on any struct that stands for a row of a GTFS table, such as a consumer's own stop model
+pub trait TranslateRow {
+    // The translatable fields this row has
+    fn fields(&self) -> &[TranslatableField];
+
+    // record_id and record_sub_id as translations.txt refers to this row, e.g. (trip_id, stop_sequence) for stop_times
+    fn record_key(&self) -> (Option<&str>, Option<&str>);
+
+    // The current value, which value keyed translations match on
+    fn field(&self, field: &TranslatableField) -> Option<&str>;
+
+    fn set_field(&mut self, field: &TranslatableField, value: &str);
+}
+
+// The languages to try for a requested one: itself, then the shorter tags it falls back to that the set has,
+// so fr-CA uses fr where there is no Canadian French translation
+pub fn fallback_languages(result: &TranslationResult, language: &LanguageTag) -> Vec<LanguageTag> {
+    let mut languages = result.languages_as_written().iter()
+        .filter(|available| falls_back_to(language.as_str(), available.as_str()))
+        .cloned()
+        .collect::<Vec<LanguageTag>>();
+
+    languages.sort_by_key(|available| std::cmp::Reverse(available.as_str().len()));
+    languages
+}
+
+// TranslationResult::resolve over fallback_languages for one target language, which is worked out once for a run over
+// many rows. translate_row, apply_gtfs and localize_feed all translate through it, counting what they replaced.
+pub(crate) struct RowTranslator<'a> {
+    result: &'a TranslationResult,
+    languages: Vec<LanguageTag>,
+    pub(crate) replaced: usize,
+}
+
+impl<'a> RowTranslator<'a> {
+    pub(crate) fn new(result: &'a TranslationResult, language: &LanguageTag) -> RowTranslator<'a> {
+        RowTranslator { result, languages: fallback_languages(result, language), replaced: 0 }
+    }
+
+    pub(crate) fn resolve(&self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: Option<&str>) -> Option<&'a str> {
+        self.languages.iter().find_map(|language| self.result.resolve(field, record_id, record_sub_id, original_value, language))
+    }
+
+    // Replaces the value by its translation when there is one. An empty value is not matched against value keys.
+    #[cfg(any(feature = "apply", feature = "zip"))]
+    pub(crate) fn replace(&mut self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, value: &mut String) {
+        if let Some(translation) = self.resolve(field, record_id, record_sub_id, Some(value.as_str()).filter(|value| !value.is_empty())) {
+            *value = translation.to_string();
+            self.replaced += 1;
+        }
+    }
+
+    // Values the row leaves out can still be translated by record
+    #[cfg(feature = "apply")]
+    pub(crate) fn replace_option(&mut self, field: &TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, value: &mut Option<String>) {
+        if let Some(translation) = self.resolve(field, record_id, record_sub_id, value.as_deref()) {
+            *value = Some(translation.to_string());
+            self.replaced += 1;
+        }
+    }
+}
+
+// Translates every field of the row, with the key precedence of TranslationResult::resolve and language fallback.
+// Returns how many fields were set.
+pub fn translate_row<T: TranslateRow + ?Sized>(row: &mut T, result: &TranslationResult, language: &LanguageTag) -> usize {
+    let mut translator = RowTranslator::new(result, language);
+    let fields = row.fields().to_vec();
+
+    for field in fields.iter() {
+        let (record_id, record_sub_id) = row.record_key();
+
+        if let Some(translation) = translator.resolve(field, record_id, record_sub_id, row.field(field)).map(String::from) {
+            row.set_field(field, &translation);
+            translator.replaced += 1;
+        }
+    }
+
+    translator.replaced
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::StopFields;
+
+    struct Stop {
+        id: String,
+        name: String,
+        code: Option<String>,
+    }
+
+    const STOP_FIELDS: [TranslatableField; 2] = [TranslatableField::Stops(StopFields::Name), TranslatableField::Stops(StopFields::Code)];
+
+    impl TranslateRow for Stop {
+        fn fields(&self) -> &[TranslatableField] {
+            &STOP_FIELDS
+        }
+
+        fn record_key(&self) -> (Option<&str>, Option<&str>) {
+            (Some(&self.id), None)
+        }
+
+        fn field(&self, field: &TranslatableField) -> Option<&str> {
+            match field {
+                TranslatableField::Stops(StopFields::Name) => Some(&self.name),
+                TranslatableField::Stops(StopFields::Code) => self.code.as_deref(),
+                _ => None,
+            }
+        }
+
+        fn set_field(&mut self, field: &TranslatableField, value: &str) {
+            match field {
+                TranslatableField::Stops(StopFields::Name) => self.name = value.to_string(),
+                TranslatableField::Stops(StopFields::Code) => self.code = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,fr-CA,Gare d'autocars,stop_1,,\n\
+            stops,stop_code,fr,G1,,,S1\n")
+    }
+
+    fn stop(code: Option<&str>) -> Stop {
+        Stop { id: "stop_1".to_string(), name: "Station".to_string(), code: code.map(str::to_string) }
+    }
+
+    #[test]
+    fn translates_every_field_of_a_row() {
+        let mut stop = stop(Some("S1"));
+
+        assert_eq!(translate_row(&mut stop, &result(), &LanguageTag::parse("fr-CA").unwrap()), 2);
+        assert_eq!(stop.name, "Gare d'autocars");
+        assert_eq!(stop.code.as_deref(), Some("G1"));
+    }
+
+    #[test]
+    fn falls_back_to_the_shorter_language() {
+        let mut stop = stop(None);
+
+        assert_eq!(translate_row(&mut stop, &result(), &LanguageTag::parse("fr-BE").unwrap()), 1);
+        assert_eq!(stop.name, "Gare");
+    }
+
+    #[test]
+    fn leaves_rows_without_a_translation_alone() {
+        let mut stop = stop(Some("S1"));
+
+        assert_eq!(translate_row(&mut stop, &result(), &LanguageTag::parse("de").unwrap()), 0);
+        assert_eq!((stop.name.as_str(), stop.code.as_deref()), ("Station", Some("S1")));
+    }
+}
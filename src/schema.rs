@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{RootSchema, Schema};
+use schemars::{schema_for, JsonSchema};
+use crate::serialization::SerializedTranslationResult;
+use crate::{Notice, ParseStats, RawTranslation, TranslationResult};
+
+// TranslationResult serializes as SerializedTranslationResult, so that is what its schema describes
+impl JsonSchema for TranslationResult {
+    fn schema_name() -> String {
+        "TranslationResult".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        SerializedTranslationResult::json_schema(gen)
+    }
+}
+
+// JSON Schemas of the types that leave the crate as JSON, by type name, for generating and checking
+// types in other languages
+pub fn json_schemas() -> BTreeMap<&'static str, RootSchema> {
+    BTreeMap::from([
+        ("Notice", schema_for!(Notice)),
+        ("ParseStats", schema_for!(ParseStats)),
+        ("RawTranslation", schema_for!(RawTranslation)),
+        ("TranslationResult", schema_for!(TranslationResult)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn result_schema() -> serde_json::Value {
+        serde_json::to_value(&json_schemas()["TranslationResult"]).unwrap()
+    }
+
+    #[test]
+    fn describes_serialized_result() {
+        let schema = result_schema();
+
+        assert_eq!(schema["title"], "TranslationResult");
+        assert!(schema["properties"]["translations"].is_object());
+        assert!(schema["definitions"]["Provenance"].is_object());
+    }
+
+    #[test]
+    fn leaves_extras_out_of_rows() {
+        assert!(serde_json::to_value(&json_schemas()["RawTranslation"]).unwrap()["properties"].get("extras").is_none());
+    }
+
+    #[test]
+    fn requires_only_what_is_serialized() {
+        let json = serde_json::to_value(translations("stops,stop_name,fr,Gare,stop_1,,\n")).unwrap();
+
+        for required in result_schema()["required"].as_array().unwrap() {
+            assert!(json.get(required.as_str().unwrap()).is_some());
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use language_tags::LanguageTag;
+use crate::validate::sort_notices;
+use crate::{enum_to_table_and_field, Notice, Severity, TranslationResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Bengali,
+    Thai,
+    Georgian,
+    Ethiopic,
+    Hangul,
+    Kana,
+    Han,
+}
+
+use Script::*;
+
+fn script_of(character: char) -> Option<Script> {
+    match character {
+        'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' => Some(Latin),
+        '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' => Some(Greek),
+        '\u{0400}'..='\u{052F}' => Some(Cyrillic),
+        '\u{0530}'..='\u{058F}' => Some(Armenian),
+        '\u{0590}'..='\u{05FF}' | '\u{FB1D}'..='\u{FB4F}' => Some(Hebrew),
+        '\u{0600}'..='\u{06FF}' | '\u{0750}'..='\u{077F}' | '\u{08A0}'..='\u{08FF}' | '\u{FB50}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}' => Some(Arabic),
+        '\u{0900}'..='\u{097F}' => Some(Devanagari),
+        '\u{0980}'..='\u{09FF}' => Some(Bengali),
+        '\u{0E00}'..='\u{0E7F}' => Some(Thai),
+        '\u{10A0}'..='\u{10FF}' => Some(Georgian),
+        '\u{1200}'..='\u{139F}' => Some(Ethiopic),
+        '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => Some(Hangul),
+        '\u{3040}'..='\u{30FF}' | '\u{FF66}'..='\u{FF9F}' => Some(Kana),
+        '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' => Some(Han),
+        _ => None,
+    }
+}
+
+fn scripts_of_subtag(script: &str) -> Option<&'static [Script]> {
+    match script.to_ascii_lowercase().as_str() {
+        "latn" => Some(&[Latin]),
+        "grek" => Some(&[Greek]),
+        "cyrl" => Some(&[Cyrillic]),
+        "arab" => Some(&[Arabic]),
+        "hebr" => Some(&[Hebrew]),
+        "hans" | "hant" | "hani" => Some(&[Han]),
+        "jpan" => Some(&[Han, Kana]),
+        "kore" => Some(&[Hangul, Han]),
+        _ => None,
+    }
+}
+
+// The scripts a language is usually written in. Languages that are not listed are not checked.
+fn scripts_of_language(language: &str) -> Option<&'static [Script]> {
+    match language {
+        "en" | "fr" | "de" | "es" | "it" | "pt" | "nl" | "ca" | "eu" | "gl" | "da" | "sv" | "nb" | "nn" | "no" | "fi" | "is" | "et" | "lv" | "lt"
+        | "pl" | "cs" | "sk" | "sl" | "hr" | "bs" | "hu" | "ro" | "sq" | "tr" | "az" | "vi" | "id" | "ms" | "tl" | "fil" | "sw" | "ga" | "cy"
+        | "mt" | "lb" | "rm" | "br" | "oc" | "eo" | "mi" | "haw" | "qu" | "gn" => Some(&[Latin]),
+        // Serbian is written in both
+        "sr" => Some(&[Cyrillic, Latin]),
+        "ru" | "uk" | "be" | "bg" | "mk" | "kk" | "ky" | "tg" | "mn" => Some(&[Cyrillic]),
+        "el" => Some(&[Greek]),
+        "hy" => Some(&[Armenian]),
+        "he" | "iw" | "yi" => Some(&[Hebrew]),
+        "ar" | "fa" | "ur" | "ps" | "ckb" | "sd" | "ug" => Some(&[Arabic]),
+        "hi" | "mr" | "ne" => Some(&[Devanagari]),
+        "bn" | "as" => Some(&[Bengali]),
+        "th" => Some(&[Thai]),
+        "ka" => Some(&[Georgian]),
+        "am" | "ti" => Some(&[Ethiopic]),
+        "ko" => Some(&[Hangul, Han]),
+        "ja" => Some(&[Han, Kana]),
+        "zh" | "yue" => Some(&[Han]),
+        _ => None,
+    }
+}
+
+fn expected_scripts(language: &LanguageTag) -> Option<&'static [Script]> {
+    match language.script() {
+        Some(script) => scripts_of_subtag(script),
+        None => scripts_of_language(&language.primary_language().to_ascii_lowercase()),
+    }
+}
+
+// Below this many letters a text is too short to tell, and is often a code such as M1 whatever the language
+const MIN_LETTERS: usize = 4;
+
+// Flags translations written mostly in a script their language is not written in, such as a ja row in Latin
+// letters or Russian text tagged en. Letters of the expected scripts only have to make up half of the text,
+// so route numbers and names in another script inside a translation are fine.
+pub fn check_scripts(result: &TranslationResult) -> Vec<Notice> {
+    let mut notices = vec![];
+
+    for (lookup, translation) in result.translations.iter() {
+        let Some(expected) = expected_scripts(&lookup.language) else {
+            continue;
+        };
+
+        let scripts = translation.chars().filter_map(script_of).collect::<Vec<Script>>();
+        let matching = scripts.iter().filter(|script| expected.contains(script)).count();
+
+        if scripts.len() < MIN_LETTERS || matching * 2 >= scripts.len() {
+            continue;
+        }
+
+        // the script most of the other letters are in, for the message
+        let dominant = scripts.iter()
+            .filter(|script| !expected.contains(script))
+            .max_by_key(|script| scripts.iter().filter(|other| other == script).count())
+            .copied();
+
+        let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+        let written_in = dominant.map(|script| format!(" is mostly {:?} script", script)).unwrap_or_default();
+        notices.push(Notice::about(lookup, "script_mismatch", Severity::Warning,
+            format!("{}.{} {} translation \"{}\"{}, unusual for {}", table_name, field_name, lookup.language, translation, written_in, lookup.language)));
+    }
+
+    sort_notices(&mut notices);
+    notices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn flagged(rows: &str) -> Vec<Notice> {
+        check_scripts(&translations(rows))
+    }
+
+    #[test]
+    fn flags_latin_text_in_japanese() {
+        let notices = flagged("stops,stop_name,ja,Tokyo Station,stop_1,,\nstops,stop_name,ja,JR\u{6771}\u{4EAC}\u{99C5},stop_2,,\n");
+
+        // a few Latin letters among the kanji are fine
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].language.as_deref(), Some("ja"));
+    }
+
+    #[test]
+    fn names_the_script_found() {
+        let notices = flagged("stops,stop_name,en,\u{0412}\u{043E}\u{043A}\u{0437}\u{0430}\u{043B},stop_3,,\n");
+
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].message.contains("mostly Cyrillic script"));
+    }
+
+    #[test]
+    fn accepts_explicit_scripts_codes_and_unknown_languages() {
+        assert!(flagged("stops,stop_name,ja-Latn,Tokyo Eki,stop_4,,\n\
+            stops,stop_name,ko,M1,stop_5,,\n\
+            stops,stop_name,sr,Beograd,stop_6,,\n\
+            stops,stop_name,xx,\u{0412}\u{043E}\u{043A}\u{0437}\u{0430}\u{043B},stop_7,,\n").is_empty());
+    }
+}
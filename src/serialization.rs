@@ -0,0 +1,247 @@
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize, Serializer};
+use crate::memory::TextPool;
+use crate::{enum_to_table_and_field, key_options_to_struct, table_and_field_to_enum};
+use crate::{Provenance, TranslatableField, TranslationHasher, TranslationKey, TranslationLookup, TranslationMap, TranslationResult};
+
+// Bump whenever the layout of SerializedTranslationResult changes so cached copies are rejected instead of misread.
+pub const SERIALIZATION_VERSION: u32 = 2;
+
+// Language tags are stored as strings and fields as their gtfs table/column names,
+// so the format does not depend on the Rust enum layout.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SerializedTranslationResult {
+    pub version: u32,
+    pub avaliable_languages: Vec<String>,
+    pub possible_translations: Vec<SerializedField>,
+    pub translations: Vec<SerializedTranslation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SerializedField {
+    pub table_name: String,
+    pub field_name: String,
+    pub language: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SerializedTranslation {
+    pub table_name: String,
+    pub field_name: String,
+    pub language: String,
+    pub record_id: Option<String>,
+    pub record_sub_id: Option<String>,
+    pub field_value: Option<String>,
+    pub translation: String,
+    pub provenance: Option<Provenance>,
+}
+
+fn field_from_names(table_name: &str, field_name: &str) -> Result<TranslatableField, String> {
+    table_and_field_to_enum(table_name, field_name)
+        .ok_or_else(|| format!("unknown translatable field {}.{}", table_name, field_name))
+}
+
+fn language_from_str(language: &str) -> Result<LanguageTag, String> {
+    LanguageTag::parse(language).map_err(|err| format!("invalid language tag {}: {}", language, err))
+}
+
+// The same layout borrowed from a TranslationResult, which is what serializing it writes, so the map is not
+// cloned to be written out
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SerializedTranslationResultRef<'a> {
+    pub version: u32,
+    pub avaliable_languages: Vec<&'a str>,
+    pub possible_translations: Vec<SerializedFieldRef<'a>>,
+    pub translations: Vec<SerializedTranslationRef<'a>>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SerializedFieldRef<'a> {
+    pub table_name: &'a str,
+    pub field_name: &'a str,
+    pub language: &'a str,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SerializedTranslationRef<'a> {
+    pub table_name: &'a str,
+    pub field_name: &'a str,
+    pub language: &'a str,
+    pub record_id: Option<&'a str>,
+    pub record_sub_id: Option<&'a str>,
+    pub field_value: Option<&'a str>,
+    pub translation: &'a str,
+    pub provenance: Option<&'a Provenance>,
+}
+
+impl<'a> From<&'a TranslationResult> for SerializedTranslationResultRef<'a> {
+    fn from(result: &'a TranslationResult) -> Self {
+        let mut avaliable_languages = result.languages_as_written().iter().map(|language| language.as_str()).collect::<Vec<&str>>();
+        avaliable_languages.sort();
+
+        let mut possible_translations = result.possible_translations.iter().map(|(field, language)| {
+            let (table_name, field_name) = enum_to_table_and_field(field);
+            SerializedFieldRef { table_name, field_name, language: language.as_str() }
+        }).collect::<Vec<SerializedFieldRef>>();
+        possible_translations.sort_by_key(|field| (field.table_name, field.field_name, field.language));
+
+        let mut translations = result.translations.iter().map(|(lookup, translation)| {
+            let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+            let (record_id, record_sub_id, field_value) = match &lookup.key {
+                TranslationKey::Record(record_id) => (Some(record_id.as_str()), None, None),
+                TranslationKey::RecordSub((record_id, record_sub_id)) => (Some(record_id.as_str()), Some(record_sub_id.as_str()), None),
+                TranslationKey::Value(field_value) => (None, None, Some(field_value.as_str())),
+            };
+            SerializedTranslationRef {
+                table_name,
+                field_name,
+                language: lookup.language.as_str(),
+                record_id,
+                record_sub_id,
+                field_value,
+                translation,
+                provenance: result.provenance.get(lookup),
+            }
+        }).collect::<Vec<SerializedTranslationRef>>();
+        translations.sort_by_key(|item| (item.table_name, item.field_name, item.language, item.record_id, item.record_sub_id, item.field_value));
+
+        SerializedTranslationResultRef {
+            version: SERIALIZATION_VERSION,
+            avaliable_languages,
+            possible_translations,
+            translations,
+        }
+    }
+}
+
+impl From<SerializedTranslationResultRef<'_>> for SerializedTranslationResult {
+    fn from(serialized: SerializedTranslationResultRef<'_>) -> Self {
+        SerializedTranslationResult {
+            version: serialized.version,
+            avaliable_languages: serialized.avaliable_languages.into_iter().map(String::from).collect(),
+            possible_translations: serialized.possible_translations.into_iter().map(|item| SerializedField {
+                table_name: item.table_name.to_string(),
+                field_name: item.field_name.to_string(),
+                language: item.language.to_string(),
+            }).collect(),
+            translations: serialized.translations.into_iter().map(|item| SerializedTranslation {
+                table_name: item.table_name.to_string(),
+                field_name: item.field_name.to_string(),
+                language: item.language.to_string(),
+                record_id: item.record_id.map(String::from),
+                record_sub_id: item.record_sub_id.map(String::from),
+                field_value: item.field_value.map(String::from),
+                translation: item.translation.to_string(),
+                provenance: item.provenance.cloned(),
+            }).collect(),
+        }
+    }
+}
+
+impl From<TranslationResult> for SerializedTranslationResult {
+    fn from(result: TranslationResult) -> Self {
+        SerializedTranslationResult::from(SerializedTranslationResultRef::from(&result))
+    }
+}
+
+impl Serialize for TranslationResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedTranslationResultRef::from(self).serialize(serializer)
+    }
+}
+
+impl TryFrom<SerializedTranslationResult> for TranslationResult {
+    type Error = String;
+
+    fn try_from(serialized: SerializedTranslationResult) -> Result<Self, Self::Error> {
+        if serialized.version != SERIALIZATION_VERSION {
+            return Err(format!("unsupported serialization version {}, expected {}", serialized.version, SERIALIZATION_VERSION));
+        }
+
+        let mut avaliable_languages = serialized.avaliable_languages.iter()
+            .map(|language| language_from_str(language))
+            .collect::<Result<Vec<LanguageTag>, String>>()?;
+        avaliable_languages.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        let possible_translations = serialized.possible_translations.iter()
+            .map(|item| Ok((field_from_names(&item.table_name, &item.field_name)?, language_from_str(&item.language)?)))
+            .collect::<Result<Vec<(TranslatableField, LanguageTag)>, String>>()?;
+
+        let mut translations = TranslationMap::with_capacity_and_hasher(serialized.translations.len(), TranslationHasher::default());
+        let mut provenance = TranslationMap::default();
+        let mut texts = TextPool::default();
+
+        for item in serialized.translations {
+            let key = key_options_to_struct(item.record_id, item.record_sub_id, item.field_value)
+                .ok_or_else(|| format!("translation for {}.{} has no record_id or field_value", item.table_name, item.field_name))?;
+
+            let lookup = TranslationLookup {
+                language: language_from_str(&item.language)?,
+                field: field_from_names(&item.table_name, &item.field_name)?,
+                key,
+            };
+
+            if let Some(item_provenance) = item.provenance {
+                provenance.insert(lookup.clone(), item_provenance);
+            }
+
+            translations.insert(lookup, texts.share(&item.translation));
+        }
+
+        #[allow(deprecated)]
+        let mut result = TranslationResult {
+            avaliable_languages,
+            translations,
+            possible_translations,
+            provenance,
+            key_counts: Default::default(),
+        };
+        // derived from the keys, so it is not part of the format
+        result.recompute_key_counts();
+
+        Ok(result)
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare Centrale,stop_1,,\n\
+            stop_times,stop_headsign,de,Zentrum,trip_1,5,\n\
+            routes,route_long_name,ja,中央線,,,Chuo Line\n")
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let result = result();
+        let json = serde_json::to_string(&result).unwrap();
+        let parsed: TranslationResult = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.translations, result.translations);
+        assert_eq!(parsed.languages_as_written().len(), 3);
+        assert_eq!(parsed.possible_translations.len(), 3);
+        assert!(json.starts_with("{\"version\":2,"));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let json = serde_json::to_string(&result()).unwrap().replacen("{\"version\":2,", "{\"version\":1,", 1);
+        let err = serde_json::from_str::<TranslationResult>(&json).unwrap_err();
+
+        assert!(err.to_string().contains("unsupported serialization version 1"));
+    }
+
+    #[test]
+    fn borrowed_and_owned_layouts_match() {
+        let result = result();
+        let owned = SerializedTranslationResult::from(result.clone());
+        assert_eq!(serde_json::to_value(&result).unwrap(), serde_json::to_value(&owned).unwrap());
+        assert_eq!(bincode::serialize(&result).unwrap(), bincode::serialize(&owned).unwrap());
+    }
+}
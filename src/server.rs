@@ -0,0 +1,102 @@
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize};
+use crate::{fallback_languages, table_and_field_to_enum, SharedTranslations};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranslateQuery {
+    pub table: String,
+    pub field: String,
+    pub record_id: Option<String>,
+    pub record_sub_id: Option<String>,
+    // The original text, for value-keyed translations
+    pub field_value: Option<String>,
+    pub lang: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranslateResponse {
+    pub translation: String,
+    // The language that answered, which is fr for a request for fr-CA without its own translation
+    pub language: String,
+}
+
+async fn translate(State(shared): State<SharedTranslations>, Query(query): Query<TranslateQuery>) -> Result<Json<TranslateResponse>, (StatusCode, String)> {
+    let field = table_and_field_to_enum(&query.table, &query.field)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("{}.{} is not translatable", query.table, query.field)))?;
+    let language = LanguageTag::parse(&query.lang)
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+
+    // held for the whole request, so a reload halfway through doesn't mix versions
+    let translations = shared.load();
+
+    fallback_languages(&translations, &language).iter()
+        .find_map(|language| {
+            translations.resolve(&field, query.record_id.as_deref(), query.record_sub_id.as_deref(), query.field_value.as_deref(), language)
+                .map(|translation| TranslateResponse { translation: translation.to_string(), language: language.to_string() })
+        })
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "no translation".to_string()))
+}
+
+async fn languages(State(shared): State<SharedTranslations>) -> Json<Vec<String>> {
+    let mut languages = shared.load().languages_as_written().iter().map(|language| language.to_string()).collect::<Vec<String>>();
+    languages.sort();
+    Json(languages)
+}
+
+// GET /translate?table=stops&field=stop_name&record_id=X&lang=fr and GET /languages. Swapping the
+// SharedTranslations behind the router reloads the feed without restarting the service.
+pub fn router(shared: SharedTranslations) -> Router {
+    Router::new()
+        .route("/translate", get(translate))
+        .route("/languages", get(languages))
+        .with_state(shared)
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn shared() -> SharedTranslations {
+        SharedTranslations::new(translations("stops,stop_name,fr,Gare,stop_1,,\n"))
+    }
+
+    fn query(field: &str, record_id: &str, lang: &str) -> Query<TranslateQuery> {
+        Query(TranslateQuery {
+            table: "stops".to_string(),
+            field: field.to_string(),
+            record_id: Some(record_id.to_string()),
+            record_sub_id: None,
+            field_value: None,
+            lang: lang.to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn answers_with_fallback() {
+        let Json(found) = translate(State(shared()), query("stop_name", "stop_1", "fr-CA")).await.unwrap();
+
+        assert_eq!(found, TranslateResponse { translation: "Gare".to_string(), language: "fr".to_string() });
+    }
+
+    #[tokio::test]
+    async fn answers_not_found_without_a_translation() {
+        assert_eq!(translate(State(shared()), query("stop_name", "stop_2", "fr")).await.unwrap_err().0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_queries() {
+        assert_eq!(translate(State(shared()), query("stop_colour", "stop_1", "fr")).await.unwrap_err().0, StatusCode::BAD_REQUEST);
+        assert_eq!(translate(State(shared()), query("stop_name", "stop_1", "not a language")).await.unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn lists_languages() {
+        assert_eq!(languages(State(shared())).await.0, vec!["fr".to_string()]);
+    }
+}
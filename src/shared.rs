@@ -0,0 +1,55 @@
+use std::sync::{Arc, RwLock};
+use crate::TranslationResult;
+
+// A handle to the current translations that web handlers can clone freely. Readers take an Arc of the
+// version that is live when they ask and keep it for the whole request, while a reload swaps the pointer
+// without copying any maps or blocking readers for longer than the swap itself.
+#[derive(Debug, Clone, Default)]
+pub struct SharedTranslations {
+    current: Arc<RwLock<Arc<TranslationResult>>>,
+}
+
+impl SharedTranslations {
+    pub fn new(translations: TranslationResult) -> SharedTranslations {
+        SharedTranslations {
+            current: Arc::new(RwLock::new(Arc::new(translations))),
+        }
+    }
+
+    pub fn load(&self) -> Arc<TranslationResult> {
+        // a writer that panicked mid-swap cannot leave a half written Arc behind, so poisoning is ignored
+        self.current.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    // Installs a new feed version and returns the previous one, which stays valid for readers still holding it
+    pub fn swap(&self, translations: TranslationResult) -> Arc<TranslationResult> {
+        let mut current = self.current.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::mem::replace(&mut *current, Arc::new(translations))
+    }
+}
+
+impl From<TranslationResult> for SharedTranslations {
+    fn from(translations: TranslationResult) -> Self {
+        SharedTranslations::new(translations)
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    #[test]
+    fn swaps_across_threads() {
+        let shared = SharedTranslations::new(translations("stops,stop_name,fr,Gare,stop_1,,\n"));
+        let held = shared.load();
+
+        let handle = shared.clone();
+        let v2 = translations("stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,fr,Quai,stop_2,,\n");
+        std::thread::spawn(move || handle.swap(v2)).join().unwrap();
+
+        // a reader keeps the version it loaded
+        assert_eq!(held.translations.len(), 1);
+        assert_eq!(shared.load().translations.len(), 2);
+    }
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use crate::serialization::{SerializedTranslationResult, SerializedTranslationResultRef};
+use crate::TranslationResult;
+
+// First byte of every snapshot. Readers refuse snapshots written with a different layout.
+pub const SNAPSHOT_FORMAT_VERSION: u8 = 1;
+
+// Snapshots are bincode of the serialization layout, written from a borrowed view of the result. A zero-copy
+// format such as rkyv would let services read translations straight out of the snapshot bytes, but it archives
+// the Rust types as they are laid out, which the serialization layout keeps out of the format on purpose.
+// MappedTranslations, behind the mmap feature, is the way to serve a large set without decoding it.
+
+impl TranslationResult {
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        let serialized = SerializedTranslationResultRef::from(self);
+
+        let mut bytes = vec![SNAPSHOT_FORMAT_VERSION];
+        bincode::serialize_into(&mut bytes, &serialized).expect("serializing into a Vec cannot fail");
+        bytes
+    }
+
+    pub fn from_snapshot(bytes: &[u8]) -> Result<TranslationResult, Box<dyn Error>> {
+        let (version, body) = bytes.split_first().ok_or("empty snapshot")?;
+
+        if *version != SNAPSHOT_FORMAT_VERSION {
+            return Err(format!("unsupported snapshot format version {}, expected {}", version, SNAPSHOT_FORMAT_VERSION).into());
+        }
+
+        let serialized: SerializedTranslationResult = bincode::deserialize(body)?;
+
+        Ok(TranslationResult::try_from(serialized)?)
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use crate::test_util::translations;
+    use crate::TranslationResult;
+
+    fn result() -> TranslationResult {
+        translations("stops,stop_name,fr,Gare Centrale,stop_1,,\ntrips,trip_headsign,es,Centro,,,Downtown\n")
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let result = result();
+        let snapshot = result.to_snapshot();
+
+        assert_eq!(snapshot[0], super::SNAPSHOT_FORMAT_VERSION);
+        assert_eq!(TranslationResult::from_snapshot(&snapshot).unwrap().translations, result.translations);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut wrong_version = result().to_snapshot();
+        wrong_version[0] = 0;
+
+        assert!(TranslationResult::from_snapshot(&wrong_version).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_and_truncated_snapshots() {
+        let snapshot = result().to_snapshot();
+
+        assert!(TranslationResult::from_snapshot(&[]).is_err());
+        assert!(TranslationResult::from_snapshot(&snapshot[..snapshot.len() / 2]).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::{enum_to_table_and_field, ParseStats, TranslationKey, TranslationResult};
+
+// How many languages TranslationSummary::top_languages lists
+const TOP_LANGUAGES: usize = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct KeyTypeCounts {
+    pub record: usize,
+    pub record_sub: usize,
+    pub value: usize,
+}
+
+// An overview of a translation set for dashboards, counted in one pass over the translations
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TranslationSummary {
+    pub translations: usize,
+    pub by_table: BTreeMap<String, usize>,
+    pub by_language: BTreeMap<String, usize>,
+    pub by_key_type: KeyTypeCounts,
+    // The languages with the most translations, most first and then by name
+    pub top_languages: Vec<(String, usize)>,
+    // From the ParseStats of the file, with summary_with_stats
+    pub rows_read: Option<usize>,
+    pub rows_skipped: Option<usize>,
+    pub duplicate_rows: Option<usize>,
+}
+
+impl TranslationResult {
+    pub fn summary(&self) -> TranslationSummary {
+        let mut summary = TranslationSummary {
+            translations: self.translations.len(),
+            ..TranslationSummary::default()
+        };
+
+        for lookup in self.translations.keys() {
+            *summary.by_table.entry(enum_to_table_and_field(&lookup.field).0.to_string()).or_default() += 1;
+            *summary.by_language.entry(lookup.language.to_string()).or_default() += 1;
+
+            match lookup.key {
+                TranslationKey::Record(_) => summary.by_key_type.record += 1,
+                TranslationKey::RecordSub(_) => summary.by_key_type.record_sub += 1,
+                TranslationKey::Value(_) => summary.by_key_type.value += 1,
+            }
+        }
+
+        let mut top_languages = summary.by_language.iter().map(|(language, count)| (language.clone(), *count)).collect::<Vec<(String, usize)>>();
+        top_languages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        top_languages.truncate(TOP_LANGUAGES);
+        summary.top_languages = top_languages;
+
+        summary
+    }
+
+    pub fn summary_with_stats(&self, stats: &ParseStats) -> TranslationSummary {
+        TranslationSummary {
+            rows_read: Some(stats.rows_read),
+            rows_skipped: Some(stats.rows_skipped),
+            duplicate_rows: Some(stats.duplicate_rows),
+            ..self.summary()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::HEADER;
+    use crate::{from_reader, ParseOptions, TranslationSummary};
+
+    fn summary() -> TranslationSummary {
+        let data = format!("{}stops,stop_name,fr,Gare,stop_1,,\n\
+            stops,stop_name,fr,Gare SNCF,stop_1,,\n\
+            stops,stop_name,de,Bahnhof,stop_1,,\n\
+            stop_times,stop_headsign,fr,Centre,trip_1,3,\n\
+            routes,route_long_name,nl,Blauwe lijn,,,Blue Line\n\
+            routes,route_long_name,xx-,?,route_1,,\n", HEADER);
+        let (result, stats) = from_reader(data.as_bytes(), &ParseOptions::default()).unwrap();
+
+        result.summary_with_stats(&stats)
+    }
+
+    #[test]
+    fn counts_by_table_key_and_language() {
+        let summary = summary();
+
+        assert_eq!(summary.translations, 4);
+        assert_eq!(summary.by_table["stops"], 2);
+        assert_eq!((summary.by_key_type.record, summary.by_key_type.record_sub, summary.by_key_type.value), (2, 1, 1));
+        assert_eq!(summary.top_languages, vec![("fr".to_string(), 2), ("de".to_string(), 1), ("nl".to_string(), 1)]);
+    }
+
+    #[test]
+    fn carries_the_parse_stats() {
+        let summary = summary();
+
+        assert_eq!((summary.rows_read, summary.rows_skipped, summary.duplicate_rows), (Some(6), Some(1), Some(1)));
+        assert_eq!(crate::test_util::translations("stops,stop_name,fr,Gare,stop_1,,\n").summary().rows_read, None);
+    }
+
+    #[test]
+    fn serializes_languages_in_order() {
+        assert!(serde_json::to_string(&summary()).unwrap().contains("\"by_language\":{\"de\":1,\"fr\":2,\"nl\":1}"));
+    }
+}
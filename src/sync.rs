@@ -0,0 +1,337 @@
+use std::error::Error;
+use language_tags::LanguageTag;
+use serde_json::{json, Value};
+use crate::export::{collect_units, KeyCodec, UnitIdCodec};
+use crate::{enum_to_table_and_field, ConflictPolicy, MergeConflict, TranslationResult};
+
+const TRANSIFEX_API: &str = "https://rest.api.transifex.com";
+const CROWDIN_API: &str = "https://api.crowdin.com/api/v2";
+// Transifex takes at most 150 strings per bulk request, Crowdin lists at most 500 per page
+const TRANSIFEX_BATCH: usize = 150;
+const CROWDIN_PAGE: usize = 500;
+
+// Where the strings of a feed live on the translation platform
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Platform {
+    // Slugs of the organization, project and resource
+    Transifex { organization: String, project: String, resource: String },
+    // Ids of the project and of the file the strings are added to
+    Crowdin { project_id: u64, file_id: u64 },
+}
+
+// One string to translate. The key is the unit id of the xliff and po exports, so pulled strings map back to
+// the field and key they came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceString {
+    pub key: String,
+    pub text: String,
+    // table.field, shown to translators
+    pub context: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncReport {
+    // Pulled translations that are now in the result
+    pub applied: usize,
+    // Pulled translations that differ from the local ones, settled by the policy
+    pub conflicts: Vec<MergeConflict>,
+    // Keys on the platform that are not unit ids, such as strings added by hand
+    pub unknown_keys: Vec<String>,
+}
+
+// The strings of the source language, or the original values of value keys
+pub fn source_strings(result: &TranslationResult, source_lang: &LanguageTag) -> Vec<SourceString> {
+    let codec = UnitIdCodec::default();
+
+    collect_units(result, source_lang, source_lang).into_iter()
+        .filter_map(|unit| {
+            let (table_name, field_name) = enum_to_table_and_field(&unit.field);
+
+            Some(SourceString {
+                key: codec.encode(&unit.field, &unit.key),
+                text: unit.source.filter(|text| !text.is_empty())?,
+                context: format!("{}.{}", table_name, field_name),
+            })
+        })
+        .collect()
+}
+
+// Merges pulled (key, translation) pairs into the result as translations in language. Empty translations are
+// strings nobody has translated yet and are left out. With ConflictPolicy::Error nothing is merged on a conflict.
+pub fn reconcile(result: &mut TranslationResult, language: &LanguageTag, pulled: Vec<(String, String)>, policy: &ConflictPolicy) -> Result<SyncReport, Box<dyn Error>> {
+    let codec = UnitIdCodec::default();
+    let mut remote = TranslationResult::default();
+    let mut unknown_keys = vec![];
+
+    for (key, translation) in pulled.into_iter().filter(|(_, translation)| !translation.is_empty()) {
+        match codec.decode(&key) {
+            Some((field, key)) => {
+                remote.insert(field, key, language.clone(), translation);
+            }
+            None => unknown_keys.push(key),
+        }
+    }
+
+    // what merge will write: translations the result lacks, and differing ones the policy lets through
+    let applied = remote.translations.iter()
+        .filter(|(lookup, theirs)| match result.translations.get(*lookup) {
+            Some(ours) => ours != *theirs && policy.prefers_other(&lookup.language),
+            None => true,
+        })
+        .count();
+    let conflicts = result.merge(remote, policy)?;
+
+    Ok(SyncReport { applied, conflicts, unknown_keys })
+}
+
+// The (key, translation) pairs of one page of Transifex resource_translations with the resource strings
+// included, and the url of the next page
+fn transifex_translations(page: &Value) -> (Vec<(String, String)>, Option<String>) {
+    let empty = vec![];
+    let included = page["included"].as_array().unwrap_or(&empty);
+    let key_of = |id: &str| included.iter()
+        .find(|string| string["id"] == id)
+        .and_then(|string| string["attributes"]["key"].as_str());
+
+    let pairs = page["data"].as_array().unwrap_or(&empty).iter()
+        .filter_map(|translation| {
+            let text = translation["attributes"]["strings"]["other"].as_str()?;
+            let key = key_of(translation["relationships"]["resource_string"]["data"]["id"].as_str()?)?;
+            Some((key.to_string(), text.to_string()))
+        })
+        .collect();
+
+    (pairs, page["links"]["next"].as_str().map(String::from))
+}
+
+// Crowdin wraps every item of a list in {"data": ...}
+fn crowdin_items(page: &Value) -> Vec<&Value> {
+    page["data"].as_array().map(|items| items.iter().map(|item| &item["data"]).collect()).unwrap_or_default()
+}
+
+pub struct SyncClient {
+    platform: Platform,
+    token: String,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl SyncClient {
+    pub fn new(platform: Platform, token: impl Into<String>) -> SyncClient {
+        let base_url = match platform {
+            Platform::Transifex { .. } => TRANSIFEX_API,
+            Platform::Crowdin { .. } => CROWDIN_API,
+        };
+
+        SyncClient {
+            platform,
+            token: token.into(),
+            base_url: base_url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    // For Crowdin Enterprise, which has an api url per organization
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> SyncClient {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Value, Box<dyn Error>> {
+        let response = request.bearer_auth(&self.token).send().await?.error_for_status()?;
+        let body = response.text().await?;
+
+        Ok(match body.is_empty() {
+            true => Value::Null,
+            false => serde_json::from_str(&body)?,
+        })
+    }
+
+    // Adds the source strings of the result to the platform. Returns how many were sent.
+    pub async fn push_sources(&self, result: &TranslationResult, source_lang: &LanguageTag) -> Result<usize, Box<dyn Error>> {
+        let strings = source_strings(result, source_lang);
+
+        match &self.platform {
+            Platform::Transifex { organization, project, resource } => {
+                let resource_id = format!("o:{}:p:{}:r:{}", organization, project, resource);
+
+                for batch in strings.chunks(TRANSIFEX_BATCH) {
+                    let data = batch.iter().map(|string| json!({
+                        "type": "resource_strings",
+                        "attributes": {"key": string.key, "strings": {"other": string.text}, "context": string.context},
+                        "relationships": {"resource": {"data": {"type": "resources", "id": resource_id}}},
+                    })).collect::<Vec<Value>>();
+
+                    let request = self.client.post(format!("{}/resource_strings", self.base_url))
+                        .header(reqwest::header::CONTENT_TYPE, "application/vnd.api+json;profile=\"bulk\"")
+                        .body(json!({"data": data}).to_string());
+                    self.send(request).await?;
+                }
+            }
+            Platform::Crowdin { project_id, file_id } => {
+                for string in strings.iter() {
+                    let request = self.client.post(format!("{}/projects/{}/strings", self.base_url, project_id))
+                        .header(reqwest::header::CONTENT_TYPE, "application/json")
+                        .body(json!({"identifier": string.key, "text": string.text, "context": string.context, "fileId": file_id}).to_string());
+                    self.send(request).await?;
+                }
+            }
+        }
+
+        Ok(strings.len())
+    }
+
+    fn transifex_first_page(&self, organization: &str, project: &str, resource: &str, language: &str) -> reqwest::RequestBuilder {
+        self.client.get(format!("{}/resource_translations", self.base_url)).query(&[
+            ("filter[resource]", format!("o:{}:p:{}:r:{}", organization, project, resource)),
+            ("filter[language]", format!("l:{}", language)),
+            ("include", "resource_string".to_string()),
+        ])
+    }
+
+    // Every (key, translation) pair the platform has for the language, in the platform's own language code
+    pub async fn pull(&self, language: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let mut pulled = vec![];
+
+        match &self.platform {
+            Platform::Transifex { organization, project, resource } => {
+                let (pairs, mut next) = transifex_translations(&self.send(self.transifex_first_page(organization, project, resource, language)).await?);
+                pulled.extend(pairs);
+
+                // the next links come back complete, query included
+                while let Some(page_url) = next {
+                    let (pairs, after) = transifex_translations(&self.send(self.client.get(page_url)).await?);
+                    pulled.extend(pairs);
+                    next = after;
+                }
+            }
+            Platform::Crowdin { project_id, file_id } => {
+                let mut keys = std::collections::HashMap::new();
+
+                for offset in (0..).step_by(CROWDIN_PAGE) {
+                    let page = self.send(self.client.get(format!("{}/projects/{}/strings", self.base_url, project_id))
+                        .query(&[("fileId", file_id.to_string()), ("limit", CROWDIN_PAGE.to_string()), ("offset", offset.to_string())])).await?;
+                    let items = crowdin_items(&page);
+
+                    keys.extend(items.iter().filter_map(|string| Some((string["id"].as_u64()?, string["identifier"].as_str()?.to_string()))));
+
+                    if items.len() < CROWDIN_PAGE {
+                        break;
+                    }
+                }
+
+                for offset in (0..).step_by(CROWDIN_PAGE) {
+                    let page = self.send(self.client.get(format!("{}/projects/{}/languages/{}/translations", self.base_url, project_id, language))
+                        .query(&[("fileId", file_id.to_string()), ("limit", CROWDIN_PAGE.to_string()), ("offset", offset.to_string())])).await?;
+                    let items = crowdin_items(&page);
+
+                    pulled.extend(items.iter().filter_map(|translation| {
+                        let key = keys.get(&translation["stringId"].as_u64()?)?;
+                        Some((key.clone(), translation["text"].as_str()?.to_string()))
+                    }));
+
+                    if items.len() < CROWDIN_PAGE {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(pulled)
+    }
+
+    // Pulls the language and reconciles it into the result
+    pub async fn pull_into(&self, result: &mut TranslationResult, language: &LanguageTag, policy: &ConflictPolicy) -> Result<SyncReport, Box<dyn Error>> {
+        let pulled = self.pull(language.as_str()).await?;
+        reconcile(result, language, pulled, policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::{StopFields, TranslatableField};
+
+    fn local() -> TranslationResult {
+        translations("stops,stop_name,en,Station,stop_1,,\n\
+            stops,stop_name,en,Square,stop_2,,\n\
+            stops,stop_name,fr,Gare,stop_1,,\n")
+    }
+
+    fn key(record_id: &str) -> String {
+        UnitIdCodec::default().encode(&TranslatableField::Stops(StopFields::Name), &crate::TranslationKey::Record(record_id.to_string()))
+    }
+
+    #[test]
+    fn lists_source_strings() {
+        let strings = source_strings(&local(), &LanguageTag::parse("en").unwrap());
+
+        assert_eq!(strings.len(), 2);
+        assert!(strings.iter().all(|string| string.context == "stops.stop_name"));
+        assert!(strings.iter().any(|string| string.key == key("stop_1") && string.text == "Station"));
+    }
+
+    #[test]
+    fn reads_transifex_pages() {
+        let page = json!({
+            "data": [
+                {"attributes": {"strings": {"other": "Gare SNCF"}}, "relationships": {"resource_string": {"data": {"id": "s1"}}}},
+                {"attributes": {"strings": null}, "relationships": {"resource_string": {"data": {"id": "s2"}}}},
+            ],
+            "included": [
+                {"id": "s1", "attributes": {"key": key("stop_1")}},
+                {"id": "s2", "attributes": {"key": key("stop_2")}},
+            ],
+            "links": {"next": "https://rest.api.transifex.com/resource_translations?page=2"},
+        });
+
+        let (pulled, next) = transifex_translations(&page);
+        assert_eq!(pulled, vec![(key("stop_1"), "Gare SNCF".to_string())]);
+        assert_eq!(next.as_deref(), Some("https://rest.api.transifex.com/resource_translations?page=2"));
+    }
+
+    #[test]
+    fn escapes_the_transifex_language() {
+        let client = SyncClient::new(Platform::Transifex { organization: "o".to_string(), project: "p".to_string(), resource: "r".to_string() }, "token");
+        let request = client.transifex_first_page("o", "p", "r", "fr&include=x").build().unwrap();
+        let query = request.url().query_pairs().collect::<Vec<_>>();
+
+        assert_eq!(query.len(), 3);
+        assert!(query.iter().any(|(name, value)| name == "filter[language]" && value == "l:fr&include=x"));
+    }
+
+    #[test]
+    fn counts_only_changed_translations() {
+        let mut result = local();
+        let fr = LanguageTag::parse("fr").unwrap();
+        let pulled = vec![(key("stop_1"), "Gare".to_string()), (key("stop_2"), "Place".to_string())];
+
+        let report = reconcile(&mut result, &fr, pulled, &ConflictPolicy::PreferOther).unwrap();
+        assert_eq!(report.applied, 1);
+        assert!(report.conflicts.is_empty());
+        assert_eq!(result.resolve(&TranslatableField::Stops(StopFields::Name), Some("stop_2"), None, None, &fr), Some("Place"));
+    }
+
+    #[test]
+    fn keeps_local_translations_on_conflict() {
+        let mut result = local();
+        let fr = LanguageTag::parse("fr").unwrap();
+        let pulled = vec![(key("stop_1"), "Gare SNCF".to_string()), ("hand_added".to_string(), "Main".to_string()), (key("stop_2"), String::new())];
+
+        let report = reconcile(&mut result, &fr, pulled, &ConflictPolicy::PreferSelf).unwrap();
+        assert_eq!(report.applied, 0);
+        assert_eq!(report.conflicts[0].discarded, "Gare SNCF");
+        assert_eq!(report.unknown_keys, vec!["hand_added"]);
+        assert_eq!(result.resolve(&TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, None, &fr), Some("Gare"));
+    }
+
+    #[test]
+    fn merges_nothing_on_conflict_with_the_error_policy() {
+        let mut result = local();
+        let pulled = vec![(key("stop_1"), "Gare SNCF".to_string()), (key("stop_2"), "Place".to_string())];
+
+        assert!(reconcile(&mut result, &LanguageTag::parse("fr").unwrap(), pulled, &ConflictPolicy::Error).is_err());
+        assert_eq!(result.translations, local().translations);
+    }
+}
@@ -0,0 +1,133 @@
+// proptest strategies for translations.txt rows and files, for this crate's tests and for downstream crates
+// that enable the test-util feature
+use std::collections::HashMap;
+use proptest::collection::{vec, SizeRange};
+use proptest::prelude::*;
+use crate::writer::write_raw_translations;
+use crate::{enum_to_table_and_field, translation_csv_text_to_translations, RawTranslation, TranslationResult, TRANSLATABLE_FIELDS};
+
+// The standard header, for fixtures written as rows
+pub const HEADER: &str = "table_name,field_name,language,translation,record_id,record_sub_id,field_value\n";
+
+pub const LANGUAGES: [&str; 6] = ["fr", "de-CH", "ja", "zh-Hant", "es-419", "nl"];
+
+// Short text with the characters that need quoting in csv, and some outside ASCII
+pub fn text() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9 ,\"\r\néüß中央-]{1,12}"
+}
+
+pub fn language() -> impl Strategy<Value = String> {
+    (0..LANGUAGES.len()).prop_map(|language| LANGUAGES[language].to_string())
+}
+
+// A row the indexer accepts: a translatable field, a valid language and at least one way to reference the record
+pub fn raw_translation() -> impl Strategy<Value = RawTranslation> {
+    let key = prop_oneof![
+        (text(), proptest::option::of(text()), proptest::option::of(text())).prop_map(|(record_id, record_sub_id, field_value)| (Some(record_id), record_sub_id, field_value)),
+        text().prop_map(|field_value| (None, None, Some(field_value))),
+    ];
+
+    (0..TRANSLATABLE_FIELDS.len(), language(), text(), key)
+        .prop_map(|(field, language, translation, (record_id, record_sub_id, field_value))| {
+            let (table_name, field_name) = enum_to_table_and_field(&TRANSLATABLE_FIELDS[field]);
+
+            RawTranslation {
+                table_name: table_name.to_string(),
+                field_name: field_name.to_string(),
+                language,
+                translation,
+                record_id,
+                record_sub_id,
+                field_value,
+                extras: HashMap::new(),
+            }
+        })
+}
+
+// A row the indexer drops, for one of the three reasons it drops rows
+pub fn invalid_raw_translation() -> impl Strategy<Value = RawTranslation> {
+    (raw_translation(), 0..3).prop_map(|(mut row, defect)| {
+        match defect {
+            0 => row.language = "not a language".to_string(),
+            1 => row.field_name = "stop_colour".to_string(),
+            _ => {
+                row.record_id = None;
+                row.record_sub_id = None;
+                row.field_value = None;
+            }
+        }
+
+        row
+    })
+}
+
+// Mostly valid rows with the odd invalid one, like a real feed
+pub fn any_raw_translation() -> impl Strategy<Value = RawTranslation> {
+    prop_oneof![
+        4 => raw_translation(),
+        1 => invalid_raw_translation(),
+    ]
+}
+
+// A whole translations.txt in the standard layout
+pub fn translation_file(rows: impl Into<SizeRange>) -> impl Strategy<Value = String> {
+    vec(any_raw_translation(), rows).prop_map(|rows| {
+        let mut file: Vec<u8> = vec![];
+        write_raw_translations(&rows, &mut file).expect("writing to a Vec does not fail");
+        String::from_utf8(file).expect("rows are valid UTF-8")
+    })
+}
+
+// Parses rows in the standard layout, without the header. Panics on input the parser rejects, as it is for fixtures.
+pub fn translations(rows: &str) -> TranslationResult {
+    translation_csv_text_to_translations(&format!("{}{}", HEADER, rows)).expect("fixture rows parse")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_tags::LanguageTag;
+    use crate::{parse_translations, table_and_field_to_enum, translate_raw_translations};
+
+    proptest! {
+        #[test]
+        fn record_reference_beats_field_value(row in raw_translation(), other in text(), record_first in any::<bool>()) {
+            prop_assume!(row.record_id.is_some() && row.field_value.is_some() && row.translation != other);
+
+            let by_value = RawTranslation {
+                record_id: None,
+                record_sub_id: None,
+                translation: other,
+                ..row.clone()
+            };
+            let rows = if record_first { vec![row.clone(), by_value] } else { vec![by_value, row.clone()] };
+            let result = translate_raw_translations(rows);
+
+            let field = table_and_field_to_enum(&row.table_name, &row.field_name).unwrap();
+            let language = LanguageTag::parse(&row.language).unwrap();
+            let resolved = result.resolve(&field, row.record_id.as_deref(), row.record_sub_id.as_deref(), row.field_value.as_deref(), &language);
+
+            prop_assert_eq!(resolved, Some(row.translation.as_str()));
+        }
+
+        #[test]
+        fn repeated_rows_collapse(rows in vec(any_raw_translation(), 0..40)) {
+            let once = translate_raw_translations(rows.clone());
+            let twice = translate_raw_translations(rows.iter().chain(rows.iter()).cloned().collect());
+
+            // the summaries are in hash order, so only their contents are compared
+            prop_assert_eq!(&twice.translations, &once.translations);
+            prop_assert_eq!(twice.languages_as_written().len(), once.languages_as_written().len());
+            prop_assert_eq!(twice.possible_translations.len(), once.possible_translations.len());
+            prop_assert!(once.translations.len() <= rows.len());
+        }
+
+        #[test]
+        fn invalid_rows_are_skipped(file in translation_file(0..40)) {
+            let (result, stats) = parse_translations(&file).unwrap();
+
+            prop_assert_eq!(stats.rows_skipped, stats.errors.len());
+            prop_assert!(result.translations.len() + stats.rows_skipped <= stats.rows_read);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use language_tags::LanguageTag;
+use crate::validate::{sort_notices, source_text};
+use crate::{enum_to_table_and_field, Notice, Severity, TranslationResult};
+
+// Arabic-Indic, Persian and fullwidth digits read as ASCII ones, so ٤٥ in an Arabic translation keeps the 45 of the source
+fn ascii_digit(character: char) -> char {
+    match character {
+        '\u{0660}'..='\u{0669}' => char::from(b'0' + (character as u32 - 0x0660) as u8),
+        '\u{06F0}'..='\u{06F9}' => char::from(b'0' + (character as u32 - 0x06F0) as u8),
+        '\u{FF10}'..='\u{FF19}' => char::from(b'0' + (character as u32 - 0xFF10) as u8),
+        _ => character,
+    }
+}
+
+// The parts of a text that translating should leave alone: numbers and designators with a digit such as 45A or M1,
+// bracketed placeholders such as {stop} or [platform], and printf style ones such as %s
+pub(crate) fn protected_tokens(text: &str) -> Vec<String> {
+    let text = text.chars().map(ascii_digit).collect::<String>();
+    let mut tokens = vec![];
+    let mut rest = text.as_str();
+
+    while let Some(character) = rest.chars().next() {
+        let closing = match character {
+            '{' => Some('}'),
+            '[' => Some(']'),
+            _ => None,
+        };
+
+        if let Some(end) = closing.and_then(|closing| rest.find(closing)) {
+            tokens.push(rest[..=end].to_string());
+            rest = &rest[end + 1..];
+        } else if character == '%' && rest[1..].starts_with(|next: char| next.is_ascii_alphabetic()) {
+            tokens.push(rest[..2].to_string());
+            rest = &rest[2..];
+        } else if character.is_alphanumeric() {
+            let end = rest.find(|next: char| !next.is_alphanumeric()).unwrap_or(rest.len());
+            let word = &rest[..end];
+            if word.contains(|next: char| next.is_ascii_digit()) {
+                tokens.push(word.to_string());
+            }
+            rest = &rest[end..];
+        } else {
+            rest = &rest[character.len_utf8()..];
+        }
+    }
+
+    tokens
+}
+
+// Flags translations that lose a number, designator or placeholder of their source, such as Route 45 to Airport
+// translated without the 45. Sources are the original value of value keys and, with source_lang, the source
+// language translation of the same record. Each missing token is only looked for as a whole word.
+pub fn check_tokens(result: &TranslationResult, source_lang: Option<&LanguageTag>) -> Vec<Notice> {
+    let mut notices = vec![];
+
+    for (lookup, translation) in result.translations.iter().filter(|(lookup, _)| Some(&lookup.language) != source_lang) {
+        let Some(source) = source_text(result, lookup, source_lang) else {
+            continue;
+        };
+
+        let kept = protected_tokens(translation);
+        let mut missing = protected_tokens(source);
+        missing.retain(|token| !kept.contains(token));
+        missing.dedup();
+
+        if !missing.is_empty() {
+            let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+            notices.push(Notice::about(lookup, "token_missing", Severity::Warning,
+                format!("{}.{} {} translation \"{}\" leaves out {} of \"{}\"", table_name, field_name, lookup.language, translation, missing.join(", "), source)));
+        }
+    }
+
+    sort_notices(&mut notices);
+    notices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+
+    fn languages(notices: &[Notice]) -> Vec<&str> {
+        notices.iter().map(|notice| notice.language.as_deref().unwrap()).collect()
+    }
+
+    #[test]
+    fn finds_numbers_and_placeholders() {
+        assert_eq!(protected_tokens("Route 45A to {stop}, platform [2], %s"), vec!["45A", "{stop}", "[2]", "%s"]);
+    }
+
+    #[test]
+    fn flags_dropped_numbers() {
+        let result = translations("trips,trip_headsign,en,Route 45 to Airport,trip_1,,\n\
+            trips,trip_headsign,fr,Ligne 45 vers l'a\u{00E9}roport,trip_1,,\n\
+            trips,trip_headsign,de,Linie zum Flughafen,trip_1,,\n");
+        let notices = check_tokens(&result, Some(&LanguageTag::parse("en").unwrap()));
+
+        assert_eq!(languages(&notices), vec!["de"]);
+        assert!(notices.iter().all(|notice| notice.code == "token_missing"));
+    }
+
+    #[test]
+    fn accepts_numbers_in_other_digits() {
+        let result = translations("trips,trip_headsign,en,Route 45 to Airport,trip_1,,\n\
+            trips,trip_headsign,ar,\u{0627}\u{0644}\u{062E}\u{0637} \u{0664}\u{0665},trip_1,,\n");
+
+        assert!(check_tokens(&result, Some(&LanguageTag::parse("en").unwrap())).is_empty());
+    }
+
+    #[test]
+    fn compares_translations_by_value_with_the_value() {
+        let notices = check_tokens(&translations("routes,route_long_name,fr,Ligne Bleue,,,Blue Line M1\n"), None);
+
+        assert_eq!(languages(&notices), vec!["fr"]);
+        assert!(notices[0].message.contains("leaves out M1"));
+    }
+}
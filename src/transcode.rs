@@ -0,0 +1,112 @@
+use std::io::{self, Read};
+
+// Label that asks for the encoding to be guessed from the input
+pub const AUTO_ENCODING: &str = "auto";
+
+pub(crate) enum Transcoded<R: Read> {
+    Utf8(R),
+    #[cfg(feature = "encoding")]
+    Decoded(encoding_rs_io::DecodeReaderBytes<io::BufReader<R>, Vec<u8>>),
+}
+
+impl<R: Read> Read for Transcoded<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Transcoded::Utf8(reader) => reader.read(buf),
+            #[cfg(feature = "encoding")]
+            Transcoded::Decoded(reader) => reader.read(buf),
+        }
+    }
+}
+
+pub(crate) fn is_utf8_label(label: &str) -> bool {
+    matches!(label.trim().to_lowercase().as_str(), "utf-8" | "utf8" | "unicode-1-1-utf-8")
+}
+
+// Bytes looked at when guessing the encoding
+#[cfg(feature = "encoding")]
+const SNIFF_LEN: usize = 64 * 1024;
+
+#[cfg(feature = "encoding")]
+fn guess_encoding(head: &[u8]) -> &'static encoding_rs::Encoding {
+    if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(head) {
+        return encoding;
+    }
+
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(head, false);
+    detector.guess(None, true)
+}
+
+// Wraps the reader so it yields UTF-8. Returns the name of the encoding that was decoded from, if any.
+#[cfg(feature = "encoding")]
+pub(crate) fn transcode<R: Read>(reader: R, label: Option<&str>) -> io::Result<(Transcoded<R>, Option<String>)> {
+    use std::io::BufRead;
+
+    let Some(label) = label.filter(|label| !is_utf8_label(label)) else {
+        return Ok((Transcoded::Utf8(reader), None));
+    };
+
+    let mut reader = io::BufReader::with_capacity(SNIFF_LEN, reader);
+
+    let encoding = if label.eq_ignore_ascii_case(AUTO_ENCODING) {
+        guess_encoding(reader.fill_buf()?)
+    } else {
+        encoding_rs::Encoding::for_label(label.trim().as_bytes())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("unknown encoding {}", label)))?
+    };
+
+    if encoding == encoding_rs::UTF_8 {
+        return Ok((Transcoded::Decoded(encoding_rs_io::DecodeReaderBytesBuilder::new().encoding(None).build(reader)), None));
+    }
+
+    let decoded = encoding_rs_io::DecodeReaderBytesBuilder::new().encoding(Some(encoding)).build(reader);
+
+    Ok((Transcoded::Decoded(decoded), Some(encoding.name().to_string())))
+}
+
+#[cfg(not(feature = "encoding"))]
+pub(crate) fn transcode<R: Read>(reader: R, label: Option<&str>) -> io::Result<(Transcoded<R>, Option<String>)> {
+    match label {
+        Some(label) if !is_utf8_label(label) => Err(io::Error::new(io::ErrorKind::Unsupported, format!("reading {} input requires the encoding feature", label))),
+        _ => Ok((Transcoded::Utf8(reader), None)),
+    }
+}
+
+#[cfg(all(test, feature = "encoding"))]
+mod tests {
+    use crate::test_util::HEADER;
+    use crate::{from_reader, ParseOptions, StopFields, TranslatableField};
+    use language_tags::LanguageTag;
+
+    fn parse(data: &[u8], label: &str) -> Result<(crate::TranslationResult, crate::ParseStats), Box<dyn std::error::Error>> {
+        from_reader(data, &ParseOptions { encoding: Some(label.to_string()), ..ParseOptions::default() })
+    }
+
+    #[test]
+    fn transcodes_shift_jis_by_label_and_by_sniffing() {
+        let data = format!("{}stops,stop_name,ja,東京駅,stop_1,,\n", HEADER);
+        let (shift_jis, _, _) = encoding_rs::SHIFT_JIS.encode(&data);
+
+        for label in ["shift_jis", "auto"] {
+            let (result, stats) = parse(&shift_jis, label).unwrap();
+
+            assert_eq!(stats.encoding.as_deref(), Some("Shift_JIS"));
+            assert_eq!(result.resolve(&TranslatableField::Stops(StopFields::Name), Some("stop_1"), None, None, &LanguageTag::parse("ja").unwrap()), Some("東京駅"));
+        }
+    }
+
+    #[test]
+    fn transcodes_windows_1252() {
+        let data = format!("{}stops,stop_name,fr,Gare de l'Est à Paris,stop_1,,\n", HEADER);
+        let (windows_1252, _, _) = encoding_rs::WINDOWS_1252.encode(&data);
+        let (result, _) = parse(&windows_1252, "windows-1252").unwrap();
+
+        assert!(result.translations.values().any(|translation| &**translation == "Gare de l'Est à Paris"));
+    }
+
+    #[test]
+    fn rejects_unknown_labels() {
+        assert!(parse(HEADER.as_bytes(), "not-an-encoding").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use language_tags::LanguageTag;
+use crate::universe::{TranslationUniverse, UniverseEntry};
+use crate::{is_url, Provenance, TranslationLookup, TranslationResult};
+
+// A machine translation service. The crate only orchestrates, the services themselves live elsewhere.
+// from is None when the language of the feed is not known, most services then detect it.
+pub trait Translator {
+    fn translate(&mut self, text: &str, from: Option<&LanguageTag>, to: &LanguageTag) -> Result<String, Box<dyn Error>>;
+}
+
+impl<F> Translator for F
+where
+    F: FnMut(&str, Option<&LanguageTag>, &LanguageTag) -> Result<String, Box<dyn Error>>,
+{
+    fn translate(&mut self, text: &str, from: Option<&LanguageTag>, to: &LanguageTag) -> Result<String, Box<dyn Error>> {
+        self(text, from, to)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FillReport {
+    pub filled: usize,
+    // Entries the translator failed on, with its error
+    pub failed: Vec<(UniverseEntry, String)>,
+}
+
+impl TranslationResult {
+    // Machine translates the entries of the feed that have no translation in the language yet, marking what it
+    // adds with Origin::Machine. Every distinct text goes to the translator once, however many records share it.
+    pub fn fill_missing<T: Translator>(&mut self, universe: &TranslationUniverse, language: &LanguageTag, translator: &mut T) -> FillReport {
+        let mut report = FillReport::default();
+        // shared by every record with the same text
+        let mut translated: HashMap<&str, Result<Arc<str>, String>> = HashMap::new();
+
+        let missing = universe.entries.iter()
+            .filter(|entry| !is_url(&entry.field) && self.translate_entry(entry, language).is_none())
+            .collect::<Vec<&UniverseEntry>>();
+
+        for entry in missing {
+            let translation = translated.entry(entry.original_value.as_str())
+                .or_insert_with(|| translator.translate(&entry.original_value, None, language).map(Arc::from).map_err(|err| err.to_string()));
+
+            match translation {
+                Ok(translation) => {
+                    let lookup = TranslationLookup { language: language.clone(), field: entry.field.clone(), key: entry.key() };
+                    self.provenance.insert(lookup.clone(), Provenance::machine());
+                    self.insert_lookup(lookup, translation.clone());
+                    report.filled += 1;
+                }
+                Err(err) => report.failed.push((entry.clone(), err.clone())),
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Origin, StopFields, TranslatableField, TranslationKey};
+
+    #[test]
+    fn fills_only_missing_entries() {
+        let field = TranslatableField::Stops(StopFields::Name);
+        let fr = LanguageTag::parse("fr").unwrap();
+
+        let mut universe = TranslationUniverse::new();
+        universe.push(field.clone(), Some("stop_1"), None, "Central");
+        universe.push(field.clone(), Some("stop_2"), None, "Central");
+        universe.push(field.clone(), Some("stop_3"), None, "Harbour");
+        universe.push(field.clone(), Some("stop_4"), None, "?");
+
+        let mut result = TranslationResult::default();
+        result.insert(field.clone(), TranslationKey::Record("stop_3".to_string()), fr.clone(), "Port");
+
+        let mut calls = 0;
+        let mut translator = |text: &str, _: Option<&LanguageTag>, _: &LanguageTag| -> Result<String, Box<dyn Error>> {
+            calls += 1;
+            if text == "?" { Err("nothing to translate".into()) } else { Ok(format!("{} (fr)", text)) }
+        };
+
+        let report = result.fill_missing(&universe, &fr, &mut translator);
+
+        assert_eq!(calls, 2);
+        assert_eq!(report.filled, 2);
+        assert_eq!(report.failed.len(), 1);
+
+        let stop_2 = TranslationLookup { language: fr.clone(), field: field.clone(), key: TranslationKey::Record("stop_2".to_string()) };
+        assert_eq!(&*result.translations[&stop_2], "Central (fr)");
+        assert_eq!(result.provenance()[&stop_2].origin, Origin::Machine);
+        assert_eq!(result.get(&field, &TranslationKey::Record("stop_3".to_string()), &fr), Some("Port"));
+        assert_eq!(result.provenance().len(), 2);
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+#[cfg(feature = "zip")]
+use std::error::Error;
+#[cfg(feature = "zip")]
+use std::io::{Read, Seek};
+#[cfg(feature = "zip")]
+use std::path::Path;
+use language_tags::LanguageTag;
+use crate::{TranslatableField, TranslationKey, TranslationResult};
+#[cfg(feature = "zip")]
+use crate::{enum_to_table_and_field, TRANSLATABLE_FIELDS};
+
+// A translatable value that exists in the feed. For stop_times the record_sub_id is the stop_sequence.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct UniverseEntry {
+    pub field: TranslatableField,
+    pub record_id: Option<String>,
+    pub record_sub_id: Option<String>,
+    pub original_value: String,
+}
+
+impl UniverseEntry {
+    // The most specific key a translation of this entry can have
+    pub fn key(&self) -> TranslationKey {
+        match (&self.record_id, &self.record_sub_id) {
+            (Some(record_id), Some(record_sub_id)) => TranslationKey::RecordSub((record_id.clone(), record_sub_id.clone())),
+            (Some(record_id), None) => TranslationKey::Record(record_id.clone()),
+            (None, _) => TranslationKey::Value(self.original_value.clone()),
+        }
+    }
+}
+
+// Every translatable (field, record, original value) in a feed, the denominator for coverage and missing-translation reports
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranslationUniverse {
+    pub entries: Vec<UniverseEntry>,
+}
+
+impl TranslationUniverse {
+    pub fn new() -> TranslationUniverse {
+        TranslationUniverse::default()
+    }
+
+    pub fn push(&mut self, field: TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, original_value: &str) {
+        self.entries.push(UniverseEntry {
+            field,
+            record_id: record_id.map(String::from),
+            record_sub_id: record_sub_id.map(String::from),
+            original_value: original_value.to_string(),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn fields(&self) -> HashSet<TranslatableField> {
+        self.entries.iter().map(|entry| entry.field.clone()).collect()
+    }
+
+    // The keys of the records whose field has this value, a resolver for TranslationResult::materialize_value_keys
+    pub fn records_with_value(&self, field: &TranslatableField, value: &str) -> Vec<TranslationKey> {
+        self.entries.iter()
+            .filter(|entry| &entry.field == field && entry.original_value == value && entry.record_id.is_some())
+            .map(UniverseEntry::key)
+            .collect()
+    }
+
+    pub fn untranslated<'a>(&'a self, result: &'a TranslationResult, language: &'a LanguageTag) -> impl Iterator<Item = &'a UniverseEntry> + 'a {
+        self.entries.iter().filter(move |entry| result.translate_entry(entry, language).is_none())
+    }
+}
+
+impl TranslationResult {
+    pub fn translate_entry(&self, entry: &UniverseEntry, language: &LanguageTag) -> Option<&str> {
+        self.resolve(&entry.field, entry.record_id.as_deref(), entry.record_sub_id.as_deref(), Some(&entry.original_value), language)
+    }
+}
+
+#[cfg(feature = "zip")]
+impl TranslationUniverse {
+    // Every non-empty translatable value in the tables of a GTFS zip, keyed the way translations.txt refers to it.
+    // feed_info has no id, so its values are only keyed by value. calendar.service_id is an id rather than text and is left out.
+    pub fn from_gtfs_zip<R: Read + Seek>(reader: R) -> Result<TranslationUniverse, Box<dyn Error>> {
+        TranslationUniverse::from_gtfs_archive(&mut zip::ZipArchive::new(reader)?)
+    }
+
+    // Same as from_gtfs_zip for an archive that is already open, such as the one from_gtfs_archive read
+    pub fn from_gtfs_archive<R: Read + Seek>(archive: &mut zip::ZipArchive<R>) -> Result<TranslationUniverse, Box<dyn Error>> {
+        let mut universe = TranslationUniverse::new();
+
+        let mut tables = TRANSLATABLE_FIELDS.iter().map(|field| enum_to_table_and_field(field).0).collect::<Vec<&str>>();
+        tables.sort();
+        tables.dedup();
+
+        for table_name in tables {
+            let columns = crate::localize::translatable_columns(table_name);
+            let Some(path) = crate::gtfs_zip::find_entry(archive, &format!("{}.txt", table_name)).filter(|_| !columns.is_empty()) else {
+                continue;
+            };
+
+            let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(archive.by_name(&path)?);
+            let headers = rdr.headers()?.clone();
+            let column = |name: &str| headers.iter().position(|header| header.trim_start_matches('\u{feff}').trim() == name);
+
+            let (record_id, record_sub_id) = crate::localize::record_columns(table_name);
+            let (record_id, record_sub_id) = (record_id.and_then(column), record_sub_id.and_then(column));
+            let fields = columns.into_iter()
+                .filter_map(|(field, name)| column(name).map(|index| (field, index)))
+                .collect::<Vec<(TranslatableField, usize)>>();
+
+            for record in rdr.records() {
+                let record = record?;
+                let read = |index: Option<usize>| index.and_then(|index| record.get(index)).filter(|value| !value.is_empty());
+
+                for (field, index) in fields.iter() {
+                    if let Some(value) = read(Some(*index)) {
+                        universe.push(field.clone(), read(record_id), read(record_sub_id), value);
+                    }
+                }
+            }
+        }
+
+        Ok(universe)
+    }
+
+    pub fn from_gtfs_zip_path<P: AsRef<Path>>(path: P) -> Result<TranslationUniverse, Box<dyn Error>> {
+        TranslationUniverse::from_gtfs_zip(std::io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
+
+#[cfg(feature = "apply")]
+impl TranslationUniverse {
+    // Same as from_gtfs_zip for a feed gtfs_structures has already read
+    pub fn from_gtfs(gtfs: &gtfs_structures::Gtfs) -> TranslationUniverse {
+        use crate::{AgencyFields, FeedInfoFields, RouteFields, StopFields, StopTimeFields, TripFields};
+
+        let mut universe = TranslationUniverse::new();
+        let mut push = |field: TranslatableField, record_id: Option<&str>, record_sub_id: Option<&str>, value: Option<&str>| {
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                universe.push(field, record_id, record_sub_id, value);
+            }
+        };
+
+        for agency in gtfs.agencies.iter() {
+            let id = agency.id.as_deref();
+            push(TranslatableField::Agency(AgencyFields::Name), id, None, Some(&agency.name));
+            push(TranslatableField::Agency(AgencyFields::Url), id, None, Some(&agency.url));
+            push(TranslatableField::Agency(AgencyFields::FareUrl), id, None, agency.fare_url.as_deref());
+        }
+
+        for stop in gtfs.stops.values() {
+            let id = Some(stop.id.as_str());
+            push(TranslatableField::Stops(StopFields::Code), id, None, stop.code.as_deref());
+            push(TranslatableField::Stops(StopFields::Name), id, None, stop.name.as_deref());
+            push(TranslatableField::Stops(StopFields::TtsName), id, None, stop.tts_name.as_deref());
+            push(TranslatableField::Stops(StopFields::Desc), id, None, stop.description.as_deref());
+            push(TranslatableField::Stops(StopFields::PlatformCode), id, None, stop.platform_code.as_deref());
+        }
+
+        for route in gtfs.routes.values() {
+            let id = Some(route.id.as_str());
+            push(TranslatableField::Routes(RouteFields::ShortName), id, None, route.short_name.as_deref());
+            push(TranslatableField::Routes(RouteFields::LongName), id, None, route.long_name.as_deref());
+            push(TranslatableField::Routes(RouteFields::Desc), id, None, route.desc.as_deref());
+            push(TranslatableField::Routes(RouteFields::Url), id, None, route.url.as_deref());
+        }
+
+        for trip in gtfs.trips.values() {
+            let id = Some(trip.id.as_str());
+            push(TranslatableField::Trips(TripFields::Headsign), id, None, trip.trip_headsign.as_deref());
+            push(TranslatableField::Trips(TripFields::ShortName), id, None, trip.trip_short_name.as_deref());
+
+            for stop_time in trip.stop_times.iter() {
+                push(TranslatableField::StopTimes(StopTimeFields::Headsign), id, Some(&stop_time.stop_sequence.to_string()), stop_time.stop_headsign.as_deref());
+            }
+        }
+
+        for feed_info in gtfs.feed_info.iter() {
+            push(TranslatableField::FeedInfo(FeedInfoFields::PublisherName), None, None, Some(&feed_info.name));
+        }
+
+        universe
+    }
+}
+
+#[cfg(all(test, feature = "zip"))]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use crate::{StopFields, StopTimeFields};
+
+    #[test]
+    fn scans_a_gtfs_zip() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in [
+            ("feed/stops.txt", "stop_id,stop_name,stop_code\nstop_1,Station,\nstop_2,Square,S2\n"),
+            ("feed/stop_times.txt", "trip_id,stop_id,stop_sequence,stop_headsign\ntrip_1,stop_1,1,Downtown\n"),
+            ("feed/calendar.txt", "service_id,monday\nweekday,1\n"),
+        ] {
+            writer.start_file(name, zip::write::FileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+
+        let universe = TranslationUniverse::from_gtfs_zip(writer.finish().unwrap()).unwrap();
+
+        assert_eq!(universe.len(), 4);
+        assert!(universe.entries.contains(&UniverseEntry {
+            field: TranslatableField::StopTimes(StopTimeFields::Headsign),
+            record_id: Some("trip_1".to_string()),
+            record_sub_id: Some("1".to_string()),
+            original_value: "Downtown".to_string(),
+        }));
+        assert_eq!(universe.records_with_value(&TranslatableField::Stops(StopFields::Code), "S2"), vec![TranslationKey::Record("stop_2".to_string())]);
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use language_tags::LanguageTag;
+use crate::{enum_to_table_and_field, key_struct_to_options, normalize_language, Dialect, ParseStats, TranslatableField, TranslationKey, TranslationLookup, TranslationResult};
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+// One finding about a translations file. Codes are stable snake_case names such as skipped_row,
+// the message is for people.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Notice {
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+    // 1-based line in translations.txt, for notices about a single row
+    pub line: Option<u64>,
+    pub field: Option<TranslatableField>,
+    pub key: Option<TranslationKey>,
+    pub language: Option<String>,
+    // What the translation should probably say instead
+    pub suggestion: Option<String>,
+}
+
+impl Notice {
+    pub fn new(code: &str, severity: Severity, message: String) -> Notice {
+        Notice {
+            code: code.to_string(),
+            severity,
+            message,
+            line: None,
+            field: None,
+            key: None,
+            language: None,
+            suggestion: None,
+        }
+    }
+
+    // A notice about one translation, pointing at its field, key and language
+    pub(crate) fn about(lookup: &TranslationLookup, code: &str, severity: Severity, message: String) -> Notice {
+        Notice {
+            field: Some(lookup.field.clone()),
+            key: Some(lookup.key.clone()),
+            language: Some(lookup.language.to_string()),
+            ..Notice::new(code, severity, message)
+        }
+    }
+}
+
+// The text a translation translates: the original value for value keys, or else the translation of the same
+// record into the source language when one is given
+pub(crate) fn source_text<'a>(result: &'a TranslationResult, lookup: &'a TranslationLookup, source_lang: Option<&LanguageTag>) -> Option<&'a str> {
+    match &lookup.key {
+        TranslationKey::Value(field_value) => Some(field_value),
+        _ => result.get(&lookup.field, &lookup.key, source_lang?),
+    }
+}
+
+// Notices of one check over the translations come out of a hash map, so this puts them in the same order on every run
+pub(crate) fn sort_notices(notices: &mut [Notice]) {
+    notices.sort_by_cached_key(|notice| (
+        notice.field.as_ref().map(enum_to_table_and_field),
+        notice.key.as_ref().map(key_struct_to_options),
+        notice.language.clone(),
+        notice.code.clone(),
+    ));
+}
+
+impl fmt::Display for Notice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{} [{}] line {}: {}", self.severity, self.code, line, self.message),
+            None => write!(f, "{} [{}] {}", self.severity, self.code, self.message),
+        }
+    }
+}
+
+fn stats_notices(stats: &ParseStats, notices: &mut Vec<Notice>) {
+    if stats.dialect == Some(Dialect::Legacy) {
+        notices.push(Notice::new("legacy_dialect", Severity::Warning, "translations.txt uses the legacy trans_id/lang layout".to_string()));
+    }
+
+    if let Some(encoding) = &stats.encoding {
+        notices.push(Notice::new("non_utf8_encoding", Severity::Warning, format!("file is encoded as {} instead of UTF-8", encoding)));
+    }
+
+    for (header, column) in stats.aliases_applied.iter() {
+        notices.push(Notice::new("non_standard_header", Severity::Info, format!("header {} was read as {}", header, column)));
+    }
+
+    for column in stats.unknown_columns.iter() {
+        notices.push(Notice::new("unknown_column", Severity::Info, format!("column {} is not part of the dialect", column)));
+    }
+
+    for error in stats.errors.iter() {
+        notices.push(Notice {
+            line: error.line,
+            ..Notice::new("skipped_row", Severity::Warning, error.message.clone())
+        });
+    }
+
+    for change in stats.text_changes.iter() {
+        notices.push(Notice {
+            line: change.line,
+            suggestion: Some(change.after.clone()),
+            ..Notice::new("text_cleaned", Severity::Info, format!("{} {:?} was cleaned up", change.column, change.before))
+        });
+    }
+}
+
+fn result_notices(result: &TranslationResult, notices: &mut Vec<Notice>) {
+    let mut found = vec![];
+
+    for (lookup, translation) in result.translations.iter() {
+        let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+        let notice = |code: &str, severity: Severity, message: String| Notice::about(lookup, code, severity, message);
+
+        if translation.trim().is_empty() {
+            found.push(notice("empty_translation", Severity::Warning, format!("{}.{} has an empty {} translation", table_name, field_name, lookup.language)));
+        }
+
+        if let TranslationKey::Value(field_value) = &lookup.key {
+            if **field_value == **translation {
+                found.push(notice("translation_equals_original", Severity::Info, format!("{}.{} {} translation of \"{}\" is the original value", table_name, field_name, lookup.language, field_value)));
+            }
+        }
+    }
+
+    for language in result.languages_as_written().iter() {
+        if let Some(canonical) = normalize_language(language.as_str()).filter(|canonical| canonical.as_str() != language.as_str()) {
+            found.push(Notice {
+                language: Some(language.to_string()),
+                ..Notice::new("non_canonical_language", Severity::Info, format!("language {} is usually written {}", language, canonical))
+            });
+        }
+    }
+
+    sort_notices(&mut found);
+    notices.extend(found);
+}
+
+// Notices about how the file was read followed by notices about its translations
+pub fn validate(result: &TranslationResult, stats: &ParseStats) -> Vec<Notice> {
+    let mut notices = vec![];
+    stats_notices(stats, &mut notices);
+    result_notices(result, &mut notices);
+    notices
+}
+
+pub fn validate_with(result: &TranslationResult, stats: &ParseStats, config: &NoticeConfig) -> Vec<Notice> {
+    config.apply(validate(result, stats))
+}
+
+// Per agency tuning of notices by code, kept as a document next to the feed like the canonical validator's.
+// Works on the output of any check, such as check_glossary or check_bidi, not only validate.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoticeConfig {
+    // Codes reported at another severity, such as translation_equals_original as a warning
+    #[serde(default)]
+    pub severities: BTreeMap<String, Severity>,
+    #[serde(default)]
+    pub suppressed: BTreeSet<String>,
+    // Notices below this severity, after the overrides, are dropped
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+}
+
+impl NoticeConfig {
+    pub fn new() -> NoticeConfig {
+        NoticeConfig::default()
+    }
+
+    pub fn with_severity(mut self, code: &str, severity: Severity) -> NoticeConfig {
+        self.severities.insert(code.to_string(), severity);
+        self
+    }
+
+    pub fn suppress(mut self, code: &str) -> NoticeConfig {
+        self.suppressed.insert(code.to_string());
+        self
+    }
+
+    pub fn min_severity(mut self, severity: Severity) -> NoticeConfig {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    pub fn apply(&self, notices: Vec<Notice>) -> Vec<Notice> {
+        notices.into_iter()
+            .filter(|notice| !self.suppressed.contains(&notice.code))
+            .map(|mut notice| {
+                if let Some(severity) = self.severities.get(&notice.code) {
+                    notice.severity = *severity;
+                }
+                notice
+            })
+            .filter(|notice| self.min_severity.is_none_or(|min_severity| notice.severity >= min_severity))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_reader, ParseOptions};
+
+    fn parsed() -> (TranslationResult, ParseStats) {
+        let data = "table_name,field_name,language,translation,record_id,record_sub_id,field_value,notes\n\
+            stops,stop_name,fr,Gare,stop_1,,,\n\
+            stops,stop_name,not a language,x,stop_1,,,\n\
+            trips,trip_headsign,fr,Downtown,,,Downtown,\n\
+            routes,route_long_name,fr,\" \",route_1,,,\n";
+
+        from_reader(data.as_bytes(), &ParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn reports_skipped_rows_and_suspicious_translations() {
+        let (result, stats) = parsed();
+        let notices = validate(&result, &stats);
+        let codes = notices.iter().map(|notice| notice.code.as_str()).collect::<Vec<&str>>();
+
+        assert_eq!(codes, vec!["unknown_column", "skipped_row", "empty_translation", "translation_equals_original"]);
+        assert_eq!(notices[1].line, Some(3));
+        assert_eq!(notices[1].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn displays_severity_and_code() {
+        let (result, stats) = parsed();
+
+        assert!(validate(&result, &stats)[2].to_string().starts_with("warning [empty_translation]"));
+    }
+
+    fn config() -> NoticeConfig {
+        serde_json::from_str(r#"{"severities": {"translation_equals_original": "error"}, "suppressed": ["skipped_row"], "min_severity": "warning"}"#).unwrap()
+    }
+
+    #[test]
+    fn reads_the_config_like_the_builder() {
+        assert_eq!(config(), NoticeConfig::new().with_severity("translation_equals_original", Severity::Error).suppress("skipped_row").min_severity(Severity::Warning));
+    }
+
+    #[test]
+    fn tunes_severities_and_suppresses() {
+        let (result, stats) = parsed();
+        let tuned = validate_with(&result, &stats, &config());
+        let codes = tuned.iter().map(|notice| (notice.code.as_str(), notice.severity)).collect::<Vec<_>>();
+
+        assert_eq!(codes, vec![("empty_translation", Severity::Warning), ("translation_equals_original", Severity::Error)]);
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use language_tags::LanguageTag;
+use crate::{TranslatableField, TranslationKey, TranslationResult};
+
+impl TranslationResult {
+    // Every translation of a displayed string, for realtime feeds that only carry the text, such as a headsign in
+    // GTFS-Realtime. This probes the translations map once for each language the field is translated into, which
+    // suits one-off lookups. value_index groups every value key up front for pipelines that translate many strings.
+    pub fn value_translations(&self, field: &TranslatableField, original_value: &str) -> HashMap<LanguageTag, &str> {
+        let key = TranslationKey::Value(original_value.to_string());
+
+        self.possible_translations.iter()
+            .filter(|(translated, _)| translated == field)
+            .filter_map(|(_, language)| self.get(field, &key, language).map(|translation| (language.clone(), translation)))
+            .collect()
+    }
+
+    // Groups the Value keyed translations by field and original value in one pass. The index borrows the result,
+    // so it cannot go stale; build it again after editing.
+    pub fn value_index(&self) -> ValueIndex<'_> {
+        let mut values: HashMap<&TranslatableField, HashMap<&str, HashMap<LanguageTag, &str>>> = HashMap::new();
+
+        for (lookup, translation) in self.translations.iter() {
+            if let TranslationKey::Value(original_value) = &lookup.key {
+                values.entry(&lookup.field)
+                    .or_default()
+                    .entry(original_value.as_str())
+                    .or_default()
+                    .insert(lookup.language.clone(), translation.as_ref());
+            }
+        }
+
+        ValueIndex { values }
+    }
+}
+
+pub struct ValueIndex<'a> {
+    values: HashMap<&'a TranslatableField, HashMap<&'a str, HashMap<LanguageTag, &'a str>>>,
+}
+
+impl<'a> ValueIndex<'a> {
+    // The same as TranslationResult::value_translations, without hashing a key per language
+    pub fn value_translations(&self, field: &TranslatableField, original_value: &str) -> Option<&HashMap<LanguageTag, &'a str>> {
+        self.values.get(field)?.get(original_value)
+    }
+
+    // The original values of a field that have translations
+    pub fn values(&self, field: &TranslatableField) -> impl Iterator<Item = &'a str> + '_ {
+        self.values.get(field).into_iter().flat_map(|values| values.keys().copied())
+    }
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::test_util::translations;
+    use crate::TripFields;
+
+    fn result() -> TranslationResult {
+        translations("trips,trip_headsign,fr,Centre-ville,,,Downtown\n\
+            trips,trip_headsign,de,Innenstadt,,,Downtown\n\
+            trips,trip_headsign,es,Aeropuerto,,,Airport\n\
+            trips,trip_headsign,nl,Centrum,trip_1,,\n")
+    }
+
+    fn headsign() -> TranslatableField {
+        TranslatableField::Trips(TripFields::Headsign)
+    }
+
+    #[test]
+    fn finds_every_language_of_a_value() {
+        let result = result();
+        let translations = result.value_translations(&headsign(), "Downtown");
+
+        assert_eq!(translations.len(), 2);
+        assert_eq!(translations[&LanguageTag::parse("fr").unwrap()], "Centre-ville");
+        assert_eq!(translations[&LanguageTag::parse("de").unwrap()], "Innenstadt");
+    }
+
+    #[test]
+    fn index_answers_like_the_scan() {
+        let result = result();
+        let index = result.value_index();
+
+        assert_eq!(index.value_translations(&headsign(), "Downtown"), Some(&result.value_translations(&headsign(), "Downtown")));
+        // translations by record are not values
+        assert_eq!(index.value_translations(&headsign(), "Centrum"), None);
+    }
+
+    #[test]
+    fn lists_the_values_of_a_field() {
+        let result = result();
+        let index = result.value_index();
+        let mut values = index.values(&headsign()).collect::<Vec<&str>>();
+        values.sort();
+
+        assert_eq!(values, ["Airport", "Downtown"]);
+    }
+}
@@ -0,0 +1,72 @@
+use language_tags::LanguageTag;
+use wasm_bindgen::prelude::*;
+use crate::{fallback_languages, field_from_name, parse_translations, TranslationResult};
+
+// Parsed translations for JavaScript, resolving exactly like the backend does
+#[wasm_bindgen(js_name = Translations)]
+pub struct WasmTranslations {
+    result: TranslationResult,
+}
+
+#[wasm_bindgen(js_name = parseTranslations)]
+pub fn parse_translations_js(csv: &str) -> Result<WasmTranslations, JsError> {
+    parse_translations(csv)
+        .map(|(result, _)| WasmTranslations { result })
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+#[wasm_bindgen(js_class = Translations)]
+impl WasmTranslations {
+    #[wasm_bindgen(js_name = getTranslation)]
+    pub fn get_translation(&self, field: &str, record_id: &str, language: &str) -> Option<String> {
+        self.resolve(field, Some(record_id.to_string()), None, None, language)
+    }
+
+    // undefined for the key parts a value does not have, with fallback from fr-CA to fr
+    pub fn resolve(&self, field: &str, record_id: Option<String>, record_sub_id: Option<String>, original_value: Option<String>, language: &str) -> Option<String> {
+        let field = field_from_name(field)?;
+        let language = LanguageTag::parse(language).ok()?;
+
+        fallback_languages(&self.result, &language).iter()
+            .find_map(|language| self.result.resolve(&field, record_id.as_deref(), record_sub_id.as_deref(), original_value.as_deref(), language))
+            .map(String::from)
+    }
+
+    // Language tags in the order of the available languages
+    pub fn languages(&self) -> Vec<String> {
+        self.result.languages_as_written().iter().map(|language| language.to_string()).collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.result.translations.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::HEADER;
+
+    // JsError only exists inside a JavaScript host, so only the success paths run natively
+    fn translations() -> WasmTranslations {
+        parse_translations_js(&format!("{}stops,stop_name,fr,Gare,stop_1,,\ntrips,trip_headsign,fr,Centre,,,Downtown\n", HEADER)).ok().unwrap()
+    }
+
+    #[test]
+    fn resolves_like_the_backend() {
+        let translations = translations();
+
+        assert_eq!(translations.length(), 2);
+        assert_eq!(translations.get_translation("stop_name", "stop_1", "fr-CA").as_deref(), Some("Gare"));
+        assert_eq!(translations.resolve("trip_headsign", Some("trip_1".to_string()), None, Some("Downtown".to_string()), "fr").as_deref(), Some("Centre"));
+    }
+
+    #[test]
+    fn accepts_fields_with_or_without_their_table() {
+        let translations = translations();
+
+        assert_eq!(translations.get_translation("stops.stop_name", "stop_1", "fr").as_deref(), Some("Gare"));
+        assert_eq!(translations.get_translation("routes.stop_name", "stop_1", "fr"), None);
+    }
+}
@@ -0,0 +1,121 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use crate::{from_reader, ParseOptions, SharedTranslations, TranslationResult, SNAPSHOT_FORMAT_VERSION};
+
+// Passed to the subscriber after every change to the watched file
+#[derive(Debug, Clone)]
+pub enum ReloadEvent {
+    // The new version, already live in the SharedTranslations
+    Reloaded(Arc<TranslationResult>),
+    // The file could not be read or parsed, the previous version stays live
+    Failed(String),
+}
+
+// Keeps watching until dropped
+pub struct TranslationWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+// A snapshot starts with its format version byte, which no csv does
+fn load_file(path: &Path, options: &ParseOptions) -> Result<TranslationResult, Box<dyn Error>> {
+    let bytes = fs::read(path)?;
+
+    match bytes.first() == Some(&SNAPSHOT_FORMAT_VERSION) {
+        true => TranslationResult::from_snapshot(&bytes),
+        false => Ok(from_reader(bytes.as_slice(), options)?.0),
+    }
+}
+
+// Loads a translations.txt or snapshot into shared now and again whenever it changes. The directory is watched
+// rather than the file, as editors and deploy scripts often replace a file by renaming a new one over it.
+pub fn watch_translations<P, F>(path: P, options: ParseOptions, shared: SharedTranslations, on_reload: F) -> Result<TranslationWatcher, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    F: Fn(ReloadEvent) + Send + 'static,
+{
+    let path: PathBuf = path.as_ref().to_path_buf();
+    shared.swap(load_file(&path, &options)?);
+
+    let file_name = path.file_name().ok_or("path has no file name")?.to_os_string();
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let reload_path = path.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+
+        let touches_file = event.paths.iter().any(|changed| changed.file_name() == Some(file_name.as_os_str()));
+        if !touches_file || !(event.kind.is_create() || event.kind.is_modify()) {
+            return;
+        }
+
+        match load_file(&reload_path, &options) {
+            Ok(translations) => {
+                shared.swap(translations);
+                on_reload(ReloadEvent::Reloaded(shared.load()));
+            }
+            Err(err) => on_reload(ReloadEvent::Failed(err.to_string())),
+        }
+    })?;
+
+    watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+
+    Ok(TranslationWatcher { _watcher: watcher })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use crate::test_util::HEADER;
+
+    #[test]
+    fn reloads_on_change() {
+        let directory = std::env::temp_dir().join(format!("gtfs-translations-watch-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("translations.txt");
+        fs::write(&path, format!("{}stops,stop_name,fr,Gare,stop_1,,\n", HEADER)).unwrap();
+
+        let shared = SharedTranslations::default();
+        let (sender, receiver) = mpsc::channel();
+        let _watcher = watch_translations(&path, ParseOptions::default(), shared.clone(), move |event| {
+            let _ = sender.send(event);
+        }).unwrap();
+        assert_eq!(shared.load().translations.len(), 1);
+
+        fs::write(&path, format!("{}stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,fr,Quai,stop_2,,\n", HEADER)).unwrap();
+
+        // a single write can arrive as several events, the last one sees the whole file
+        let mut reloaded = None;
+        while let Ok(event) = receiver.recv_timeout(Duration::from_secs(2)) {
+            if let ReloadEvent::Reloaded(translations) = event {
+                reloaded = Some(translations.translations.len());
+
+                if reloaded == Some(2) {
+                    break;
+                }
+            }
+        }
+
+        assert_eq!(reloaded, Some(2));
+        assert_eq!(shared.load().translations.len(), 2);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn fails_when_the_first_load_fails() {
+        let path = std::env::temp_dir().join(format!("gtfs-translations-watch-{}-missing", std::process::id())).join("translations.txt");
+        let shared = SharedTranslations::default();
+
+        assert!(watch_translations(&path, ParseOptions::default(), shared.clone(), |_| {}).is_err());
+        assert!(shared.load().translations.is_empty());
+    }
+}
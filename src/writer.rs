@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use crate::{enum_to_table_and_field, key_struct_to_options, translation_csv_text_to_translations};
+use crate::{RawTranslation, TranslationKey, TranslationLookup, TranslationResult};
+
+// Rows come out sorted by table, field, language and key so that written files diff cleanly.
+pub fn to_raw_translations(result: &TranslationResult) -> Vec<RawTranslation> {
+    let mut rows = result.translations.iter().map(|(lookup, translation)| {
+        let (table_name, field_name) = enum_to_table_and_field(&lookup.field);
+        let (record_id, record_sub_id, field_value) = key_struct_to_options(&lookup.key);
+
+        RawTranslation {
+            table_name: table_name.to_string(),
+            field_name: field_name.to_string(),
+            language: lookup.language.to_string(),
+            translation: translation.to_string(),
+            record_id,
+            record_sub_id,
+            field_value,
+            extras: HashMap::new(),
+        }
+    }).collect::<Vec<RawTranslation>>();
+
+    rows.sort_by(|a, b| {
+        (&a.table_name, &a.field_name, &a.language, &a.record_id, &a.record_sub_id, &a.field_value)
+            .cmp(&(&b.table_name, &b.field_name, &b.language, &b.record_id, &b.record_sub_id, &b.field_value))
+    });
+
+    rows
+}
+
+pub fn write_raw_translations<W: Write>(rows: &[RawTranslation], writer: W) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_writer(writer);
+
+    // RawTranslation fields are declared in the column order of the spec, so serde writes the header for us
+    if rows.is_empty() {
+        wtr.write_record(["table_name", "field_name", "language", "translation", "record_id", "record_sub_id", "field_value"])?;
+    }
+
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+
+    wtr.flush()?;
+
+    Ok(())
+}
+
+pub fn write_translations_csv<W: Write>(result: &TranslationResult, writer: W) -> Result<(), Box<dyn Error>> {
+    write_raw_translations(&to_raw_translations(result), writer)
+}
+
+pub fn to_translations_csv(result: &TranslationResult) -> String {
+    let mut buffer: Vec<u8> = vec![];
+
+    write_translations_csv(result, &mut buffer).expect("writing csv into a Vec cannot fail");
+
+    String::from_utf8(buffer).expect("csv writer only emits the utf-8 it was given")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundTripError {
+    // Entries that would be missing or different after reading the written file back
+    pub lost: Vec<TranslationLookup>,
+}
+
+impl fmt::Display for RoundTripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} translations cannot be written to translations.txt without loss", self.lost.len())
+    }
+}
+
+impl Error for RoundTripError {}
+
+// Empty key parts are written as empty cells, which read back as absent columns
+fn key_is_representable(key: &TranslationKey) -> bool {
+    match key {
+        TranslationKey::Record(record_id) => !record_id.is_empty(),
+        TranslationKey::RecordSub((record_id, record_sub_id)) => !record_id.is_empty() && !record_sub_id.is_empty(),
+        TranslationKey::Value(field_value) => !field_value.is_empty(),
+    }
+}
+
+// Writes translations.txt and verifies that parsing the output yields exactly the same translations.
+pub fn to_translations_csv_lossless(result: &TranslationResult) -> Result<String, RoundTripError> {
+    let written = to_translations_csv(result);
+
+    let reparsed = translation_csv_text_to_translations(&written).map(|reparsed| reparsed.translations).unwrap_or_default();
+
+    let mut lost = result.translations.iter()
+        .filter(|(lookup, translation)| !key_is_representable(&lookup.key) || reparsed.get(*lookup) != Some(*translation))
+        .map(|(lookup, _)| lookup.clone())
+        .collect::<Vec<TranslationLookup>>();
+
+    if lost.is_empty() && reparsed.len() == result.translations.len() {
+        Ok(written)
+    } else {
+        lost.sort_by_key(|lookup| format!("{:?}", lookup));
+        Err(RoundTripError { lost })
+    }
+}
+
+// Normalizes a translations.txt: parse, write back out, and guarantee the rewritten file parses identically.
+pub fn rewrite_translations_csv(data: &str) -> Result<String, Box<dyn Error>> {
+    let parsed = translation_csv_text_to_translations(data)?;
+
+    Ok(to_translations_csv_lossless(&parsed)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{translations, HEADER};
+
+    #[test]
+    fn writes_key_columns_and_quotes() {
+        let result = translations("stops,stop_name,fr,\"Gare, Centrale\",stop_1,,\n\
+            stop_times,stop_headsign,de,Zentrum,trip_1,5,\n\
+            routes,route_long_name,ja,中央線,,,Chuo Line\n");
+
+        assert_eq!(to_translations_csv(&result), format!("{}routes,route_long_name,ja,中央線,,,Chuo Line\n\
+            stop_times,stop_headsign,de,Zentrum,trip_1,5,\n\
+            stops,stop_name,fr,\"Gare, Centrale\",stop_1,,\n", HEADER));
+    }
+
+    #[test]
+    fn writes_the_header_without_translations() {
+        assert_eq!(to_translations_csv(&TranslationResult::default()), HEADER);
+    }
+
+    #[test]
+    fn rejects_unrepresentable_keys() {
+        let mut result = translations("stops,stop_name,fr,Gare,stop_1,,\n");
+        assert!(to_translations_csv_lossless(&result).is_ok());
+
+        let lookup = TranslationLookup {
+            language: language_tags::LanguageTag::parse("fr").unwrap(),
+            field: crate::TranslatableField::Stops(crate::StopFields::Name),
+            key: TranslationKey::Value(String::new()),
+        };
+        result.translations.insert(lookup.clone(), "Vide".into());
+
+        assert_eq!(to_translations_csv_lossless(&result).unwrap_err().lost, vec![lookup]);
+    }
+
+    mod round_trip {
+        use super::super::*;
+        use proptest::prelude::*;
+        use crate::test_util::any_raw_translation;
+
+        proptest! {
+            #[test]
+            fn parse_write_parse_is_lossless(rows in proptest::collection::vec(any_raw_translation(), 0..40)) {
+                let mut input: Vec<u8> = vec![];
+                write_raw_translations(&rows, &mut input).unwrap();
+                let input = String::from_utf8(input).unwrap();
+
+                let parsed = translation_csv_text_to_translations(&input).unwrap();
+                let rewritten = to_translations_csv_lossless(&parsed).unwrap();
+                let reparsed = translation_csv_text_to_translations(&rewritten).unwrap();
+
+                prop_assert_eq!(reparsed.translations, parsed.translations);
+                prop_assert_eq!(rewrite_translations_csv(&rewritten).unwrap(), rewritten);
+            }
+        }
+    }
+}
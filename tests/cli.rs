@@ -0,0 +1,96 @@
+use std::io::{Cursor, Read, Write};
+use std::path::PathBuf;
+use assert_cmd::Command;
+use gtfs_translations::translation_csv_text_to_translations;
+
+const HEADER: &str = "table_name,field_name,language,translation,record_id,record_sub_id,field_value\n";
+
+const STOPS: &str = "stop_id,stop_name\nstop_1,Central\nstop_2,Harbour\n";
+
+// A directory of its own for every test, as they run at the same time
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("gtfs-translations-cli-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_feed(path: &PathBuf, files: &[(&str, &str)]) {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (name, contents) in files {
+        writer.start_file(*name, zip::write::FileOptions::default()).unwrap();
+        writer.write_all(contents.as_bytes()).unwrap();
+    }
+
+    std::fs::write(path, writer.finish().unwrap().into_inner()).unwrap();
+}
+
+fn cli() -> Command {
+    Command::cargo_bin("gtfs-translations").unwrap()
+}
+
+#[test]
+fn validate_exits_by_severity() {
+    let dir = temp_dir("validate");
+    let clean = dir.join("clean.txt");
+    let empty = dir.join("empty.txt");
+    std::fs::write(&clean, format!("{}stops,stop_name,fr,Gare,stop_1,,\n", HEADER)).unwrap();
+    std::fs::write(&empty, format!("{}stops,stop_name,fr,,stop_1,,\n", HEADER)).unwrap();
+
+    cli().args(["validate", clean.to_str().unwrap()]).assert().code(0);
+    // an empty translation is only a warning, unless warnings count as errors
+    cli().args(["validate", empty.to_str().unwrap()]).assert().code(0);
+    cli().args(["validate", "--strict", empty.to_str().unwrap()]).assert().code(1);
+    cli().args(["validate", dir.join("missing.txt").to_str().unwrap()]).assert().code(2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn convert_round_trips_through_wide_csv() {
+    let dir = temp_dir("convert");
+    let standard = dir.join("translations.txt");
+    let wide = dir.join("wide.csv");
+    let text = format!("{}stops,stop_name,fr,Gare,stop_1,,\nstops,stop_name,de,Bahnhof,stop_1,,\ntrips,trip_headsign,fr,Centre,,,Downtown\n", HEADER);
+    std::fs::write(&standard, &text).unwrap();
+
+    cli().args(["convert", standard.to_str().unwrap(), "--to", "wide", "-o", wide.to_str().unwrap()]).assert().success();
+    let output = cli().args(["convert", wide.to_str().unwrap(), "--from", "wide"]).assert().success().get_output().stdout.clone();
+
+    let round_tripped = translation_csv_text_to_translations(&String::from_utf8(output).unwrap()).unwrap();
+    assert_eq!(round_tripped.translations, translation_csv_text_to_translations(&text).unwrap().translations);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn reports_coverage_of_a_feed() {
+    let dir = temp_dir("report");
+    let feed = dir.join("feed.zip");
+    write_feed(&feed, &[("stops.txt", STOPS), ("translations.txt", &format!("{}stops,stop_name,fr,Gare,stop_1,,\n", HEADER))]);
+
+    let output = cli().args(["report", feed.to_str().unwrap()]).assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("1/2 (50.0%)"), "{}", output);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn localizes_a_feed() {
+    let dir = temp_dir("localize");
+    let feed = dir.join("feed.zip");
+    let localized = dir.join("feed-fr.zip");
+    write_feed(&feed, &[("stops.txt", STOPS), ("translations.txt", &format!("{}stops,stop_name,fr,Gare,stop_1,,\n", HEADER))]);
+
+    cli().args(["localize", feed.to_str().unwrap(), "--lang", "fr", "-o", localized.to_str().unwrap()]).assert().success();
+    cli().args(["localize", feed.to_str().unwrap(), "--lang", "fr", "-o", feed.to_str().unwrap()]).assert().code(2);
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&localized).unwrap()).unwrap();
+    let mut stops = String::new();
+    archive.by_name("stops.txt").unwrap().read_to_string(&mut stops).unwrap();
+    assert_eq!(stops, "stop_id,stop_name\nstop_1,Gare\nstop_2,Harbour\n");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}